@@ -0,0 +1,647 @@
+/// a TurboPlonk constraint system that keeps its gates row by row, for circuits over fields without a large radix-2 domain
+///
+/// Jellyfish hands out a finalized circuit only as polynomials over a radix-2 domain, which the scalar field of Grumpkin
+/// (the base field of BN254, of two-adicity 1) does not have, here the PLONK shape, instance and witness are read off the
+/// rows directly, while the gadgets keep the names and gate layouts of their Jellyfish counterparts
+///
+use ark_ff::{BigInteger, PrimeField};
+use jf_relation::{
+    constants::{GATE_WIDTH, N_MUL_SELECTORS},
+    Variable,
+};
+
+use crate::{
+    error::MyError,
+    plonk::{PLONKInstance, PLONKShape, PLONKWitness},
+    traits::{Group, ROConstantsTrait, ROTrait},
+    CommitmentKey,
+};
+
+/// the in-circuit counterpart of `ROTrait`, absorbing variables of a circuit over the base field
+pub trait ROCircuitTrait<BaseField: PrimeField> {
+    type Constants: ROConstantsTrait<BaseField> + Clone;
+
+    /// Initializes the hash function
+    fn new(constants: Self::Constants) -> Self;
+
+    /// Adds a variable to the internal state
+    fn absorb(&mut self, e: Variable);
+
+    /// Returns the little-endian bits of a challenge of `num_bits` by hashing the internal state
+    fn squeeze(
+        &mut self,
+        cs: &mut PlonkishCircuit<BaseField>,
+        num_bits: usize,
+    ) -> Result<Vec<Variable>, MyError>;
+}
+
+/// the random oracle of `Group` inside a circuit over its base field, as an add-on only the augmented circuits need
+pub trait CircuitGroup: Group {
+    /// the in-circuit counterpart of `RO`, sharing its constants
+    type ROCircuit: ROCircuitTrait<
+        <Self as Group>::BaseField,
+        Constants = <Self::RO as ROTrait<
+            <Self as Group>::BaseField,
+            <Self as Group>::ScalarField,
+        >>::Constants,
+    >;
+}
+
+/// selectors of a single TurboPlonk gate,
+/// q_lc·w + q_mul + q_hash·w^5 + q_ecc·Πw + q_c = q_o·w_o
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Gate<F: PrimeField> {
+    pub q_lc: [F; GATE_WIDTH],
+    pub q_mul: [F; N_MUL_SELECTORS],
+    pub q_hash: [F; GATE_WIDTH],
+    pub q_ecc: F,
+    pub q_o: F,
+    pub q_c: F,
+}
+
+impl<F: PrimeField> Default for Gate<F> {
+    fn default() -> Self {
+        Gate {
+            q_lc: [F::ZERO; GATE_WIDTH],
+            q_mul: [F::ZERO; N_MUL_SELECTORS],
+            q_hash: [F::ZERO; GATE_WIDTH],
+            q_ecc: F::ZERO,
+            q_o: F::ZERO,
+            q_c: F::ZERO,
+        }
+    }
+}
+
+impl<F: PrimeField> Gate<F> {
+    /// left hand side of the gate minus its output, zero on a satisfied row
+    fn eval(&self, w: &[F; GATE_WIDTH + 1], pi: F) -> F {
+        let lc = (0..GATE_WIDTH).fold(pi + self.q_c, |acc, i| {
+            acc + self.q_lc[i] * w[i] + self.q_hash[i] * w[i].pow([5u64])
+        });
+        let mul = self.q_mul[0] * w[0] * w[1] + self.q_mul[1] * w[2] * w[3];
+        let ecc = self.q_ecc * w[0] * w[1] * w[2] * w[3] * w[4];
+        lc + mul + ecc - self.q_o * w[GATE_WIDTH]
+    }
+}
+
+/// A TurboPlonk circuit with five wires per gate, public inputs occupy the leading rows
+#[derive(Clone, Debug)]
+pub struct PlonkishCircuit<F: PrimeField> {
+    witness: Vec<F>,
+    gates: Vec<(Gate<F>, [Variable; GATE_WIDTH + 1])>,
+    pub_inputs: Vec<Variable>,
+}
+
+impl<F: PrimeField> Default for PlonkishCircuit<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F: PrimeField> PlonkishCircuit<F> {
+    /// creates a circuit holding the constant variables zero and one, the same as `new_turbo_plonk` of Jellyfish
+    pub fn new() -> Self {
+        let mut circuit = PlonkishCircuit {
+            witness: vec![],
+            gates: vec![],
+            pub_inputs: vec![],
+        };
+        circuit.create_constant_variable(F::ZERO).unwrap();
+        circuit.create_constant_variable(F::ONE).unwrap();
+        circuit
+    }
+
+    fn check_var(&self, var: Variable) -> Result<(), MyError> {
+        if var < self.witness.len() {
+            Ok(())
+        } else {
+            Err(MyError::CircuitError)
+        }
+    }
+
+    pub fn num_gates(&self) -> usize {
+        self.pub_inputs.len() + self.gates.len()
+    }
+
+    pub fn num_vars(&self) -> usize {
+        self.witness.len()
+    }
+
+    pub fn num_inputs(&self) -> usize {
+        self.pub_inputs.len()
+    }
+
+    pub fn zero(&self) -> Variable {
+        0
+    }
+
+    pub fn one(&self) -> Variable {
+        1
+    }
+
+    pub fn witness(&self, var: Variable) -> Result<F, MyError> {
+        self.check_var(var)?;
+        Ok(self.witness[var])
+    }
+
+    pub fn public_input(&self) -> Vec<F> {
+        self.pub_inputs.iter().map(|v| self.witness[*v]).collect()
+    }
+
+    /// inserts a gate over the given wires, the last one being the output wire
+    pub fn insert_gate(
+        &mut self,
+        wires: &[Variable; GATE_WIDTH + 1],
+        gate: Gate<F>,
+    ) -> Result<(), MyError> {
+        wires.iter().try_for_each(|v| self.check_var(*v))?;
+        self.gates.push((gate, *wires));
+        Ok(())
+    }
+
+    pub fn create_variable(&mut self, val: F) -> Result<Variable, MyError> {
+        self.witness.push(val);
+        Ok(self.witness.len() - 1)
+    }
+
+    pub fn create_constant_variable(&mut self, val: F) -> Result<Variable, MyError> {
+        let var = self.create_variable(val)?;
+        self.enforce_constant(var, val)?;
+        Ok(var)
+    }
+
+    pub fn create_public_variable(&mut self, val: F) -> Result<Variable, MyError> {
+        let var = self.create_variable(val)?;
+        self.set_variable_public(var)?;
+        Ok(var)
+    }
+
+    /// public inputs get an io gate of their own in order of being set public
+    pub fn set_variable_public(&mut self, var: Variable) -> Result<(), MyError> {
+        self.check_var(var)?;
+        self.pub_inputs.push(var);
+        Ok(())
+    }
+
+    pub fn create_boolean_variable(&mut self, val: bool) -> Result<Variable, MyError> {
+        let var = self.create_variable(if val { F::ONE } else { F::ZERO })?;
+        self.enforce_bool(var)?;
+        Ok(var)
+    }
+
+    /// var = constant
+    pub fn enforce_constant(&mut self, var: Variable, constant: F) -> Result<(), MyError> {
+        let zero = self.zero();
+        self.insert_gate(
+            &[zero, zero, zero, zero, var],
+            Gate {
+                q_o: F::ONE,
+                q_c: constant,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// a = b
+    pub fn enforce_equal(&mut self, a: Variable, b: Variable) -> Result<(), MyError> {
+        let zero = self.zero();
+        self.insert_gate(
+            &[a, b, zero, zero, zero],
+            Gate {
+                q_lc: [F::ONE, -F::ONE, F::ZERO, F::ZERO],
+                ..Default::default()
+            },
+        )
+    }
+
+    /// a * a = a
+    pub fn enforce_bool(&mut self, a: Variable) -> Result<(), MyError> {
+        let zero = self.zero();
+        self.insert_gate(
+            &[a, a, zero, zero, zero],
+            Gate {
+                q_lc: [-F::ONE, F::ZERO, F::ZERO, F::ZERO],
+                q_mul: [F::ONE, F::ZERO],
+                ..Default::default()
+            },
+        )
+    }
+
+    /// output = Σ coeffs[i] * wires_in[i]
+    pub fn lc(
+        &mut self,
+        wires_in: &[Variable; GATE_WIDTH],
+        coeffs: &[F; GATE_WIDTH],
+    ) -> Result<Variable, MyError> {
+        wires_in.iter().try_for_each(|v| self.check_var(*v))?;
+        let val = (0..GATE_WIDTH).fold(F::ZERO, |acc, i| {
+            acc + coeffs[i] * self.witness[wires_in[i]]
+        });
+        let out = self.create_variable(val)?;
+        self.insert_gate(
+            &[wires_in[0], wires_in[1], wires_in[2], wires_in[3], out],
+            Gate {
+                q_lc: *coeffs,
+                q_o: F::ONE,
+                ..Default::default()
+            },
+        )?;
+        Ok(out)
+    }
+
+    /// output = q_muls[0] * wires_in[0] * wires_in[1] + q_muls[1] * wires_in[2] * wires_in[3]
+    pub fn mul_add(
+        &mut self,
+        wires_in: &[Variable; GATE_WIDTH],
+        q_muls: &[F; N_MUL_SELECTORS],
+    ) -> Result<Variable, MyError> {
+        wires_in.iter().try_for_each(|v| self.check_var(*v))?;
+        let w = wires_in.map(|v| self.witness[v]);
+        let out = self.create_variable(q_muls[0] * w[0] * w[1] + q_muls[1] * w[2] * w[3])?;
+        self.insert_gate(
+            &[wires_in[0], wires_in[1], wires_in[2], wires_in[3], out],
+            Gate {
+                q_mul: *q_muls,
+                q_o: F::ONE,
+                ..Default::default()
+            },
+        )?;
+        Ok(out)
+    }
+
+    pub fn add(&mut self, a: Variable, b: Variable) -> Result<Variable, MyError> {
+        let zero = self.zero();
+        self.lc(&[a, b, zero, zero], &[F::ONE, F::ONE, F::ZERO, F::ZERO])
+    }
+
+    pub fn sub(&mut self, a: Variable, b: Variable) -> Result<Variable, MyError> {
+        let zero = self.zero();
+        self.lc(&[a, b, zero, zero], &[F::ONE, -F::ONE, F::ZERO, F::ZERO])
+    }
+
+    pub fn mul(&mut self, a: Variable, b: Variable) -> Result<Variable, MyError> {
+        let zero = self.zero();
+        self.mul_add(&[a, b, zero, zero], &[F::ONE, F::ZERO])
+    }
+
+    pub fn add_constant(&mut self, a: Variable, c: &F) -> Result<Variable, MyError> {
+        let zero = self.zero();
+        let out = self.create_variable(self.witness(a)? + c)?;
+        self.insert_gate(
+            &[a, zero, zero, zero, out],
+            Gate {
+                q_lc: [F::ONE, F::ZERO, F::ZERO, F::ZERO],
+                q_o: F::ONE,
+                q_c: *c,
+                ..Default::default()
+            },
+        )?;
+        Ok(out)
+    }
+
+    pub fn mul_constant(&mut self, a: Variable, c: &F) -> Result<Variable, MyError> {
+        let zero = self.zero();
+        self.lc(&[a, zero, zero, zero], &[*c, F::ZERO, F::ZERO, F::ZERO])
+    }
+
+    /// output = a^5, through the q_hash selector
+    pub fn power_5_gen(&mut self, a: Variable) -> Result<Variable, MyError> {
+        let zero = self.zero();
+        let out = self.create_variable(self.witness(a)?.pow([5u64]))?;
+        self.insert_gate(
+            &[a, zero, zero, zero, out],
+            Gate {
+                q_hash: [F::ONE, F::ZERO, F::ZERO, F::ZERO],
+                q_o: F::ONE,
+                ..Default::default()
+            },
+        )?;
+        Ok(out)
+    }
+
+    /// output = Σ q_lc_i * w_i + Σ q_hash_i * w_i^5 + q_c in a single gate, a round of an SPN permutation
+    /// over a state of `GATE_WIDTH` elements
+    pub fn lc_power_5(
+        &mut self,
+        wires_in: &[Variable; GATE_WIDTH],
+        q_lc: &[F; GATE_WIDTH],
+        q_hash: &[F; GATE_WIDTH],
+        q_c: &F,
+    ) -> Result<Variable, MyError> {
+        wires_in.iter().try_for_each(|v| self.check_var(*v))?;
+        let val = (0..GATE_WIDTH).fold(*q_c, |acc, i| {
+            let w = self.witness[wires_in[i]];
+            acc + q_lc[i] * w + q_hash[i] * w.pow([5u64])
+        });
+        let out = self.create_variable(val)?;
+        self.insert_gate(
+            &[wires_in[0], wires_in[1], wires_in[2], wires_in[3], out],
+            Gate {
+                q_lc: *q_lc,
+                q_hash: *q_hash,
+                q_c: *q_c,
+                q_o: F::ONE,
+                ..Default::default()
+            },
+        )?;
+        Ok(out)
+    }
+
+    /// output = Σ elements, accumulating three more elements per gate
+    pub fn sum(&mut self, elements: &[Variable]) -> Result<Variable, MyError> {
+        let zero = self.zero();
+        let mut acc = zero;
+        for chunk in elements.chunks(GATE_WIDTH - 1) {
+            let mut wires = [zero; GATE_WIDTH];
+            wires[0] = acc;
+            wires[1..=chunk.len()].copy_from_slice(chunk);
+            acc = self.lc(&wires, &[F::ONE; GATE_WIDTH])?;
+        }
+        Ok(acc)
+    }
+
+    /// output = x_0 if b = 0, x_1 if b = 1, with b assumed boolean
+    pub fn conditional_select(
+        &mut self,
+        b: Variable,
+        x_0: Variable,
+        x_1: Variable,
+    ) -> Result<Variable, MyError> {
+        let (b_val, x_0_val, x_1_val) = (self.witness(b)?, self.witness(x_0)?, self.witness(x_1)?);
+        let out = self.create_variable(x_0_val + b_val * (x_1_val - x_0_val))?;
+        // x_0 - b * x_0 + b * x_1 = out
+        self.insert_gate(
+            &[b, x_0, b, x_1, out],
+            Gate {
+                q_lc: [F::ZERO, F::ONE, F::ZERO, F::ZERO],
+                q_mul: [-F::ONE, F::ONE],
+                q_o: F::ONE,
+                ..Default::default()
+            },
+        )?;
+        Ok(out)
+    }
+
+    /// little-endian boolean decomposition of a into `length` bits, which also range-checks a
+    pub fn unpack(&mut self, a: Variable, length: usize) -> Result<Vec<Variable>, MyError> {
+        let bits = self.witness(a)?.into_bigint().to_bits_le();
+        if bits.iter().skip(length).any(|b| *b) {
+            return Err(MyError::CircuitError);
+        }
+        let bit_vars = (0..length)
+            .map(|i| self.create_boolean_variable(bits.get(i).copied().unwrap_or(false)))
+            .collect::<Result<Vec<Variable>, MyError>>()?;
+        let acc = self.from_bits_le(&bit_vars)?;
+        self.enforce_equal(acc, a)?;
+        Ok(bit_vars)
+    }
+
+    /// a < 2^bit_len
+    pub fn enforce_in_range(&mut self, a: Variable, bit_len: usize) -> Result<(), MyError> {
+        self.unpack(a, bit_len).map(|_| ())
+    }
+
+    /// Σ 2^i * bits[i], the bits are not checked to be boolean
+    pub fn from_bits_le(&mut self, bits: &[Variable]) -> Result<Variable, MyError> {
+        let zero = self.zero();
+        let mut acc = zero;
+        // the accumulator goes into the first wire, three more bits per gate from the most significant down
+        for chunk in bits.rchunks(GATE_WIDTH - 1) {
+            let mut wires = [zero; GATE_WIDTH];
+            let mut coeffs = [F::ZERO; GATE_WIDTH];
+            wires[0] = acc;
+            coeffs[0] = F::from(2u64).pow([chunk.len() as u64]);
+            chunk.iter().enumerate().for_each(|(i, b)| {
+                wires[i + 1] = *b;
+                coeffs[i + 1] = F::from(1u64 << i);
+            });
+            acc = self.lc(&wires, &coeffs)?;
+        }
+        Ok(acc)
+    }
+
+    /// output = Σ coeffs * vars over any number of terms, chaining the accumulator through the first wire
+    pub fn linear_combination(&mut self, terms: &[(Variable, F)]) -> Result<Variable, MyError> {
+        let zero = self.zero();
+        let mut acc = (zero, F::ZERO);
+        for chunk in terms.chunks(GATE_WIDTH - 1) {
+            let mut wires = [zero; GATE_WIDTH];
+            let mut coeffs = [F::ZERO; GATE_WIDTH];
+            (wires[0], coeffs[0]) = acc;
+            chunk.iter().enumerate().for_each(|(i, (v, c))| {
+                wires[i + 1] = *v;
+                coeffs[i + 1] = *c;
+            });
+            acc = (self.lc(&wires, &coeffs)?, F::ONE);
+        }
+        Ok(acc.0)
+    }
+
+    /// 1 - b
+    pub fn logic_neg(&mut self, b: Variable) -> Result<Variable, MyError> {
+        let (zero, one) = (self.zero(), self.one());
+        self.lc(&[one, b, zero, zero], &[F::ONE, -F::ONE, F::ZERO, F::ZERO])
+    }
+
+    /// a * b = 0
+    pub fn enforce_mul_zero(&mut self, a: Variable, b: Variable) -> Result<(), MyError> {
+        let zero = self.zero();
+        self.insert_gate(
+            &[a, b, zero, zero, zero],
+            Gate {
+                q_mul: [F::ONE, F::ZERO],
+                ..Default::default()
+            },
+        )
+    }
+
+    /// a boolean variable that is one iff a = 0
+    pub fn is_zero(&mut self, a: Variable) -> Result<Variable, MyError> {
+        let zero = self.zero();
+        let a_val = self.witness(a)?;
+        let inv = self.create_variable(a_val.inverse().unwrap_or(F::ZERO))?;
+        let b = self.create_variable(if a_val.is_zero() { F::ONE } else { F::ZERO })?;
+        // a * inv + b = 1 and a * b = 0
+        self.insert_gate(
+            &[a, inv, b, zero, zero],
+            Gate {
+                q_lc: [F::ZERO, F::ZERO, F::ONE, F::ZERO],
+                q_mul: [F::ONE, F::ZERO],
+                q_c: -F::ONE,
+                ..Default::default()
+            },
+        )?;
+        self.enforce_mul_zero(a, b)?;
+        Ok(b)
+    }
+
+    /// little-endian boolean decomposition of a into the canonical bits of its field element, i.e. the bits are below the modulus
+    pub fn unpack_strict(&mut self, a: Variable) -> Result<Vec<Variable>, MyError> {
+        let bits = self.unpack(a, F::MODULUS_BIT_SIZE as usize)?;
+        let modulus_minus_one = (-F::ONE).into_bigint().to_bits_le();
+        // from the most significant bit down, `eq` tracks whether the bits so far equal those of the modulus minus one,
+        // while equal a bit can not be set where the modulus minus one has none
+        let mut eq = self.one();
+        for (b, m) in bits.iter().zip(modulus_minus_one).rev() {
+            if m {
+                eq = self.mul(eq, *b)?;
+            } else {
+                self.enforce_mul_zero(eq, *b)?;
+            }
+        }
+        Ok(bits)
+    }
+
+    /// rows of the circuit, io gates first and zero-padded up to a power of two
+    fn rows(&self) -> Vec<(Gate<F>, [Variable; GATE_WIDTH + 1])> {
+        let zero = self.zero();
+        let io_gates = self.pub_inputs.iter().map(|v| {
+            (
+                Gate {
+                    q_o: F::ONE,
+                    ..Default::default()
+                },
+                [zero, zero, zero, zero, *v],
+            )
+        });
+        let mut rows = io_gates
+            .chain(self.gates.iter().cloned())
+            .collect::<Vec<_>>();
+        rows.resize(
+            rows.len().next_power_of_two(),
+            (Gate::default(), [zero; GATE_WIDTH + 1]),
+        );
+        rows
+    }
+
+    /// checks every gate, with the public inputs on the leading rows
+    pub fn check_circuit_satisfiability(&self) -> Result<(), MyError> {
+        let pub_input = self.public_input();
+        self.rows()
+            .iter()
+            .enumerate()
+            .try_for_each(|(i, (gate, wires))| {
+                let pi = pub_input.get(i).copied().unwrap_or(F::ZERO);
+                if gate.eval(&wires.map(|v| self.witness[v]), pi) == F::ZERO {
+                    Ok(())
+                } else {
+                    Err(MyError::UnSatIndex(i))
+                }
+            })
+    }
+
+    /// the shape of the circuit, every occurrence of a variable is wired to the next one in a cycle
+    pub fn plonk_shape<G: Group<ScalarField = F>>(&self) -> Result<PLONKShape<G>, MyError> {
+        let rows = self.rows();
+        let n = rows.len();
+
+        let mut occurrences = vec![vec![]; self.num_vars()];
+        (0..GATE_WIDTH + 1).for_each(|i| {
+            rows.iter()
+                .enumerate()
+                .for_each(|(j, (_, wires))| occurrences[wires[i]].push(i * n + j))
+        });
+        let mut sigma = vec![vec![0; n]; GATE_WIDTH + 1];
+        occurrences.iter().for_each(|positions| {
+            positions.iter().enumerate().for_each(|(k, p)| {
+                sigma[p / n][p % n] = positions[(k + 1) % positions.len()];
+            })
+        });
+
+        let q_lc = (0..GATE_WIDTH)
+            .map(|i| rows.iter().map(|(g, _)| g.q_lc[i]).collect())
+            .collect::<Vec<Vec<F>>>();
+        let q_mul = (0..N_MUL_SELECTORS)
+            .map(|i| rows.iter().map(|(g, _)| g.q_mul[i]).collect())
+            .collect::<Vec<Vec<F>>>();
+        let q_hash = (0..GATE_WIDTH)
+            .map(|i| rows.iter().map(|(g, _)| g.q_hash[i]).collect())
+            .collect::<Vec<Vec<F>>>();
+        let q_ecc = rows.iter().map(|(g, _)| g.q_ecc).collect::<Vec<F>>();
+        let q_o = rows.iter().map(|(g, _)| g.q_o).collect::<Vec<F>>();
+        let q_c = rows.iter().map(|(g, _)| g.q_c).collect::<Vec<F>>();
+
+        PLONKShape::new(
+            n,
+            GATE_WIDTH + 1,
+            self.num_inputs(),
+            &q_c,
+            &q_lc,
+            &q_mul,
+            &q_ecc,
+            &q_hash,
+            &q_o,
+            &sigma,
+        )
+    }
+
+    /// the instance and witness of the circuit, committing to the witness with `ck`
+    pub fn plonk_instance_and_witness<G: Group<ScalarField = F>>(
+        &self,
+        S: &PLONKShape<G>,
+        ck: &CommitmentKey<G>,
+    ) -> Result<(PLONKInstance<G>, PLONKWitness<G>), MyError> {
+        let rows = self.rows();
+        if rows.len() != S.num_cons {
+            return Err(MyError::CircuitError);
+        }
+        let wires = (0..GATE_WIDTH + 1)
+            .map(|i| rows.iter().map(|(_, w)| self.witness[w[i]]).collect())
+            .collect::<Vec<Vec<F>>>();
+
+        let W = PLONKWitness::new(S, &wires)?;
+        let U = PLONKInstance::new(S, &W.commit(ck), &self.public_input())?;
+        Ok((U, W))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::grumpkin::Grumpkin;
+    use crate::traits::CommitmentEngineTrait;
+    use ark_ff::{Field, UniformRand};
+    use jf_utils::test_rng;
+
+    type F = <Grumpkin as Group>::ScalarField;
+
+    #[test]
+    fn test_plonkish_circuit() {
+        let rng = &mut test_rng();
+        let (x, z) = (F::rand(rng), F::rand(rng));
+
+        // y = (x * z + x)^5 + 2 * x - z, with x and y public
+        let mut cs = PlonkishCircuit::<F>::new();
+        let x_var = cs.create_public_variable(x).unwrap();
+        let z_var = cs.create_variable(z).unwrap();
+        let xz_var = cs.mul(x_var, z_var).unwrap();
+        let sum_var = cs.add(xz_var, x_var).unwrap();
+        let pow_var = cs.power_5_gen(sum_var).unwrap();
+        let zero_var = cs.zero();
+        let y_var = cs
+            .lc(
+                &[pow_var, x_var, z_var, zero_var],
+                &[F::ONE, F::from(2u64), -F::ONE, F::ZERO],
+            )
+            .unwrap();
+        cs.set_variable_public(y_var).unwrap();
+        let bits = cs.unpack(z_var, F::MODULUS_BIT_SIZE as usize).unwrap();
+        let b = cs.conditional_select(bits[0], x_var, z_var).unwrap();
+        cs.check_circuit_satisfiability().unwrap();
+        assert_eq!(
+            cs.witness(b).unwrap(),
+            if z.into_bigint().is_odd() { z } else { x }
+        );
+
+        let S: PLONKShape<Grumpkin> = cs.plonk_shape().unwrap();
+        let ck = <Grumpkin as Group>::CE::setup(rng, S.num_cons);
+        let (U, mut W) = cs.plonk_instance_and_witness(&S, &ck).unwrap();
+        assert_eq!(U.X, vec![x, (x * z + x).pow([5u64]) + x + x - z]);
+        S.is_sat(&ck, &U, &W).unwrap();
+
+        // x on the right input of x * z + x
+        W.W[1][S.num_public_input + 3] += F::ONE;
+        let U = PLONKInstance::new(&S, &W.commit(&ck), &U.X).unwrap();
+        assert!(S.is_sat(&ck, &U, &W).is_err());
+    }
+}
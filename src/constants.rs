@@ -0,0 +1,5 @@
+//! Global constants shared by the native folding scheme and its circuits
+pub(crate) const NUM_CHALLENGE_BITS: usize = 128;
+pub(crate) const BN_LIMB_WIDTH: usize = 64;
+pub(crate) const BN_N_LIMBS: usize = 4;
+pub(crate) const NUM_HASH_BITS: usize = 250;
@@ -28,4 +28,67 @@ pub enum MyError {
     PublicIntputError,
     #[error("Selector error")]
     SelectorError,
+    /// the gate equation does not hold at the given row
+    #[error("unsatisfied gate at row {0}")]
+    UnSatIndex(usize),
+    /// the wire at the given (wire, row) position breaks a copy constraint
+    #[error("unsatisfied copy constraint at wire {0} row {1}")]
+    UnSatCopy(usize, usize),
+    /// the powers of the compressed mode break their recurrence at the given row
+    #[error("unsatisfied power of beta at row {0}")]
+    UnSatPower(usize),
+    /// the compressed error does not match the gates combined with the powers of beta
+    #[error("unsatisfied compressed error")]
+    UnSatCompressed,
+    /// the β of an accumulated instance does not hold log(num_cons) challenges
+    #[error("invalid beta length")]
+    InvalidBetaLength,
+    /// sigma is not a permutation over the wire positions
+    #[error("permutation error")]
+    PermutationError,
+    /// the vector does not fit the commitment key
+    #[error("invalid input length")]
+    InvalidInputLength,
+    /// the evaluation argument does not verify
+    #[error("invalid PCS")]
+    InvalidPCS,
+    /// the circuit is not a finalized TurboPlonk circuit
+    #[error("circuit error")]
+    CircuitError,
+    /// the number of steps does not match the recursive proof
+    #[error("invalid number of steps")]
+    InvalidNumSteps,
+    /// the initial input does not match the arity of the step circuit
+    #[error("invalid initial input length")]
+    InvalidInitialInputLength,
+    /// the recursive proof does not verify
+    #[error("proof verification error")]
+    ProofVerifyError,
+    /// the lookup does not fit the shape, or a looked-up value is missing from the table
+    #[error("lookup error")]
+    LookupError,
+    /// the inverse of the looked-up value breaks its relation at the given row
+    #[error("unsatisfied lookup at row {0}")]
+    UnSatLookup(usize),
+    /// the inverse of the table entry breaks its relation at the given row
+    #[error("unsatisfied table at row {0}")]
+    UnSatTable(usize),
+    /// the inverses of the looked-up values and of the table entries sum up differently
+    #[error("unsatisfied lookup sum")]
+    UnSatLookupSum,
+    /// the memory does not fit the shape, or an access reads another value than the last one written
+    #[error("memory error")]
+    MemoryError,
+    /// the inverse of the read or written tuple breaks its relation at the given row
+    #[error("unsatisfied memory access at row {0}")]
+    UnSatMemoryAccess(usize),
+    /// the inverse of the initial or final tuple breaks its relation at the given address
+    #[error("unsatisfied memory state at address {0}")]
+    UnSatMemoryState(usize),
+    /// the inverses of the writes and initial tuples sum up differently from those of the reads and final tuples
+    #[error("unsatisfied memory sum")]
+    UnSatMemorySum,
+    /// a round of the sumcheck does not match its claim
+    #[error("invalid sumcheck")]
+    InvalidSumcheck,
 }
@@ -0,0 +1,294 @@
+/// commitments of the other curve of a cycle allocated in a circuit over its base field, where their coordinates are
+/// native, together with the group operations the in-circuit verifier of the folding scheme needs
+///
+/// the curves are of prime order with a = 0, y^2 = x^3 + b as BN254 and Grumpkin are, so that a point other than the
+/// identity never doubles into it, the identity is kept as (0, 0) with its flag set
+///
+use ark_ec::CurveGroup;
+use ark_ff::{Field, PrimeField};
+use jf_relation::Variable;
+
+use crate::{
+    circuit::{Gate, PlonkishCircuit, ROCircuitTrait},
+    error::MyError,
+    to_coordinates,
+    traits::{CommitmentTrait, Group},
+    Commitment,
+};
+
+/// affine coordinates of a commitment with its boolean infinity flag
+#[derive(Clone, Debug)]
+pub struct AllocatedCommitment {
+    pub(crate) x: Variable,
+    pub(crate) y: Variable,
+    pub(crate) is_infinity: Variable,
+}
+
+impl AllocatedCommitment {
+    pub fn alloc<G: Group>(
+        cs: &mut PlonkishCircuit<G::BaseField>,
+        c: &Commitment<G>,
+    ) -> Result<Self, MyError> {
+        let (x, y, is_infinity) = c.to_coordinates();
+        Ok(AllocatedCommitment {
+            x: cs.create_variable(x)?,
+            y: cs.create_variable(y)?,
+            is_infinity: cs.create_boolean_variable(is_infinity)?,
+        })
+    }
+
+    /// the identity, (0, 0, true)
+    pub fn default<F: PrimeField>(cs: &PlonkishCircuit<F>) -> Self {
+        AllocatedCommitment {
+            x: cs.zero(),
+            y: cs.zero(),
+            is_infinity: cs.one(),
+        }
+    }
+
+    /// a fixed point of the curve as constants of the circuit
+    fn constant<G: Group>(
+        cs: &mut PlonkishCircuit<G::BaseField>,
+        p: &<G::Curve as CurveGroup>::Affine,
+    ) -> Result<Self, MyError> {
+        let (x, y, is_infinity) = to_coordinates::<G>(p);
+        Ok(AllocatedCommitment {
+            x: cs.create_constant_variable(x)?,
+            y: cs.create_constant_variable(y)?,
+            is_infinity: if is_infinity { cs.one() } else { cs.zero() },
+        })
+    }
+
+    pub fn absorb_in_ro<F: PrimeField, RO: ROCircuitTrait<F>>(&self, ro: &mut RO) {
+        ro.absorb(self.x);
+        ro.absorb(self.y);
+        ro.absorb(self.is_infinity);
+    }
+
+    /// c_0 if b = 0, c_1 if b = 1
+    pub fn conditionally_select<F: PrimeField>(
+        cs: &mut PlonkishCircuit<F>,
+        b: Variable,
+        c_0: &Self,
+        c_1: &Self,
+    ) -> Result<Self, MyError> {
+        Ok(AllocatedCommitment {
+            x: cs.conditional_select(b, c_0.x, c_1.x)?,
+            y: cs.conditional_select(b, c_0.y, c_1.y)?,
+            is_infinity: cs.conditional_select(b, c_0.is_infinity, c_1.is_infinity)?,
+        })
+    }
+
+    /// x3 = λ^2 - x1 - x2 and y3 = λ * (x1 - x3) - y1, the third point on the line of slope λ through self and x2
+    fn chord<F: PrimeField>(
+        &self,
+        cs: &mut PlonkishCircuit<F>,
+        x_2: Variable,
+        lambda: Variable,
+    ) -> Result<(Variable, Variable), MyError> {
+        let x_3_val = cs.witness(lambda)?.square() - cs.witness(self.x)? - cs.witness(x_2)?;
+        let x_3 = cs.create_variable(x_3_val)?;
+        cs.insert_gate(
+            &[lambda, lambda, self.x, x_2, x_3],
+            Gate {
+                q_lc: [F::ZERO, F::ZERO, -F::ONE, -F::ONE],
+                q_mul: [F::ONE, F::ZERO],
+                q_o: F::ONE,
+                ..Default::default()
+            },
+        )?;
+        let t = cs.mul_add(&[lambda, self.x, lambda, x_3], &[F::ONE, -F::ONE])?;
+        let y_3 = cs.sub(t, self.y)?;
+        Ok((x_3, y_3))
+    }
+
+    /// self + other for two points other than the identity of distinct x, which is left unsatisfied otherwise
+    fn add_incomplete<F: PrimeField>(
+        &self,
+        cs: &mut PlonkishCircuit<F>,
+        other: &Self,
+    ) -> Result<Self, MyError> {
+        let dy = cs.sub(other.y, self.y)?;
+        let dx = cs.witness(other.x)? - cs.witness(self.x)?;
+        let lambda = cs.create_variable(cs.witness(dy)? * dx.inverse().unwrap_or(F::ZERO))?;
+        // λ * x2 - λ * x1 = y2 - y1
+        cs.insert_gate(
+            &[lambda, other.x, lambda, self.x, dy],
+            Gate {
+                q_mul: [F::ONE, -F::ONE],
+                q_o: F::ONE,
+                ..Default::default()
+            },
+        )?;
+        let (x, y) = self.chord(cs, other.x, lambda)?;
+        Ok(AllocatedCommitment {
+            x,
+            y,
+            is_infinity: cs.zero(),
+        })
+    }
+
+    /// 2 * self for a point other than the identity, of slope λ = 3 * x^2 / (2 * y)
+    fn double<F: PrimeField>(&self, cs: &mut PlonkishCircuit<F>) -> Result<Self, MyError> {
+        let (x, y) = (cs.witness(self.x)?, cs.witness(self.y)?);
+        let lambda = cs.create_variable(
+            F::from(3u64) * x.square() * y.double().inverse().unwrap_or(F::ZERO),
+        )?;
+        let zero = cs.zero();
+        // 2 * y * λ - 3 * x * x = 0
+        cs.insert_gate(
+            &[self.y, lambda, self.x, self.x, zero],
+            Gate {
+                q_mul: [F::from(2u64), -F::from(3u64)],
+                ..Default::default()
+            },
+        )?;
+        let (x, y) = self.chord(cs, self.x, lambda)?;
+        Ok(AllocatedCommitment {
+            x,
+            y,
+            is_infinity: cs.zero(),
+        })
+    }
+
+    /// self + other, where either may be the identity, while two points other than the identity must differ in x,
+    /// which the folding challenge leaves to negligible probability
+    pub fn add<F: PrimeField>(
+        &self,
+        cs: &mut PlonkishCircuit<F>,
+        other: &Self,
+    ) -> Result<Self, MyError> {
+        let zero = cs.zero();
+        let (inf_1, inf_2) = (
+            cs.witness(self.is_infinity)?,
+            cs.witness(other.is_infinity)?,
+        );
+        // both = (1 - inf_1) * (1 - inf_2), one iff neither is the identity
+        let both = cs.create_variable((F::ONE - inf_1) * (F::ONE - inf_2))?;
+        cs.insert_gate(
+            &[self.is_infinity, other.is_infinity, zero, zero, both],
+            Gate {
+                q_lc: [-F::ONE, -F::ONE, F::ZERO, F::ZERO],
+                q_mul: [F::ONE, F::ZERO],
+                q_c: F::ONE,
+                q_o: F::ONE,
+                ..Default::default()
+            },
+        )?;
+        // (x2 - x1) * inv = both, and λ = (y2 - y1) * inv
+        let dx = cs.witness(other.x)? - cs.witness(self.x)?;
+        let inv = cs.create_variable(cs.witness(both)? * dx.inverse().unwrap_or(F::ZERO))?;
+        cs.insert_gate(
+            &[other.x, inv, self.x, inv, both],
+            Gate {
+                q_mul: [F::ONE, -F::ONE],
+                q_o: F::ONE,
+                ..Default::default()
+            },
+        )?;
+        let lambda = cs.mul_add(&[other.y, inv, self.y, inv], &[F::ONE, -F::ONE])?;
+        let (x_3, y_3) = self.chord(cs, other.x, lambda)?;
+
+        let x = cs.conditional_select(self.is_infinity, self.x, other.x)?;
+        let y = cs.conditional_select(self.is_infinity, self.y, other.y)?;
+        Ok(AllocatedCommitment {
+            x: cs.conditional_select(both, x, x_3)?,
+            y: cs.conditional_select(both, y, y_3)?,
+            is_infinity: cs.mul(self.is_infinity, other.is_infinity)?,
+        })
+    }
+
+    /// k * self for the little-endian bits of k, fewer than those of the order of the curve and k other than 0 and 1,
+    /// by double-and-add from the lowest bit
+    ///
+    /// the sum starts at the point, so that c * P with 0 < c < 2^i never meets ±2^i * P, and the point is taken off
+    /// again unless the lowest bit is set, the identity runs through the generator instead and is restored at the end
+    pub fn scalar_mul<G: Group>(
+        &self,
+        cs: &mut PlonkishCircuit<G::BaseField>,
+        bits: &[Variable],
+    ) -> Result<Self, MyError> {
+        if bits.is_empty() {
+            return Err(MyError::CircuitError);
+        }
+        let g = Self::constant::<G>(cs, &<G::Curve as ark_ec::Group>::generator().into_affine())?;
+        let p = AllocatedCommitment {
+            x: cs.conditional_select(self.is_infinity, self.x, g.x)?,
+            y: cs.conditional_select(self.is_infinity, self.y, g.y)?,
+            is_infinity: cs.zero(),
+        };
+
+        let (mut acc, mut base) = (p.clone(), p.clone());
+        for b in &bits[1..] {
+            base = base.double(cs)?;
+            let sum = acc.add_incomplete(cs, &base)?;
+            acc = AllocatedCommitment {
+                x: cs.conditional_select(*b, acc.x, sum.x)?,
+                y: cs.conditional_select(*b, acc.y, sum.y)?,
+                is_infinity: cs.zero(),
+            };
+        }
+        let neg_p = AllocatedCommitment {
+            y: cs.mul_constant(p.y, &-G::BaseField::ONE)?,
+            ..p
+        };
+        let diff = acc.add_incomplete(cs, &neg_p)?;
+        let kp = Self::conditionally_select(cs, bits[0], &diff, &acc)?;
+
+        Self::conditionally_select(cs, self.is_infinity, &kp, &Self::default(cs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{provider::grumpkin::Grumpkin, traits::CommitmentEngineTrait};
+    use ark_bn254::Bn254;
+    use ark_ff::UniformRand;
+    use jf_utils::test_rng;
+
+    fn test_ecc_ops_with<G: Group>() {
+        let rng = &mut test_rng();
+        let ck = G::CE::setup(rng, 4);
+        let mut commit = || {
+            let v = (0..4)
+                .map(|_| <G as Group>::ScalarField::rand(rng))
+                .collect::<Vec<_>>();
+            G::CE::commit(&ck, &v)
+        };
+        let (P, Q, O) = (commit(), commit(), Commitment::<G>::default());
+        let k = u128::rand(&mut test_rng());
+
+        // P + k * Q with either side possibly the identity
+        for (P, Q) in [(&P, &Q), (&O, &Q), (&P, &O), (&O, &O)] {
+            let mut cs = PlonkishCircuit::<G::BaseField>::new();
+            let k_var = cs.create_variable(G::BaseField::from(k)).unwrap();
+            let bits = cs.unpack(k_var, 128).unwrap();
+            let P_var = AllocatedCommitment::alloc::<G>(&mut cs, P).unwrap();
+            let Q_var = AllocatedCommitment::alloc::<G>(&mut cs, Q).unwrap();
+            let kQ = Q_var.scalar_mul::<G>(&mut cs, &bits).unwrap();
+            let sum = P_var.add(&mut cs, &kQ).unwrap();
+            cs.check_circuit_satisfiability().unwrap();
+
+            let expected = P.clone() + Q.clone() * <G as Group>::ScalarField::from(k);
+            let coordinates = (
+                cs.witness(sum.x).unwrap(),
+                cs.witness(sum.y).unwrap(),
+                cs.witness(sum.is_infinity).unwrap() == G::BaseField::ONE,
+            );
+            assert_eq!(coordinates, expected.to_coordinates());
+        }
+
+        // two points of the same x are not added
+        let mut cs = PlonkishCircuit::<G::BaseField>::new();
+        let P_var = AllocatedCommitment::alloc::<G>(&mut cs, &P).unwrap();
+        P_var.add(&mut cs, &P_var).unwrap();
+        assert!(cs.check_circuit_satisfiability().is_err());
+    }
+
+    #[test]
+    fn test_ecc_ops() {
+        test_ecc_ops_with::<Bn254>();
+        test_ecc_ops_with::<Grumpkin>();
+    }
+}
@@ -0,0 +1,4 @@
+//! gadgets the augmented circuits are built from
+pub mod ecc;
+pub mod nonnative;
+pub mod plonk;
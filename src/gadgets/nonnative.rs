@@ -0,0 +1,250 @@
+/// arithmetic over the scalar field of the other curve of a cycle, inside a circuit over its own scalar field
+///
+/// a non-native value is kept as little-endian limbs of `BN_LIMB_WIDTH` bits, the same limbs the native random oracle
+/// absorbs, and c = a + r * b mod q is shown by a + r * b = qt * q + c over the integers, column by column with carries,
+/// with c < q so that the limbs of c are the canonical ones the native RO absorbs
+///
+use ark_ff::PrimeField;
+use jf_relation::Variable;
+use num_bigint::{BigInt, BigUint, Sign};
+
+use crate::{
+    circuit::{Gate, PlonkishCircuit},
+    constants::{BN_LIMB_WIDTH, BN_N_LIMBS},
+    error::MyError,
+};
+
+/// carries are below 2^CARRY_BITS in absolute value, they are range-checked after a shift by that much
+const CARRY_BITS: usize = 70;
+
+/// integer value of little-endian limbs
+fn limbs_value<F: PrimeField>(
+    cs: &PlonkishCircuit<F>,
+    limbs: &[Variable],
+) -> Result<BigUint, MyError> {
+    limbs.iter().rev().try_fold(BigUint::from(0u64), |acc, l| {
+        Ok((acc << BN_LIMB_WIDTH) + Into::<BigUint>::into(cs.witness(*l)?))
+    })
+}
+
+/// the first `n` limbs of an integer
+fn to_limbs(v: &BigUint, n: usize) -> Vec<BigUint> {
+    let mask = (BigUint::from(1u64) << BN_LIMB_WIDTH) - 1u64;
+    (0..n).map(|i| (v >> (i * BN_LIMB_WIDTH)) & &mask).collect()
+}
+
+fn signed_to_field<F: PrimeField>(v: &BigInt) -> F {
+    let abs = F::from(v.magnitude().clone());
+    if v.sign() == Sign::Minus {
+        -abs
+    } else {
+        abs
+    }
+}
+
+/// allocates range-checked limbs of the given integer
+fn alloc_biguint<F: PrimeField>(
+    cs: &mut PlonkishCircuit<F>,
+    v: &BigUint,
+    n: usize,
+) -> Result<Vec<Variable>, MyError> {
+    to_limbs(v, n)
+        .into_iter()
+        .map(|l| {
+            let var = cs.create_variable(F::from(l))?;
+            cs.enforce_in_range(var, BN_LIMB_WIDTH)?;
+            Ok(var)
+        })
+        .collect()
+}
+
+/// allocates the `BN_N_LIMBS` limbs of a non-native value, range-checking each one
+pub(crate) fn alloc_limbs<F: PrimeField, Fq: PrimeField>(
+    cs: &mut PlonkishCircuit<F>,
+    v: &Fq,
+) -> Result<Vec<Variable>, MyError> {
+    alloc_biguint(cs, &(*v).into(), BN_N_LIMBS)
+}
+
+/// limbs out of little-endian bits, the last one holding whatever bits are left
+pub(crate) fn limbs_from_bits<F: PrimeField>(
+    cs: &mut PlonkishCircuit<F>,
+    bits: &[Variable],
+) -> Result<Vec<Variable>, MyError> {
+    bits.chunks(BN_LIMB_WIDTH)
+        .map(|chunk| cs.from_bits_le(chunk))
+        .collect()
+}
+
+/// c < q for the `BN_N_LIMBS` range-checked limbs of c, by c + d = q - 1 over the integers with d range-checked too,
+/// column by column with boolean carries
+fn enforce_below_modulus<F: PrimeField, Fq: PrimeField>(
+    cs: &mut PlonkishCircuit<F>,
+    c: &[Variable],
+) -> Result<(), MyError> {
+    let q_minus_one = Into::<BigUint>::into(Fq::MODULUS) - 1u64;
+    // wraps around for c >= q, which leaves the last column unsatisfied
+    let d_val =
+        (BigUint::from(1u64) << (BN_LIMB_WIDTH * BN_N_LIMBS)) + &q_minus_one - limbs_value(cs, c)?;
+    let d = alloc_biguint(cs, &d_val, BN_N_LIMBS)?;
+
+    let base = F::from(BigUint::from(1u64) << BN_LIMB_WIDTH);
+    let mut carry = (cs.zero(), BigUint::from(0u64));
+    for (k, q_k) in to_limbs(&q_minus_one, BN_N_LIMBS).into_iter().enumerate() {
+        let sum = limbs_value(cs, &[c[k]])? + limbs_value(cs, &[d[k]])? + &carry.1;
+        let next_carry = if k + 1 < BN_N_LIMBS {
+            let val = sum >> BN_LIMB_WIDTH;
+            (cs.create_boolean_variable(val == BigUint::from(1u64))?, val)
+        } else {
+            (cs.zero(), BigUint::from(0u64))
+        };
+        // c_k + d_k + carry_in - 2^BN_LIMB_WIDTH * carry_out = (q - 1)_k
+        cs.insert_gate(
+            &[c[k], d[k], carry.0, next_carry.0, cs.zero()],
+            Gate {
+                q_lc: [F::ONE, F::ONE, F::ONE, -base],
+                q_c: -F::from(q_k),
+                ..Default::default()
+            },
+        )?;
+        carry = next_carry;
+    }
+    Ok(())
+}
+
+/// `BN_N_LIMBS` limbs of c = a + r * b mod q, q being the modulus of `Fq`,
+/// where all operands are given as range-checked limbs
+pub(crate) fn mul_add_mod<F: PrimeField, Fq: PrimeField>(
+    cs: &mut PlonkishCircuit<F>,
+    a: &[Variable],
+    r: &[Variable],
+    b: &[Variable],
+) -> Result<Vec<Variable>, MyError> {
+    let q: BigUint = Fq::MODULUS.into();
+    let lhs = limbs_value(cs, a)? + limbs_value(cs, r)? * limbs_value(cs, b)?;
+
+    // qt < 2^(|lhs| + 1 - |q|)
+    let lhs_bits = BN_LIMB_WIDTH * a.len().max(r.len() + b.len()) + 1;
+    let num_qt_limbs =
+        (lhs_bits + 1 - Fq::MODULUS_BIT_SIZE as usize + BN_LIMB_WIDTH - 1) / BN_LIMB_WIDTH;
+    let qt = alloc_biguint(cs, &(&lhs / &q), num_qt_limbs)?;
+    let c = alloc_biguint(cs, &(&lhs % &q), BN_N_LIMBS)?;
+    enforce_below_modulus::<F, Fq>(cs, &c)?;
+    let q_limbs = to_limbs(&q, BN_N_LIMBS);
+
+    // the k-th column: a_k + Σ r_i * b_j - Σ qt_i * q_j - c_k, as terms of a linear combination
+    let num_columns = a
+        .len()
+        .max(r.len() + b.len() - 1)
+        .max(num_qt_limbs + BN_N_LIMBS - 1)
+        .max(BN_N_LIMBS);
+    let mut columns = vec![vec![]; num_columns];
+    for (k, column) in columns.iter_mut().enumerate() {
+        if let Some(a_k) = a.get(k) {
+            column.push((*a_k, BigInt::from(1u64)));
+        }
+        let pairs = (0..r.len())
+            .filter(|i| k >= *i && k - *i < b.len())
+            .map(|i| (r[i], b[k - i]))
+            .collect::<Vec<_>>();
+        for pair in pairs.chunks(2) {
+            let (r_1, b_1) = pair.get(1).copied().unwrap_or((cs.zero(), cs.zero()));
+            let prod = cs.mul_add(&[pair[0].0, pair[0].1, r_1, b_1], &[F::ONE, F::ONE])?;
+            column.push((prod, BigInt::from(1u64)));
+        }
+        (0..qt.len())
+            .filter(|i| k >= *i && k - *i < BN_N_LIMBS)
+            .for_each(|i| column.push((qt[i], -BigInt::from(q_limbs[k - i].clone()))));
+        if let Some(c_k) = c.get(k) {
+            column.push((*c_k, BigInt::from(-1i64)));
+        }
+    }
+
+    // the columns sum up to zero as an integer in base 2^BN_LIMB_WIDTH,
+    // each one passes its value over 2^BN_LIMB_WIDTH on to the next as a carry
+    let base = BigInt::from(1u64) << BN_LIMB_WIDTH;
+    let offset = F::from(1u128 << CARRY_BITS);
+    let mut carry: Option<(Variable, BigInt)> = None;
+    for (k, mut column) in columns.into_iter().enumerate() {
+        let mut next_carry = None;
+        if k + 1 < num_columns {
+            let value = column.iter().try_fold(
+                carry
+                    .as_ref()
+                    .map_or(BigInt::from(0u64), |(_, v)| v.clone()),
+                |acc, (v, coeff)| {
+                    Ok::<BigInt, MyError>(
+                        acc + coeff * BigInt::from(Into::<BigUint>::into(cs.witness(*v)?)),
+                    )
+                },
+            )?;
+            let carry_val = value >> BN_LIMB_WIDTH;
+            let carry_var = cs.create_variable(signed_to_field(&carry_val))?;
+            let shifted = cs.add_constant(carry_var, &offset)?;
+            cs.enforce_in_range(shifted, CARRY_BITS + 1)?;
+            column.push((carry_var, -base.clone()));
+            next_carry = Some((carry_var, carry_val));
+        }
+        if let Some((carry_var, _)) = carry {
+            column.push((carry_var, BigInt::from(1u64)));
+        }
+        carry = next_carry;
+
+        let terms = column
+            .iter()
+            .map(|(v, coeff)| (*v, signed_to_field::<F>(coeff)))
+            .collect::<Vec<_>>();
+        let sum = cs.linear_combination(&terms)?;
+        cs.enforce_constant(sum, F::ZERO)?;
+    }
+
+    Ok(c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::{Fq, Fr};
+    use ark_ff::UniformRand;
+    use jf_utils::test_rng;
+
+    #[test]
+    fn test_mul_add_mod() {
+        let rng = &mut test_rng();
+        let (a, b) = (Fq::rand(rng), Fq::rand(rng));
+        let r = u128::rand(rng);
+
+        let mut cs = PlonkishCircuit::<Fr>::new();
+        let a_limbs = alloc_limbs::<Fr, Fq>(&mut cs, &a).unwrap();
+        let b_limbs = alloc_limbs::<Fr, Fq>(&mut cs, &b).unwrap();
+        let r_var = cs.create_variable(Fr::from(r)).unwrap();
+        let r_bits = cs.unpack(r_var, 128).unwrap();
+        let r_limbs = limbs_from_bits(&mut cs, &r_bits).unwrap();
+        let c_limbs = mul_add_mod::<Fr, Fq>(&mut cs, &a_limbs, &r_limbs, &b_limbs).unwrap();
+        cs.check_circuit_satisfiability().unwrap();
+
+        let c = a + Fq::from(r) * b;
+        assert_eq!(limbs_value(&cs, &c_limbs).unwrap(), c.into());
+
+        // u + r for a fresh instance, with b = 1
+        let mut cs = PlonkishCircuit::<Fr>::new();
+        let a_limbs = alloc_limbs::<Fr, Fq>(&mut cs, &a).unwrap();
+        let r_var = cs.create_variable(Fr::from(r)).unwrap();
+        let r_bits = cs.unpack(r_var, 128).unwrap();
+        let r_limbs = limbs_from_bits(&mut cs, &r_bits).unwrap();
+        let one = cs.one();
+        let c_limbs = mul_add_mod::<Fr, Fq>(&mut cs, &a_limbs, &r_limbs, &[one]).unwrap();
+        assert_eq!(
+            limbs_value(&cs, &c_limbs).unwrap(),
+            (a + Fq::from(r)).into()
+        );
+        cs.check_circuit_satisfiability().unwrap();
+
+        // c + q has limbs as well, but is not below the modulus
+        let mut cs = PlonkishCircuit::<Fr>::new();
+        let q = Into::<BigUint>::into(Fq::MODULUS);
+        let c_limbs = alloc_biguint(&mut cs, &(q + Into::<BigUint>::into(a)), BN_N_LIMBS).unwrap();
+        enforce_below_modulus::<Fr, Fq>(&mut cs, &c_limbs).unwrap();
+        assert!(cs.check_circuit_satisfiability().is_err());
+    }
+}
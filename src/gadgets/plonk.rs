@@ -0,0 +1,231 @@
+/// PLONK instances of the other curve of a cycle allocated in a circuit over its base field, together with the
+/// in-circuit verifier of the compressed non-interactive folding scheme
+///
+/// commitment coordinates are native and folded with the group operations of `ecc`, u, β, e and X are non-native and
+/// kept as the limbs the native RO absorbs
+///
+use ark_ff::PrimeField;
+use jf_relation::Variable;
+
+use crate::{
+    circuit::{CircuitGroup, PlonkishCircuit, ROCircuitTrait},
+    constants::{BN_N_LIMBS, NUM_CHALLENGE_BITS, NUM_HASH_BITS},
+    error::MyError,
+    gadgets::{
+        ecc::AllocatedCommitment,
+        nonnative::{alloc_limbs, limbs_from_bits, mul_add_mod},
+    },
+    plonk::{CompressedRelaxedPLONKInstance, PLONKInstance},
+    scalar_as_base,
+    traits::{Group, ROConstantsTrait},
+    ROConstants,
+};
+
+/// A fresh instance, whose public inputs are hashes of `NUM_HASH_BITS` and so fit into the native field
+#[derive(Clone, Debug)]
+pub struct AllocatedPLONKInstance {
+    pub(crate) comm_W: Vec<AllocatedCommitment>,
+    pub(crate) X: Vec<Variable>,
+    pub(crate) X_limbs: Vec<Vec<Variable>>,
+}
+
+impl AllocatedPLONKInstance {
+    pub fn alloc<G: Group>(
+        cs: &mut PlonkishCircuit<G::BaseField>,
+        u: &PLONKInstance<G>,
+    ) -> Result<Self, MyError> {
+        let comm_W = u
+            .comm_W
+            .iter()
+            .map(|c| AllocatedCommitment::alloc::<G>(cs, c))
+            .collect::<Result<Vec<_>, MyError>>()?;
+        let (X, X_limbs) =
+            u.X.iter()
+                .map(|x| {
+                    let x = cs.create_variable(scalar_as_base::<G>(x))?;
+                    let bits = cs.unpack(x, NUM_HASH_BITS)?;
+                    Ok((x, limbs_from_bits(cs, &bits)?))
+                })
+                .collect::<Result<Vec<_>, MyError>>()?
+                .into_iter()
+                .unzip();
+        Ok(AllocatedPLONKInstance { comm_W, X, X_limbs })
+    }
+
+    pub fn absorb_in_ro<F: PrimeField, RO: ROCircuitTrait<F>>(&self, ro: &mut RO) {
+        self.comm_W.iter().for_each(|c| c.absorb_in_ro(ro));
+        self.X_limbs.iter().flatten().for_each(|l| ro.absorb(*l));
+    }
+}
+
+/// A running instance of the compressed mode, with u, β, e and X as limbs of the scalar field of the other curve
+#[derive(Clone, Debug)]
+pub struct AllocatedCompressedRelaxedPLONKInstance {
+    pub(crate) comm_W: Vec<AllocatedCommitment>,
+    pub(crate) comm_B: AllocatedCommitment,
+    pub(crate) comm_E: AllocatedCommitment,
+    pub(crate) u: Vec<Variable>,
+    pub(crate) beta: Vec<Variable>,
+    pub(crate) e: Vec<Variable>,
+    pub(crate) X: Vec<Vec<Variable>>,
+}
+
+impl AllocatedCompressedRelaxedPLONKInstance {
+    pub fn alloc<G: Group>(
+        cs: &mut PlonkishCircuit<G::BaseField>,
+        U: &CompressedRelaxedPLONKInstance<G>,
+    ) -> Result<Self, MyError> {
+        Ok(AllocatedCompressedRelaxedPLONKInstance {
+            comm_W: U
+                .comm_W
+                .iter()
+                .map(|c| AllocatedCommitment::alloc::<G>(cs, c))
+                .collect::<Result<Vec<_>, MyError>>()?,
+            comm_B: AllocatedCommitment::alloc::<G>(cs, &U.comm_B)?,
+            comm_E: AllocatedCommitment::alloc::<G>(cs, &U.comm_E)?,
+            u: alloc_limbs(cs, &U.u)?,
+            beta: alloc_limbs(cs, &U.beta)?,
+            e: alloc_limbs(cs, &U.e)?,
+            X: U.X
+                .iter()
+                .map(|x| alloc_limbs(cs, x))
+                .collect::<Result<Vec<_>, MyError>>()?,
+        })
+    }
+
+    /// the all-zero instance with identity commitments, the same as `CompressedRelaxedPLONKInstance::default`
+    pub fn default<F: PrimeField>(
+        cs: &PlonkishCircuit<F>,
+        num_wire_types: usize,
+        num_public_input: usize,
+    ) -> Self {
+        let zero_limbs = vec![cs.zero(); BN_N_LIMBS];
+        AllocatedCompressedRelaxedPLONKInstance {
+            comm_W: vec![AllocatedCommitment::default(cs); num_wire_types],
+            comm_B: AllocatedCommitment::default(cs),
+            comm_E: AllocatedCommitment::default(cs),
+            u: zero_limbs.clone(),
+            beta: zero_limbs.clone(),
+            e: zero_limbs.clone(),
+            X: vec![zero_limbs; num_public_input],
+        }
+    }
+
+    pub fn absorb_in_ro<F: PrimeField, RO: ROCircuitTrait<F>>(&self, ro: &mut RO) {
+        self.comm_W.iter().for_each(|c| c.absorb_in_ro(ro));
+        self.comm_B.absorb_in_ro(ro);
+        self.comm_E.absorb_in_ro(ro);
+        [&self.u, &self.beta, &self.e]
+            .into_iter()
+            .chain(&self.X)
+            .flatten()
+            .for_each(|l| ro.absorb(*l));
+    }
+
+    /// the verifier of the compressed folding scheme: recomputes β of the incoming instance and the challenge r from
+    /// the same transcripts as `CompressedNIFS`, and folds the instances with them, so that comm_W, comm_B and comm_E
+    /// take one multiplication by r each and the cross terms e_T of the gates go into e by non-native arithmetic
+    pub fn fold<G: CircuitGroup>(
+        &self,
+        cs: &mut PlonkishCircuit<G::BaseField>,
+        pp_digest: &[Variable],
+        u: &AllocatedPLONKInstance,
+        (comm_B, comm_t, e_T): (&AllocatedCommitment, &AllocatedCommitment, &[Vec<Variable>]),
+    ) -> Result<AllocatedCompressedRelaxedPLONKInstance, MyError> {
+        let mut ro = G::ROCircuit::new(ROConstants::<G>::new(3));
+        pp_digest.iter().for_each(|l| ro.absorb(*l));
+        self.absorb_in_ro(&mut ro);
+        u.absorb_in_ro(&mut ro);
+        let hash_bits = ro.squeeze(cs, NUM_HASH_BITS)?;
+        let beta = limbs_from_bits(cs, &hash_bits[..NUM_CHALLENGE_BITS])?;
+        let hash = cs.from_bits_le(&hash_bits)?;
+
+        let mut ro = G::ROCircuit::new(ROConstants::<G>::new(3));
+        ro.absorb(hash);
+        comm_B.absorb_in_ro(&mut ro);
+        comm_t.absorb_in_ro(&mut ro);
+        e_T.iter().flatten().for_each(|l| ro.absorb(*l));
+        let r_bits = ro.squeeze(cs, NUM_CHALLENGE_BITS)?;
+        let r = limbs_from_bits(cs, &r_bits)?;
+
+        let one = cs.one();
+        let u_folded = mul_add_mod::<G::BaseField, G::ScalarField>(cs, &self.u, &r, &[one])?;
+        let beta = mul_add_mod::<G::BaseField, G::ScalarField>(cs, &self.beta, &r, &beta)?;
+        let X = self
+            .X
+            .iter()
+            .zip(&u.X_limbs)
+            .map(|(X1, X2)| mul_add_mod::<G::BaseField, G::ScalarField>(cs, X1, &r, X2))
+            .collect::<Result<Vec<_>, MyError>>()?;
+        // e + Σ_k r^{k+1} * e_T_k by Horner's rule, so that every product takes r as it is
+        let (e_T_last, e_T_rest) = e_T.split_last().ok_or(MyError::CircuitError)?;
+        let e_T = e_T_rest
+            .iter()
+            .rev()
+            .try_fold(e_T_last.clone(), |acc, e_k| {
+                mul_add_mod::<G::BaseField, G::ScalarField>(cs, e_k, &r, &acc)
+            })?;
+        let e = mul_add_mod::<G::BaseField, G::ScalarField>(cs, &self.e, &r, &e_T)?;
+
+        let mut fold_comm = |c1: &AllocatedCommitment, c2: &AllocatedCommitment| {
+            let rc2 = c2.scalar_mul::<G>(cs, &r_bits)?;
+            c1.add(cs, &rc2)
+        };
+        let comm_W = self
+            .comm_W
+            .iter()
+            .zip(&u.comm_W)
+            .map(|(W1, W2)| fold_comm(W1, W2))
+            .collect::<Result<Vec<_>, MyError>>()?;
+        let comm_B = fold_comm(&self.comm_B, comm_B)?;
+        let comm_E = fold_comm(&self.comm_E, comm_t)?;
+
+        Ok(AllocatedCompressedRelaxedPLONKInstance {
+            comm_W,
+            comm_B,
+            comm_E,
+            u: u_folded,
+            beta,
+            e,
+            X,
+        })
+    }
+
+    /// U_0 if b = 0, U_1 if b = 1
+    pub fn conditionally_select<F: PrimeField>(
+        cs: &mut PlonkishCircuit<F>,
+        b: Variable,
+        U_0: &Self,
+        U_1: &Self,
+    ) -> Result<Self, MyError> {
+        let select_comm =
+            |cs: &mut PlonkishCircuit<F>, c_0: &AllocatedCommitment, c_1: &AllocatedCommitment| {
+                AllocatedCommitment::conditionally_select(cs, b, c_0, c_1)
+            };
+        let select_limbs = |cs: &mut PlonkishCircuit<F>, l_0: &[Variable], l_1: &[Variable]| {
+            l_0.iter()
+                .zip(l_1)
+                .map(|(l_0, l_1)| cs.conditional_select(b, *l_0, *l_1))
+                .collect::<Result<Vec<_>, MyError>>()
+        };
+        Ok(AllocatedCompressedRelaxedPLONKInstance {
+            comm_W: U_0
+                .comm_W
+                .iter()
+                .zip(&U_1.comm_W)
+                .map(|(c_0, c_1)| select_comm(cs, c_0, c_1))
+                .collect::<Result<Vec<_>, MyError>>()?,
+            comm_B: select_comm(cs, &U_0.comm_B, &U_1.comm_B)?,
+            comm_E: select_comm(cs, &U_0.comm_E, &U_1.comm_E)?,
+            u: select_limbs(cs, &U_0.u, &U_1.u)?,
+            beta: select_limbs(cs, &U_0.beta, &U_1.beta)?,
+            e: select_limbs(cs, &U_0.e, &U_1.e)?,
+            X: U_0
+                .X
+                .iter()
+                .zip(&U_1.X)
+                .map(|(X_0, X_1)| select_limbs(cs, X_0, X_1))
+                .collect::<Result<Vec<_>, MyError>>()?,
+        })
+    }
+}
@@ -0,0 +1,534 @@
+/// custom gates of a PLONK shape, described as sums of selector * monomial terms
+///
+/// a gate of degree d is homogenized with the relaxation factor u, every term of degree k is scaled by u^(d - k),
+/// so that the gate is homogeneous of degree d in (u, w, PI), which is all the cross-term engine relies on
+///
+/// gates are declared as polynomial expressions over named selectors, rotated wires and the public input through
+/// `GateBuilder`, and expanded into their terms once, from which the shape, its satisfiability checks and
+/// its cross terms are all derived
+///
+use ark_ff::{BigInteger, PrimeField};
+use std::ops::{Add, Mul, Neg, Sub};
+
+/// a column a term of a gate reads at its row
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Column {
+    /// the i-th wire
+    Wire(usize),
+    /// the i-th wire at the given offset from the row, wrapping around the rows
+    RotatedWire(usize, isize),
+    /// the public input, carried on the first `num_public_input` rows
+    PublicInput,
+}
+
+/// coeff * prod selectors * prod columns, selectors are fixed and do not count towards the degree
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GateTerm<F: PrimeField> {
+    pub(crate) coeff: F,
+    pub(crate) selectors: Vec<usize>,
+    pub(crate) columns: Vec<Column>,
+}
+
+impl<F: PrimeField> GateTerm<F> {
+    /// coeff * q_selector * prod columns, with no selector standing for a constant one
+    pub fn new(coeff: F, selector: Option<usize>, columns: &[Column]) -> Self {
+        Self {
+            coeff,
+            selectors: selector.into_iter().collect(),
+            columns: columns.to_vec(),
+        }
+    }
+}
+
+/// the gate Σ terms = 0 every row of a shape satisfies, over `num_selectors` selectors
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CustomGate<F: PrimeField> {
+    pub(crate) degree: usize,
+    pub(crate) num_selectors: usize,
+    pub(crate) selector_names: Vec<String>,
+    pub(crate) terms: Vec<GateTerm<F>>,
+}
+
+/// a polynomial expression of a gate
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Expression<F: PrimeField> {
+    Constant(F),
+    Selector(usize),
+    Column(Column),
+    Negated(Box<Expression<F>>),
+    Sum(Box<Expression<F>>, Box<Expression<F>>),
+    Product(Box<Expression<F>>, Box<Expression<F>>),
+}
+
+impl<F: PrimeField> Expression<F> {
+    pub fn constant(c: F) -> Self {
+        Expression::Constant(c)
+    }
+
+    /// the i-th wire at the current row
+    pub fn wire(i: usize) -> Self {
+        Expression::Column(Column::Wire(i))
+    }
+
+    /// the i-th wire at the given offset from the current row
+    pub fn rotated_wire(i: usize, rotation: isize) -> Self {
+        if rotation == 0 {
+            Self::wire(i)
+        } else {
+            Expression::Column(Column::RotatedWire(i, rotation))
+        }
+    }
+
+    pub fn public_input() -> Self {
+        Expression::Column(Column::PublicInput)
+    }
+
+    /// self^exp, the constant one for exp = 0
+    pub fn pow(self, exp: usize) -> Self {
+        if exp == 0 {
+            return Expression::Constant(F::ONE);
+        }
+        (1..exp).fold(self.clone(), |acc, _| acc * self.clone())
+    }
+
+    /// the terms of the expanded expression, with like terms collected
+    fn expand(&self) -> Vec<GateTerm<F>> {
+        let terms = match self {
+            Expression::Constant(c) => vec![GateTerm::new(*c, None, &[])],
+            Expression::Selector(s) => vec![GateTerm::new(F::ONE, Some(*s), &[])],
+            Expression::Column(c) => vec![GateTerm::new(F::ONE, None, &[*c])],
+            Expression::Negated(a) => a
+                .expand()
+                .into_iter()
+                .map(|t| GateTerm {
+                    coeff: -t.coeff,
+                    ..t
+                })
+                .collect(),
+            Expression::Sum(a, b) => [a.expand(), b.expand()].concat(),
+            Expression::Product(a, b) => {
+                let b = b.expand();
+                a.expand()
+                    .iter()
+                    .flat_map(|ta| {
+                        b.iter().map(move |tb| GateTerm {
+                            coeff: ta.coeff * tb.coeff,
+                            selectors: [ta.selectors.clone(), tb.selectors.clone()].concat(),
+                            columns: [ta.columns.clone(), tb.columns.clone()].concat(),
+                        })
+                    })
+                    .collect()
+            }
+        };
+
+        let mut collected: Vec<GateTerm<F>> = vec![];
+        for mut term in terms {
+            term.selectors.sort();
+            term.columns.sort();
+            match collected
+                .iter_mut()
+                .find(|t| t.selectors == term.selectors && t.columns == term.columns)
+            {
+                Some(t) => t.coeff += term.coeff,
+                None => collected.push(term),
+            }
+        }
+        collected.retain(|t| !t.coeff.is_zero());
+        collected
+    }
+}
+
+impl<F: PrimeField> Add for Expression<F> {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        Expression::Sum(Box::new(self), Box::new(rhs))
+    }
+}
+
+impl<F: PrimeField> Sub for Expression<F> {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        self + (-rhs)
+    }
+}
+
+impl<F: PrimeField> Mul for Expression<F> {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        Expression::Product(Box::new(self), Box::new(rhs))
+    }
+}
+
+impl<F: PrimeField> Neg for Expression<F> {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Expression::Negated(Box::new(self))
+    }
+}
+
+/// declares the named selectors of a gate, in the order their columns are handed to the shape
+#[derive(Clone, Debug, Default)]
+pub struct GateBuilder {
+    selector_names: Vec<String>,
+}
+
+impl GateBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// the selector of the given name, declared on first use
+    pub fn selector<F: PrimeField>(&mut self, name: &str) -> Expression<F> {
+        let index = match self.selector_names.iter().position(|n| n == name) {
+            Some(index) => index,
+            None => {
+                self.selector_names.push(name.to_string());
+                self.selector_names.len() - 1
+            }
+        };
+        Expression::Selector(index)
+    }
+
+    /// the gate expression = 0
+    pub fn build<F: PrimeField>(self, expression: &Expression<F>) -> CustomGate<F> {
+        let mut gate = CustomGate::new(self.selector_names.len(), expression.expand());
+        gate.selector_names = self.selector_names;
+        gate
+    }
+}
+
+/// positions of the TurboPlonk selectors, q_lc, q_mul, q_hash, q_ecc, q_o, q_c
+pub const Q_LC: usize = 0;
+pub const Q_MUL: usize = 4;
+pub const Q_HASH: usize = 6;
+pub const Q_ECC: usize = 10;
+pub const Q_O: usize = 11;
+pub const Q_C: usize = 12;
+pub const NUM_TURBO_PLONK_SELECTORS: usize = 13;
+
+impl<F: PrimeField> CustomGate<F> {
+    /// a gate of the given terms, whose degree is the highest one among them
+    pub fn new(num_selectors: usize, terms: Vec<GateTerm<F>>) -> Self {
+        let degree = terms.iter().map(|t| t.columns.len()).max().unwrap_or(0);
+        Self {
+            degree: degree.max(1),
+            num_selectors,
+            selector_names: (0..num_selectors).map(|i| format!("q_{i}")).collect(),
+            terms,
+        }
+    }
+
+    /// q_lc * w + PI - q_o * w_o + q_mul * w + q_ecc * prod(w) + q_hash * w^5 + q_c over five wires
+    pub fn turbo_plonk() -> Self {
+        let mut builder = GateBuilder::new();
+        let q_lc = (0..4)
+            .map(|i| builder.selector(&format!("q_lc_{i}")))
+            .collect::<Vec<_>>();
+        let q_mul = (0..2)
+            .map(|i| builder.selector(&format!("q_mul_{i}")))
+            .collect::<Vec<_>>();
+        let q_hash = (0..4)
+            .map(|i| builder.selector(&format!("q_hash_{i}")))
+            .collect::<Vec<_>>();
+        let (q_ecc, q_o, q_c) = (
+            builder.selector("q_ecc"),
+            builder.selector("q_o"),
+            builder.selector("q_c"),
+        );
+
+        let w = Expression::<F>::wire;
+        let lc = (0..4).fold(Expression::public_input(), |acc, i| {
+            acc + q_lc[i].clone() * w(i)
+        });
+        let mul = q_mul[0].clone() * w(0) * w(1) + q_mul[1].clone() * w(2) * w(3);
+        let hash = (1..4).fold(q_hash[0].clone() * w(0).pow(5), |acc, i| {
+            acc + q_hash[i].clone() * w(i).pow(5)
+        });
+        let ecc = (0..5).fold(q_ecc, |acc, i| acc * w(i));
+        builder.build(&(lc + mul + hash + ecc - q_o * w(4) + q_c))
+    }
+
+    /// the position of the selector of the given name
+    pub fn selector_index(&self, name: &str) -> Option<usize> {
+        self.selector_names.iter().position(|n| n == name)
+    }
+
+    /// a canonical encoding of the degree and terms, for the digest of a shape
+    pub(crate) fn to_bytes(&self) -> Vec<u8> {
+        let mut words = vec![self.degree as u64, self.num_selectors as u64];
+        let mut coeffs = vec![];
+        for t in &self.terms {
+            coeffs.extend(t.coeff.into_bigint().to_bytes_le());
+            words.push(t.selectors.len() as u64);
+            words.extend(t.selectors.iter().map(|s| *s as u64));
+            words.push(t.columns.len() as u64);
+            t.columns.iter().for_each(|c| {
+                words.extend(match c {
+                    Column::Wire(i) => [0, *i as u64, 0],
+                    Column::RotatedWire(i, rotation) => [1, *i as u64, *rotation as u64],
+                    Column::PublicInput => [2, 0, 0],
+                })
+            });
+        }
+        words
+            .iter()
+            .flat_map(|w| w.to_le_bytes())
+            .chain(coeffs)
+            .collect()
+    }
+
+    /// the number of wires the gate reads
+    pub(crate) fn num_wires(&self) -> usize {
+        self.terms
+            .iter()
+            .flat_map(|t| t.columns.iter())
+            .filter_map(|c| match c {
+                Column::Wire(i) | Column::RotatedWire(i, _) => Some(i + 1),
+                Column::PublicInput => None,
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// the homogenized gate at the given row
+    pub(crate) fn evaluate(
+        &self,
+        selectors: &[Vec<F>],
+        row: usize,
+        u: &F,
+        W: &[Vec<F>],
+        X: &[F],
+    ) -> F {
+        self.evaluate_with(
+            u,
+            |s| selectors[s][row],
+            |c| match c {
+                Column::Wire(i) => W[*i][row],
+                Column::RotatedWire(i, rotation) => {
+                    let n = W[*i].len() as isize;
+                    W[*i][(row as isize + rotation).rem_euclid(n) as usize]
+                }
+                Column::PublicInput => X.get(row).copied().unwrap_or(F::ZERO),
+            },
+        )
+    }
+
+    /// the homogenized gate over the given values of its selectors and columns, wherever they are taken
+    pub(crate) fn evaluate_with(
+        &self,
+        u: &F,
+        selector: impl Fn(usize) -> F,
+        column: impl Fn(&Column) -> F,
+    ) -> F {
+        let u_powers = (0..self.degree).fold(vec![F::ONE], |mut acc, _| {
+            acc.push(*acc.last().unwrap() * u);
+            acc
+        });
+        self.terms.iter().fold(F::ZERO, |acc, term| {
+            let q = term.selectors.iter().fold(F::ONE, |q, s| q * selector(*s));
+            if q.is_zero() {
+                return acc;
+            }
+            let monomial = term.columns.iter().fold(F::ONE, |m, c| m * column(c));
+            acc + term.coeff * q * u_powers[self.degree - term.columns.len()] * monomial
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        error::MyError,
+        nifs::{CompressedNIFS, NIFS},
+        plonk::{
+            CompressedRelaxedPLONKInstance, CompressedRelaxedPLONKWitness, PLONKInstance,
+            PLONKShape, PLONKWitness, RelaxedPLONKInstance, RelaxedPLONKWitness,
+        },
+        provider::grumpkin::Grumpkin,
+        traits::{CommitmentEngineTrait, Group},
+    };
+    use ark_bn254::Bn254;
+    use ark_ff::{Field, UniformRand};
+    use jf_utils::test_rng;
+    use rand::Rng;
+
+    /// q_range * w_0 * (w_0 - 1) * (w_0 - 2) * (w_0 - 3) + q_pow * w_1^7 + PI - q_o * w_2
+    fn range_pow_gate<F: PrimeField>() -> CustomGate<F> {
+        let w = Column::Wire;
+        let range = [
+            (F::ONE, 4),
+            (-F::from(6u64), 3),
+            (F::from(11u64), 2),
+            (-F::from(6u64), 1),
+        ]
+        .into_iter()
+        .map(|(c, d)| GateTerm::new(c, Some(0), &vec![w(0); d]));
+        let terms = range
+            .chain([
+                GateTerm::new(F::ONE, Some(1), &[w(1); 7]),
+                GateTerm::new(F::ONE, None, &[Column::PublicInput]),
+                GateTerm::new(-F::ONE, Some(2), &[w(2)]),
+            ])
+            .collect();
+        CustomGate::new(3, terms)
+    }
+
+    fn test_custom_gate_fold_with<G: Group>() {
+        let rng = &mut test_rng();
+        let (num_cons, num_public_input) = (8, 2);
+        let ck = G::CE::setup(rng, num_cons);
+        let pp_digest = G::ScalarField::rand(rng);
+
+        let selectors = vec![
+            (0..num_cons).map(|_| G::ScalarField::rand(rng)).collect(),
+            vec![G::ScalarField::ONE; num_cons],
+            vec![G::ScalarField::ONE; num_cons],
+        ];
+        let sigma = (0..3)
+            .map(|i| (0..num_cons).map(|j| i * num_cons + j).collect())
+            .collect::<Vec<Vec<usize>>>();
+        let S = PLONKShape::<G>::new_with_gate(
+            num_cons,
+            3,
+            num_public_input,
+            range_pow_gate(),
+            selectors,
+            &sigma,
+        )
+        .unwrap();
+        assert_eq!(S.num_error_columns(), 6);
+
+        let mut running_U = RelaxedPLONKInstance::default(&ck, &S);
+        let mut running_W = RelaxedPLONKWitness::default(&S);
+        for _ in 0..3 {
+            let X = (0..num_public_input)
+                .map(|_| G::ScalarField::rand(rng))
+                .collect::<Vec<_>>();
+            let w_0 = (0..num_cons)
+                .map(|_| G::ScalarField::from(rng.gen_range(0..4u64)))
+                .collect::<Vec<_>>();
+            let w_1 = (0..num_cons)
+                .map(|_| G::ScalarField::rand(rng))
+                .collect::<Vec<_>>();
+            let w_2 = (0..num_cons)
+                .map(|i| w_1[i].pow([7u64]) + X.get(i).copied().unwrap_or(G::ScalarField::ZERO))
+                .collect::<Vec<_>>();
+            let W = PLONKWitness::new(&S, &[w_0, w_1, w_2]).unwrap();
+            let U = PLONKInstance::new(&S, &W.commit(&ck), &X).unwrap();
+            S.is_sat(&ck, &U, &W).unwrap();
+
+            let (nifs, (folded_U, folded_W)) =
+                NIFS::prove(&ck, &pp_digest, &S, &running_U, &running_W, &U, &W).unwrap();
+            assert_eq!(nifs.comm_T.len(), 6);
+            assert_eq!(nifs.verify(&pp_digest, &running_U, &U).unwrap(), folded_U);
+            S.is_sat_relaxed(&ck, &folded_U, &folded_W).unwrap();
+            running_U = folded_U;
+            running_W = folded_W;
+        }
+
+        // a value out of the range breaks the gate
+        let mut W = PLONKWitness::new(&S, &vec![vec![G::ScalarField::ZERO; num_cons]; 3]).unwrap();
+        W.W[0][0] = G::ScalarField::from(4u64);
+        let U = PLONKInstance::new(&S, &W.commit(&ck), &[G::ScalarField::ZERO; 2]).unwrap();
+        assert_eq!(S.is_sat(&ck, &U, &W), Err(MyError::UnSatIndex(0)));
+    }
+
+    #[test]
+    fn test_custom_gate_fold() {
+        test_custom_gate_fold_with::<Bn254>();
+        test_custom_gate_fold_with::<Grumpkin>();
+    }
+
+    fn test_gate_dsl_with<G: Group>() {
+        let rng = &mut test_rng();
+        let (num_cons, num_public_input) = (8, 2);
+        let ck = G::CE::setup(rng, num_cons);
+        let pp_digest = G::ScalarField::rand(rng);
+
+        // like terms cancel out
+        let w = Expression::<G::ScalarField>::wire;
+        let square = (w(0) + w(1)).pow(2)
+            - w(0) * w(0)
+            - w(1) * w(1)
+            - Expression::constant(G::ScalarField::from(2u64)) * w(0) * w(1);
+        assert!(GateBuilder::new().build(&square).terms.is_empty());
+        assert_eq!(w(0).pow(0), Expression::constant(G::ScalarField::ONE));
+        assert_eq!(GateBuilder::new().build(&(w(0).pow(0) * w(1))).degree, 1);
+
+        // an accumulator over the rows, w_0' = w_0 * w_1 + PI, with bits in w_1
+        let mut builder = GateBuilder::new();
+        let (q_acc, q_bool) = (builder.selector("q_acc"), builder.selector("q_bool"));
+        let acc = Expression::rotated_wire(0, 1) - w(0) * w(1) - Expression::public_input();
+        let bool = w(1) * w(1) - w(1);
+        let gate = builder.build(&(q_acc * acc + q_bool * bool));
+        assert_eq!(gate.degree, 2);
+
+        let mut q_acc = vec![G::ScalarField::ONE; num_cons];
+        q_acc[num_cons - 1] = G::ScalarField::ZERO;
+        let q_bool = (0..num_cons).map(|_| G::ScalarField::rand(rng)).collect();
+        let sigma = (0..2)
+            .map(|i| (0..num_cons).map(|j| i * num_cons + j).collect())
+            .collect::<Vec<Vec<usize>>>();
+        let S = PLONKShape::<G>::new_with_gate(
+            num_cons,
+            2,
+            num_public_input,
+            gate,
+            vec![q_acc.clone(), q_bool],
+            &sigma,
+        )
+        .unwrap();
+        assert_eq!(S.selector("q_acc"), Some(q_acc.as_slice()));
+        assert_eq!(S.num_error_columns(), 1);
+
+        let mut random_pair = || {
+            let X = (0..num_public_input)
+                .map(|_| G::ScalarField::rand(rng))
+                .collect::<Vec<_>>();
+            let w_1 = (0..num_cons)
+                .map(|_| G::ScalarField::from(rng.gen_bool(0.5)))
+                .collect::<Vec<_>>();
+            let w_0 = (1..num_cons).fold(vec![G::ScalarField::rand(rng)], |mut acc, i| {
+                let pi = X.get(i - 1).copied().unwrap_or(G::ScalarField::ZERO);
+                acc.push(acc[i - 1] * w_1[i - 1] + pi);
+                acc
+            });
+            let W = PLONKWitness::new(&S, &[w_0, w_1]).unwrap();
+            let U = PLONKInstance::new(&S, &W.commit(&ck), &X).unwrap();
+            (U, W)
+        };
+
+        let mut running_U = RelaxedPLONKInstance::default(&ck, &S);
+        let mut running_W = RelaxedPLONKWitness::default(&S);
+        let mut compressed_U = CompressedRelaxedPLONKInstance::default(&ck, &S);
+        let mut compressed_W = CompressedRelaxedPLONKWitness::default(&S);
+        for _ in 0..3 {
+            let (U, W) = random_pair();
+            S.is_sat(&ck, &U, &W).unwrap();
+
+            let (_, (folded_U, folded_W)) =
+                NIFS::prove(&ck, &pp_digest, &S, &running_U, &running_W, &U, &W).unwrap();
+            S.is_sat_relaxed(&ck, &folded_U, &folded_W).unwrap();
+            (running_U, running_W) = (folded_U, folded_W);
+
+            let (_, (folded_U, folded_W)) =
+                CompressedNIFS::prove(&ck, &pp_digest, &S, &compressed_U, &compressed_W, &U, &W)
+                    .unwrap();
+            S.is_sat_compressed(&ck, &folded_U, &folded_W).unwrap();
+            (compressed_U, compressed_W) = (folded_U, folded_W);
+        }
+
+        // the rotated wire ties a row to the previous one
+        let (U, mut W) = random_pair();
+        W.W[0][5] += G::ScalarField::ONE;
+        let U = PLONKInstance::new(&S, &W.commit(&ck), &U.X).unwrap();
+        assert_eq!(S.is_sat(&ck, &U, &W), Err(MyError::UnSatIndex(4)));
+    }
+
+    #[test]
+    fn test_gate_dsl() {
+        test_gate_dsl_with::<Bn254>();
+        test_gate_dsl_with::<Grumpkin>();
+    }
+}
@@ -0,0 +1,214 @@
+/// extracts PLONK shapes, instances and witnesses from finalized Jellyfish TurboPlonk circuits
+///
+/// selectors, wires and copy permutations of a finalized circuit are handed out as polynomials over its
+/// evaluation domain, here they are evaluated back over the same domain so that every row is one gate
+///
+use ark_ff::PrimeField;
+use ark_poly::{univariate::DensePolynomial, EvaluationDomain, Radix2EvaluationDomain};
+use jf_relation::{
+    constants::{compute_coset_representatives, GATE_WIDTH, N_MUL_SELECTORS},
+    Arithmetization, Circuit, PlonkCircuit,
+};
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+use crate::{
+    error::MyError,
+    plonk::{PLONKInstance, PLONKShape, PLONKWitness},
+    traits::Group,
+    CommitmentKey,
+};
+
+/// `JellyfishShape` provides a method for acquiring a `PLONKShape` from a finalized circuit
+pub trait JellyfishShape<G: Group> {
+    /// Return the `PLONKShape` of the circuit
+    fn plonk_shape(&self) -> Result<PLONKShape<G>, MyError>;
+}
+
+/// `JellyfishWitness` provides a method for acquiring a `PLONKInstance` and `PLONKWitness` from a finalized circuit
+pub trait JellyfishWitness<G: Group> {
+    /// Return the instance and witness of the circuit, committing to the witness with `ck`
+    fn plonk_instance_and_witness(
+        &self,
+        S: &PLONKShape<G>,
+        ck: &CommitmentKey<G>,
+    ) -> Result<(PLONKInstance<G>, PLONKWitness<G>), MyError>;
+}
+
+/// evaluation domain of a finalized TurboPlonk circuit, lookup circuits are not supported
+fn eval_domain<F: PrimeField>(
+    circuit: &PlonkCircuit<F>,
+) -> Result<Radix2EvaluationDomain<F>, MyError> {
+    if circuit.support_lookup() || circuit.num_wire_types() != GATE_WIDTH + 1 {
+        return Err(MyError::CircuitError);
+    }
+    let size = circuit
+        .eval_domain_size()
+        .map_err(|_| MyError::CircuitError)?;
+    Radix2EvaluationDomain::<F>::new(size).ok_or(MyError::CircuitError)
+}
+
+/// evaluations of the polynomials over the domain
+fn evaluations<F: PrimeField>(
+    domain: &Radix2EvaluationDomain<F>,
+    polys: Vec<DensePolynomial<F>>,
+) -> Vec<Vec<F>> {
+    polys
+        .into_par_iter()
+        .map(|p| domain.fft(&p.coeffs))
+        .collect()
+}
+
+impl<G: Group> JellyfishShape<G> for PlonkCircuit<<G as Group>::ScalarField> {
+    fn plonk_shape(&self) -> Result<PLONKShape<G>, MyError> {
+        let domain = eval_domain(self)?;
+        let selectors = self
+            .compute_selector_polynomials()
+            .map_err(|_| MyError::CircuitError)?;
+        // q_lc, q_mul, q_hash, q_o, q_c, q_ecc
+        if selectors.len() != 2 * GATE_WIDTH + N_MUL_SELECTORS + 3 {
+            return Err(MyError::SelectorError);
+        }
+        let mut selectors = evaluations(&domain, selectors);
+        let q_ecc = selectors.pop().unwrap();
+        let q_c = selectors.pop().unwrap();
+        let q_o = selectors.pop().unwrap();
+        let q_hash = selectors.split_off(GATE_WIDTH + N_MUL_SELECTORS);
+        let q_mul = selectors.split_off(GATE_WIDTH);
+        let q_lc = selectors;
+
+        // wire i at row j is identified by k_i * w^j, map sigma evaluations back to flattened positions
+        let n = domain.size();
+        let k = compute_coset_representatives::<<G as Group>::ScalarField>(
+            self.num_wire_types(),
+            Some(n),
+        );
+        let positions = k
+            .iter()
+            .enumerate()
+            .flat_map(|(i, k_i)| {
+                domain
+                    .elements()
+                    .enumerate()
+                    .map(move |(j, w_j)| (*k_i * w_j, i * n + j))
+            })
+            .collect::<HashMap<_, _>>();
+        let sigma = evaluations(
+            &domain,
+            self.compute_extended_permutation_polynomials()
+                .map_err(|_| MyError::CircuitError)?,
+        )
+        .iter()
+        .map(|col| {
+            col.iter()
+                .map(|v| positions.get(v).copied().ok_or(MyError::PermutationError))
+                .collect::<Result<Vec<usize>, MyError>>()
+        })
+        .collect::<Result<Vec<Vec<usize>>, MyError>>()?;
+
+        PLONKShape::new(
+            domain.size(),
+            self.num_wire_types(),
+            self.num_inputs(),
+            &q_c,
+            &q_lc,
+            &q_mul,
+            &q_ecc,
+            &q_hash,
+            &q_o,
+            &sigma,
+        )
+    }
+}
+
+impl<G: Group> JellyfishWitness<G> for PlonkCircuit<<G as Group>::ScalarField> {
+    fn plonk_instance_and_witness(
+        &self,
+        S: &PLONKShape<G>,
+        ck: &CommitmentKey<G>,
+    ) -> Result<(PLONKInstance<G>, PLONKWitness<G>), MyError> {
+        let domain = eval_domain(self)?;
+        if domain.size() != S.num_cons {
+            return Err(MyError::CircuitError);
+        }
+        let wires = self
+            .compute_wire_polynomials()
+            .map_err(|_| MyError::CircuitError)?;
+        // public inputs sit on the leading io gates of a finalized circuit
+        let X = self.public_input().map_err(|_| MyError::CircuitError)?;
+
+        let W = PLONKWitness::new(S, &evaluations(&domain, wires))?;
+        let U = PLONKInstance::new(S, &W.commit(ck), &X)?;
+        Ok((U, W))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        nifs::NIFS,
+        plonk::{RelaxedPLONKInstance, RelaxedPLONKWitness},
+        traits::CommitmentEngineTrait,
+    };
+    use ark_bn254::{Bn254, Fr};
+    use ark_ff::UniformRand;
+    use jf_utils::test_rng;
+
+    /// y = (x * z + x)^5 + 2 * x - z, with x and y public
+    fn synthesize(x: Fr, z: Fr) -> PlonkCircuit<Fr> {
+        let mut circuit = PlonkCircuit::<Fr>::new_turbo_plonk();
+        let x_var = circuit.create_public_variable(x).unwrap();
+        let z_var = circuit.create_variable(z).unwrap();
+        let xz_var = circuit.mul(x_var, z_var).unwrap();
+        let sum_var = circuit.add(xz_var, x_var).unwrap();
+        let pow_var = circuit.power_5_gen(sum_var).unwrap();
+        let zero_var = circuit.zero();
+        let y_var = circuit
+            .lc(
+                &[pow_var, x_var, z_var, zero_var],
+                &[
+                    Fr::from(1u64),
+                    Fr::from(2u64),
+                    -Fr::from(1u64),
+                    Fr::from(0u64),
+                ],
+            )
+            .unwrap();
+        circuit.set_variable_public(y_var).unwrap();
+        circuit.finalize_for_arithmetization().unwrap();
+        circuit
+    }
+
+    #[test]
+    fn test_jellyfish_fold() {
+        let rng = &mut test_rng();
+        let circuit = synthesize(Fr::rand(rng), Fr::rand(rng));
+        let S: PLONKShape<Bn254> = circuit.plonk_shape().unwrap();
+        let ck = <Bn254 as Group>::CE::setup(rng, S.num_cons);
+        let pp_digest = Fr::rand(rng);
+
+        let mut running_U = RelaxedPLONKInstance::default(&ck, &S);
+        let mut running_W = RelaxedPLONKWitness::default(&S);
+        for _ in 0..2 {
+            let circuit = synthesize(Fr::rand(rng), Fr::rand(rng));
+            assert_eq!(circuit.plonk_shape().unwrap(), S);
+            let (U, W) = circuit.plonk_instance_and_witness(&S, &ck).unwrap();
+            assert_eq!(U.X, circuit.public_input().unwrap());
+            S.is_sat(&ck, &U, &W).unwrap();
+
+            let (_, (folded_U, folded_W)) =
+                NIFS::prove(&ck, &pp_digest, &S, &running_U, &running_W, &U, &W).unwrap();
+            S.is_sat_relaxed(&ck, &folded_U, &folded_W).unwrap();
+            running_U = folded_U;
+            running_W = folded_W;
+        }
+
+        // the padding gates accept any value, only their wiring to the zero variable pins it down
+        let (U, mut W) = circuit.plonk_instance_and_witness(&S, &ck).unwrap();
+        let last = S.num_cons - 1;
+        W.W[0][last] = Fr::from(1u64);
+        let U = PLONKInstance::new(&S, &W.commit(&ck), &U.X).unwrap();
+        assert!(matches!(S.is_sat(&ck, &U, &W), Err(MyError::UnSatCopy(..))));
+    }
+}
@@ -1,12 +1,732 @@
+#![allow(non_snake_case)]
+
+pub mod circuit;
+mod constants;
 pub mod error;
+pub mod gadgets;
+pub mod gate;
+pub mod jellyfish;
+pub mod lookup;
+pub mod memory;
 pub mod nifs;
 pub mod plonk;
 pub mod poseidon;
 pub mod primary;
+pub mod protogalaxy;
 pub mod provider;
 pub mod secondary;
+pub mod snark;
 pub mod traits;
 
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{BigInteger, Field, PrimeField};
+use circuit::{CircuitGroup, PlonkishCircuit};
+use constants::{BN_LIMB_WIDTH, BN_N_LIMBS, NUM_HASH_BITS};
+use error::MyError;
+use nifs::CompressedNIFS;
+use plonk::{
+    CompressedRelaxedPLONKInstance, CompressedRelaxedPLONKWitness, PLONKInstance, PLONKShape,
+    PLONKWitness,
+};
+use primary::circuit::{hash_state, AugmentedCircuit, AugmentedCircuitInputs, StepCircuit};
+use rand::rngs::StdRng;
+use sha3::{Digest, Keccak256};
+use snark::RelaxedPLONKSNARK;
+use std::marker::PhantomData;
+use traits::{CommitmentEngineTrait, CurveCycle, EvaluationEngineTrait, Group};
+
 type Commitment<G> = <<G as traits::Group>::CE as traits::CommitmentEngineTrait<G>>::Commitment;
 type CommitmentKey<G> =
     <<G as traits::Group>::CE as traits::CommitmentEngineTrait<G>>::CommitmentKey;
+type ROConstants<G> = <<G as traits::Group>::RO as traits::ROTrait<
+    <G as traits::Group>::BaseField,
+    <G as traits::Group>::ScalarField,
+>>::Constants;
+
+/// splits a scalar into little-endian limbs of `BN_LIMB_WIDTH` bits, each of which fits into the base field,
+/// so that scalars can be absorbed into the base-field random oracle
+pub(crate) fn scalar_as_limbs<G: traits::Group>(
+    s: &<G as traits::Group>::ScalarField,
+) -> Vec<<G as traits::Group>::BaseField> {
+    s.into_bigint()
+        .to_bits_le()
+        .chunks(BN_LIMB_WIDTH)
+        .take(BN_N_LIMBS)
+        .map(|limb| {
+            <<G as traits::Group>::BaseField as PrimeField>::from_bigint(
+                <<G as traits::Group>::BaseField as PrimeField>::BigInt::from_bits_le(limb),
+            )
+            .unwrap()
+        })
+        .collect()
+}
+
+/// affine coordinates of a point together with its infinity flag, the identity maps to (0, 0, true)
+pub(crate) fn to_coordinates<G: traits::Group>(
+    p: &<<G as traits::Group>::Curve as CurveGroup>::Affine,
+) -> (
+    <G as traits::Group>::BaseField,
+    <G as traits::Group>::BaseField,
+    bool,
+) {
+    match p.xy() {
+        Some((x, y)) => (*x, *y, false),
+        None => (
+            <<G as traits::Group>::BaseField as Field>::ZERO,
+            <<G as traits::Group>::BaseField as Field>::ZERO,
+            true,
+        ),
+    }
+}
+
+/// reinterprets a scalar as a base field element, lossless for values below both moduli such as digests and hashes
+pub(crate) fn scalar_as_base<G: traits::Group>(
+    s: &<G as traits::Group>::ScalarField,
+) -> <G as traits::Group>::BaseField {
+    <<G as traits::Group>::BaseField as PrimeField>::from_le_bytes_mod_order(
+        &s.into_bigint().to_bytes_le(),
+    )
+}
+
+/// A type that holds public parameters of the recursion over a curve cycle
+#[derive(Clone)]
+pub struct PublicParams<C, C1, C2>
+where
+    C: CurveCycle,
+    C1: StepCircuit<<C::G1 as Group>::ScalarField>,
+    C2: StepCircuit<<C::G2 as Group>::ScalarField>,
+{
+    F_arity_primary: usize,
+    F_arity_secondary: usize,
+    ck_primary: CommitmentKey<C::G1>,
+    S_primary: PLONKShape<C::G1>,
+    ck_secondary: CommitmentKey<C::G2>,
+    S_secondary: PLONKShape<C::G2>,
+    digest: <C::G1 as Group>::ScalarField,
+    _p: PhantomData<(C1, C2)>,
+}
+
+/// synthesizes an augmented circuit, returning it together with the outputs z_{i+1} of its step
+#[allow(clippy::type_complexity)]
+fn synthesize_augmented<G: CircuitGroup, SC: StepCircuit<G::BaseField>>(
+    circuit: &AugmentedCircuit<G, SC>,
+) -> Result<(PlonkishCircuit<G::BaseField>, Vec<G::BaseField>), MyError> {
+    let mut cs = PlonkishCircuit::<G::BaseField>::new();
+    let z_next = circuit
+        .synthesize(&mut cs)?
+        .iter()
+        .map(|v| cs.witness(*v))
+        .collect::<Result<Vec<_>, MyError>>()?;
+    Ok((cs, z_next))
+}
+
+impl<C, C1, C2> PublicParams<C, C1, C2>
+where
+    C: CurveCycle,
+    C::G1: CircuitGroup,
+    C::G2: CircuitGroup,
+    C1: StepCircuit<<C::G1 as Group>::ScalarField>,
+    C2: StepCircuit<<C::G2 as Group>::ScalarField>,
+{
+    /// creates shapes and commitment keys for the augmented circuits on both curves
+    pub fn setup(rng: &mut StdRng, c_primary: &C1, c_secondary: &C2) -> Result<Self, MyError> {
+        let F_arity_primary = c_primary.arity();
+        let F_arity_secondary = c_secondary.arity();
+
+        let circuit_primary: AugmentedCircuit<C::G2, C1> =
+            AugmentedCircuit::new(true, None, c_primary);
+        let (cs_primary, _) = synthesize_augmented(&circuit_primary)?;
+        let S_primary: PLONKShape<C::G1> = cs_primary.plonk_shape()?;
+        let ck_primary = <C::G1 as Group>::CE::setup(rng, S_primary.num_cons);
+
+        let circuit_secondary: AugmentedCircuit<C::G1, C2> =
+            AugmentedCircuit::new(false, None, c_secondary);
+        let (cs_secondary, _) = synthesize_augmented(&circuit_secondary)?;
+        let S_secondary: PLONKShape<C::G2> = cs_secondary.plonk_shape()?;
+        let ck_secondary = <C::G2 as Group>::CE::setup(rng, S_secondary.num_cons);
+
+        // truncated so that the digest is a valid scalar on both curves
+        let mut digest = Keccak256::new();
+        digest.update(S_primary.digest());
+        digest.update(S_secondary.digest());
+        let bits = digest
+            .finalize()
+            .iter()
+            .flat_map(|b| (0..8).map(move |i| (b >> i) & 1 == 1))
+            .take(NUM_HASH_BITS)
+            .collect::<Vec<bool>>();
+        let digest = <<C::G1 as Group>::ScalarField as PrimeField>::from_bigint(
+            <<C::G1 as Group>::ScalarField as PrimeField>::BigInt::from_bits_le(&bits),
+        )
+        .ok_or(MyError::HashError)?;
+
+        Ok(PublicParams {
+            F_arity_primary,
+            F_arity_secondary,
+            ck_primary,
+            S_primary,
+            ck_secondary,
+            S_secondary,
+            digest,
+            _p: PhantomData,
+        })
+    }
+}
+
+impl<C, C1, C2> PublicParams<C, C1, C2>
+where
+    C: CurveCycle,
+    C1: StepCircuit<<C::G1 as Group>::ScalarField>,
+    C2: StepCircuit<<C::G2 as Group>::ScalarField>,
+{
+    /// the digest as a scalar of the secondary curve
+    fn digest_secondary(&self) -> <C::G2 as Group>::ScalarField {
+        scalar_as_base::<C::G1>(&self.digest)
+    }
+}
+
+/// A SNARK that proves the correct execution of an incremental computation
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecursiveSNARK<C, C1, C2>
+where
+    C: CurveCycle,
+    C1: StepCircuit<<C::G1 as Group>::ScalarField>,
+    C2: StepCircuit<<C::G2 as Group>::ScalarField>,
+{
+    r_W_primary: CompressedRelaxedPLONKWitness<C::G1>,
+    r_U_primary: CompressedRelaxedPLONKInstance<C::G1>,
+    r_W_secondary: CompressedRelaxedPLONKWitness<C::G2>,
+    r_U_secondary: CompressedRelaxedPLONKInstance<C::G2>,
+    l_w_secondary: PLONKWitness<C::G2>,
+    l_u_secondary: PLONKInstance<C::G2>,
+    i: usize,
+    z0_primary: Vec<<C::G1 as Group>::ScalarField>,
+    z0_secondary: Vec<<C::G2 as Group>::ScalarField>,
+    zi_primary: Vec<<C::G1 as Group>::ScalarField>,
+    zi_secondary: Vec<<C::G2 as Group>::ScalarField>,
+    _p: PhantomData<(C1, C2)>,
+}
+
+impl<C, C1, C2> RecursiveSNARK<C, C1, C2>
+where
+    C: CurveCycle,
+    C::G1: CircuitGroup,
+    C::G2: CircuitGroup,
+    C1: StepCircuit<<C::G1 as Group>::ScalarField>,
+    C2: StepCircuit<<C::G2 as Group>::ScalarField>,
+{
+    /// runs the first step on both curves, the primary instance is folded into the default running one
+    /// while the secondary one is left to be folded in the next step
+    pub fn new(
+        pp: &PublicParams<C, C1, C2>,
+        c_primary: &C1,
+        c_secondary: &C2,
+        z0_primary: &[<C::G1 as Group>::ScalarField],
+        z0_secondary: &[<C::G2 as Group>::ScalarField],
+    ) -> Result<Self, MyError> {
+        if z0_primary.len() != pp.F_arity_primary || z0_secondary.len() != pp.F_arity_secondary {
+            return Err(MyError::InvalidInitialInputLength);
+        }
+
+        let inputs_primary = AugmentedCircuitInputs::<C::G2>::new(
+            pp.digest_secondary(),
+            0,
+            z0_primary.to_vec(),
+            None,
+            None,
+            None,
+            None,
+        );
+        let circuit_primary = AugmentedCircuit::new(true, Some(inputs_primary), c_primary);
+        let (cs_primary, zi_primary) = synthesize_augmented(&circuit_primary)?;
+        let (u_primary, w_primary) =
+            cs_primary.plonk_instance_and_witness(&pp.S_primary, &pp.ck_primary)?;
+        let (nifs_primary, (r_U_primary, r_W_primary)) = CompressedNIFS::prove(
+            &pp.ck_primary,
+            &pp.digest,
+            &pp.S_primary,
+            &CompressedRelaxedPLONKInstance::default(&pp.ck_primary, &pp.S_primary),
+            &CompressedRelaxedPLONKWitness::default(&pp.S_primary),
+            &u_primary,
+            &w_primary,
+        )?;
+
+        let inputs_secondary = AugmentedCircuitInputs::<C::G1>::new(
+            pp.digest,
+            0,
+            z0_secondary.to_vec(),
+            None,
+            None,
+            Some(u_primary),
+            Some(nifs_primary),
+        );
+        let circuit_secondary = AugmentedCircuit::new(false, Some(inputs_secondary), c_secondary);
+        let (cs_secondary, zi_secondary) = synthesize_augmented(&circuit_secondary)?;
+        let (l_u_secondary, l_w_secondary) =
+            cs_secondary.plonk_instance_and_witness(&pp.S_secondary, &pp.ck_secondary)?;
+
+        Ok(RecursiveSNARK {
+            r_W_primary,
+            r_U_primary,
+            r_W_secondary: CompressedRelaxedPLONKWitness::default(&pp.S_secondary),
+            r_U_secondary: CompressedRelaxedPLONKInstance::default(
+                &pp.ck_secondary,
+                &pp.S_secondary,
+            ),
+            l_w_secondary,
+            l_u_secondary,
+            i: 1,
+            z0_primary: z0_primary.to_vec(),
+            z0_secondary: z0_secondary.to_vec(),
+            zi_primary,
+            zi_secondary,
+            _p: PhantomData,
+        })
+    }
+
+    /// folds the pending secondary instance, whose folding the primary circuit of this step verifies,
+    /// then folds the new primary instance, whose folding the secondary circuit verifies, leaving it pending
+    pub fn prove_step(
+        &mut self,
+        pp: &PublicParams<C, C1, C2>,
+        c_primary: &C1,
+        c_secondary: &C2,
+    ) -> Result<(), MyError> {
+        let (nifs_secondary, (r_U_secondary, r_W_secondary)) = CompressedNIFS::prove(
+            &pp.ck_secondary,
+            &pp.digest_secondary(),
+            &pp.S_secondary,
+            &self.r_U_secondary,
+            &self.r_W_secondary,
+            &self.l_u_secondary,
+            &self.l_w_secondary,
+        )?;
+
+        let inputs_primary = AugmentedCircuitInputs::<C::G2>::new(
+            pp.digest_secondary(),
+            self.i,
+            self.z0_primary.clone(),
+            Some(self.zi_primary.clone()),
+            Some(self.r_U_secondary.clone()),
+            Some(self.l_u_secondary.clone()),
+            Some(nifs_secondary),
+        );
+        let circuit_primary = AugmentedCircuit::new(true, Some(inputs_primary), c_primary);
+        let (cs_primary, zi_primary) = synthesize_augmented(&circuit_primary)?;
+        let (l_u_primary, l_w_primary) =
+            cs_primary.plonk_instance_and_witness(&pp.S_primary, &pp.ck_primary)?;
+
+        let (nifs_primary, (r_U_primary, r_W_primary)) = CompressedNIFS::prove(
+            &pp.ck_primary,
+            &pp.digest,
+            &pp.S_primary,
+            &self.r_U_primary,
+            &self.r_W_primary,
+            &l_u_primary,
+            &l_w_primary,
+        )?;
+
+        let inputs_secondary = AugmentedCircuitInputs::<C::G1>::new(
+            pp.digest,
+            self.i,
+            self.z0_secondary.clone(),
+            Some(self.zi_secondary.clone()),
+            Some(self.r_U_primary.clone()),
+            Some(l_u_primary),
+            Some(nifs_primary),
+        );
+        let circuit_secondary = AugmentedCircuit::new(false, Some(inputs_secondary), c_secondary);
+        let (cs_secondary, zi_secondary) = synthesize_augmented(&circuit_secondary)?;
+        let (l_u_secondary, l_w_secondary) =
+            cs_secondary.plonk_instance_and_witness(&pp.S_secondary, &pp.ck_secondary)?;
+
+        self.r_U_primary = r_U_primary;
+        self.r_W_primary = r_W_primary;
+        self.r_U_secondary = r_U_secondary;
+        self.r_W_secondary = r_W_secondary;
+        self.l_u_secondary = l_u_secondary;
+        self.l_w_secondary = l_w_secondary;
+        self.zi_primary = zi_primary;
+        self.zi_secondary = zi_secondary;
+        self.i += 1;
+        Ok(())
+    }
+
+    /// checks that the pending secondary instance exposes the hashes of the states on both curves,
+    /// and that the running instances and the pending one are satisfied, returning the outputs z_i of the last step
+    #[allow(clippy::type_complexity)]
+    pub fn verify(
+        &self,
+        pp: &PublicParams<C, C1, C2>,
+        num_steps: usize,
+        z0_primary: &[<C::G1 as Group>::ScalarField],
+        z0_secondary: &[<C::G2 as Group>::ScalarField],
+    ) -> Result<
+        (
+            Vec<<C::G1 as Group>::ScalarField>,
+            Vec<<C::G2 as Group>::ScalarField>,
+        ),
+        MyError,
+    > {
+        if num_steps == 0 || num_steps != self.i {
+            return Err(MyError::InvalidNumSteps);
+        }
+        if z0_primary.len() != pp.F_arity_primary || z0_secondary.len() != pp.F_arity_secondary {
+            return Err(MyError::InvalidInitialInputLength);
+        }
+        if self.l_u_secondary.X.len() != 2
+            || self.r_U_primary.X.len() != 2
+            || self.r_U_secondary.X.len() != 2
+        {
+            return Err(MyError::ProofVerifyError);
+        }
+
+        let hash_primary = hash_state::<C::G2>(
+            &pp.digest_secondary(),
+            num_steps,
+            z0_primary,
+            &self.zi_primary,
+            &self.r_U_secondary,
+        );
+        let hash_secondary = hash_state::<C::G1>(
+            &pp.digest,
+            num_steps,
+            z0_secondary,
+            &self.zi_secondary,
+            &self.r_U_primary,
+        );
+        if hash_primary != self.l_u_secondary.X[0]
+            || scalar_as_base::<C::G1>(&hash_secondary) != self.l_u_secondary.X[1]
+        {
+            return Err(MyError::ProofVerifyError);
+        }
+
+        pp.S_primary
+            .is_sat_compressed(&pp.ck_primary, &self.r_U_primary, &self.r_W_primary)?;
+        pp.S_secondary.is_sat_compressed(
+            &pp.ck_secondary,
+            &self.r_U_secondary,
+            &self.r_W_secondary,
+        )?;
+        pp.S_secondary
+            .is_sat(&pp.ck_secondary, &self.l_u_secondary, &self.l_w_secondary)?;
+
+        Ok((self.zi_primary.clone(), self.zi_secondary.clone()))
+    }
+}
+
+/// the keys of the deciders on both curves
+#[derive(Clone, Debug)]
+pub struct ProverKey<C, C1, C2, EE1, EE2>
+where
+    C: CurveCycle,
+    C1: StepCircuit<<C::G1 as Group>::ScalarField>,
+    C2: StepCircuit<<C::G2 as Group>::ScalarField>,
+    EE1: EvaluationEngineTrait<C::G1, CE = <C::G1 as Group>::CE>,
+    EE2: EvaluationEngineTrait<C::G2, CE = <C::G2 as Group>::CE>,
+{
+    pk_primary: snark::ProverKey<C::G1, EE1>,
+    pk_secondary: snark::ProverKey<C::G2, EE2>,
+    _p: PhantomData<(C1, C2)>,
+}
+
+/// the keys of the deciders on both curves, together with what the verifier needs from the public parameters
+#[derive(Clone, Debug)]
+pub struct VerifierKey<C, C1, C2, EE1, EE2>
+where
+    C: CurveCycle,
+    C1: StepCircuit<<C::G1 as Group>::ScalarField>,
+    C2: StepCircuit<<C::G2 as Group>::ScalarField>,
+    EE1: EvaluationEngineTrait<C::G1, CE = <C::G1 as Group>::CE>,
+    EE2: EvaluationEngineTrait<C::G2, CE = <C::G2 as Group>::CE>,
+{
+    F_arity_primary: usize,
+    F_arity_secondary: usize,
+    digest: <C::G1 as Group>::ScalarField,
+    vk_primary: snark::VerifierKey<C::G1, EE1>,
+    vk_secondary: snark::VerifierKey<C::G2, EE2>,
+    _p: PhantomData<(C1, C2)>,
+}
+
+/// A SNARK that proves the knowledge of a valid RecursiveSNARK, by folding its pending secondary instance
+/// and proving both running instances with the deciders
+#[derive(Clone, Debug)]
+pub struct CompressedSNARK<C, C1, C2, EE1, EE2>
+where
+    C: CurveCycle,
+    C1: StepCircuit<<C::G1 as Group>::ScalarField>,
+    C2: StepCircuit<<C::G2 as Group>::ScalarField>,
+    EE1: EvaluationEngineTrait<C::G1, CE = <C::G1 as Group>::CE>,
+    EE2: EvaluationEngineTrait<C::G2, CE = <C::G2 as Group>::CE>,
+{
+    r_U_primary: CompressedRelaxedPLONKInstance<C::G1>,
+    r_W_snark_primary: RelaxedPLONKSNARK<C::G1, EE1>,
+    r_U_secondary: CompressedRelaxedPLONKInstance<C::G2>,
+    l_u_secondary: PLONKInstance<C::G2>,
+    nifs_secondary: CompressedNIFS<C::G2>,
+    f_W_snark_secondary: RelaxedPLONKSNARK<C::G2, EE2>,
+    zn_primary: Vec<<C::G1 as Group>::ScalarField>,
+    zn_secondary: Vec<<C::G2 as Group>::ScalarField>,
+    _p: PhantomData<(C1, C2)>,
+}
+
+impl<C, C1, C2, EE1, EE2> CompressedSNARK<C, C1, C2, EE1, EE2>
+where
+    C: CurveCycle,
+    C1: StepCircuit<<C::G1 as Group>::ScalarField>,
+    C2: StepCircuit<<C::G2 as Group>::ScalarField>,
+    EE1: EvaluationEngineTrait<C::G1, CE = <C::G1 as Group>::CE>,
+    EE2: EvaluationEngineTrait<C::G2, CE = <C::G2 as Group>::CE>,
+{
+    /// derives the keys of the deciders from the shapes on both curves
+    #[allow(clippy::type_complexity)]
+    pub fn setup(
+        pp: &PublicParams<C, C1, C2>,
+    ) -> Result<
+        (
+            ProverKey<C, C1, C2, EE1, EE2>,
+            VerifierKey<C, C1, C2, EE1, EE2>,
+        ),
+        MyError,
+    > {
+        let (pk_primary, vk_primary) =
+            RelaxedPLONKSNARK::setup_compressed(&pp.ck_primary, &pp.S_primary)?;
+        let (pk_secondary, vk_secondary) =
+            RelaxedPLONKSNARK::setup_compressed(&pp.ck_secondary, &pp.S_secondary)?;
+
+        Ok((
+            ProverKey {
+                pk_primary,
+                pk_secondary,
+                _p: PhantomData,
+            },
+            VerifierKey {
+                F_arity_primary: pp.F_arity_primary,
+                F_arity_secondary: pp.F_arity_secondary,
+                digest: pp.digest,
+                vk_primary,
+                vk_secondary,
+                _p: PhantomData,
+            },
+        ))
+    }
+
+    /// folds the pending secondary instance into the running one, then proves the running instances on both curves
+    pub fn prove(
+        pp: &PublicParams<C, C1, C2>,
+        pk: &ProverKey<C, C1, C2, EE1, EE2>,
+        recursive_snark: &RecursiveSNARK<C, C1, C2>,
+    ) -> Result<Self, MyError> {
+        let (nifs_secondary, (f_U_secondary, f_W_secondary)) = CompressedNIFS::prove(
+            &pp.ck_secondary,
+            &pp.digest_secondary(),
+            &pp.S_secondary,
+            &recursive_snark.r_U_secondary,
+            &recursive_snark.r_W_secondary,
+            &recursive_snark.l_u_secondary,
+            &recursive_snark.l_w_secondary,
+        )?;
+
+        let r_W_snark_primary = RelaxedPLONKSNARK::prove_compressed(
+            &pp.ck_primary,
+            &pk.pk_primary,
+            &recursive_snark.r_U_primary,
+            &recursive_snark.r_W_primary,
+        )?;
+        let f_W_snark_secondary = RelaxedPLONKSNARK::prove_compressed(
+            &pp.ck_secondary,
+            &pk.pk_secondary,
+            &f_U_secondary,
+            &f_W_secondary,
+        )?;
+
+        Ok(CompressedSNARK {
+            r_U_primary: recursive_snark.r_U_primary.clone(),
+            r_W_snark_primary,
+            r_U_secondary: recursive_snark.r_U_secondary.clone(),
+            l_u_secondary: recursive_snark.l_u_secondary.clone(),
+            nifs_secondary,
+            f_W_snark_secondary,
+            zn_primary: recursive_snark.zi_primary.clone(),
+            zn_secondary: recursive_snark.zi_secondary.clone(),
+            _p: PhantomData,
+        })
+    }
+
+    /// checks the hashes of the states as the RecursiveSNARK does, then the last folding of the secondary instance
+    /// and both decider proofs, returning the outputs z_n of the last step
+    #[allow(clippy::type_complexity)]
+    pub fn verify(
+        &self,
+        vk: &VerifierKey<C, C1, C2, EE1, EE2>,
+        num_steps: usize,
+        z0_primary: &[<C::G1 as Group>::ScalarField],
+        z0_secondary: &[<C::G2 as Group>::ScalarField],
+    ) -> Result<
+        (
+            Vec<<C::G1 as Group>::ScalarField>,
+            Vec<<C::G2 as Group>::ScalarField>,
+        ),
+        MyError,
+    > {
+        if num_steps == 0 {
+            return Err(MyError::InvalidNumSteps);
+        }
+        if z0_primary.len() != vk.F_arity_primary || z0_secondary.len() != vk.F_arity_secondary {
+            return Err(MyError::InvalidInitialInputLength);
+        }
+        if self.l_u_secondary.X.len() != 2
+            || self.r_U_primary.X.len() != 2
+            || self.r_U_secondary.X.len() != 2
+        {
+            return Err(MyError::ProofVerifyError);
+        }
+
+        let digest_secondary = scalar_as_base::<C::G1>(&vk.digest);
+        let hash_primary = hash_state::<C::G2>(
+            &digest_secondary,
+            num_steps,
+            z0_primary,
+            &self.zn_primary,
+            &self.r_U_secondary,
+        );
+        let hash_secondary = hash_state::<C::G1>(
+            &vk.digest,
+            num_steps,
+            z0_secondary,
+            &self.zn_secondary,
+            &self.r_U_primary,
+        );
+        if hash_primary != self.l_u_secondary.X[0]
+            || scalar_as_base::<C::G1>(&hash_secondary) != self.l_u_secondary.X[1]
+        {
+            return Err(MyError::ProofVerifyError);
+        }
+
+        let f_U_secondary = self.nifs_secondary.verify(
+            &digest_secondary,
+            &self.r_U_secondary,
+            &self.l_u_secondary,
+        )?;
+
+        self.r_W_snark_primary
+            .verify_compressed(&vk.vk_primary, &self.r_U_primary)?;
+        self.f_W_snark_secondary
+            .verify_compressed(&vk.vk_secondary, &f_U_secondary)?;
+
+        Ok((self.zn_primary.clone(), self.zn_secondary.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        primary::circuit::TrivialTestCircuit,
+        provider::{kzg::KZGEvaluationEngine, Bn254Grumpkin},
+        secondary::ipa::IPAEvaluationEngine,
+    };
+    use jf_relation::Variable;
+    use jf_utils::test_rng;
+
+    type G1 = <Bn254Grumpkin as CurveCycle>::G1;
+    type G2 = <Bn254Grumpkin as CurveCycle>::G2;
+    type F1 = <G1 as Group>::ScalarField;
+    type F2 = <G2 as Group>::ScalarField;
+
+    /// y = x^3 + x + 5
+    #[derive(Clone, Debug, Default)]
+    struct CubicCircuit;
+
+    impl StepCircuit<F1> for CubicCircuit {
+        fn arity(&self) -> usize {
+            1
+        }
+
+        fn synthesize(
+            &self,
+            cs: &mut PlonkishCircuit<F1>,
+            z: &[Variable],
+        ) -> Result<Vec<Variable>, MyError> {
+            let x_sq = cs.mul(z[0], z[0])?;
+            let x_cu = cs.mul(x_sq, z[0])?;
+            let sum = cs.add(x_cu, z[0])?;
+            Ok(vec![cs.add_constant(sum, &F1::from(5u64))?])
+        }
+
+        fn output(&self, z: &[F1]) -> Vec<F1> {
+            vec![z[0] * z[0] * z[0] + z[0] + F1::from(5u64)]
+        }
+    }
+
+    #[test]
+    fn test_recursive_snark() {
+        let rng = &mut test_rng();
+        let (c_primary, c_secondary) = (CubicCircuit, TrivialTestCircuit::<F2>::default());
+        let pp = PublicParams::<Bn254Grumpkin, _, _>::setup(rng, &c_primary, &c_secondary).unwrap();
+
+        let num_steps = 3;
+        let (z0_primary, z0_secondary) = (vec![F1::from(1u64)], vec![F2::from(0u64)]);
+        let mut snark =
+            RecursiveSNARK::new(&pp, &c_primary, &c_secondary, &z0_primary, &z0_secondary).unwrap();
+        for _ in 1..num_steps {
+            snark.prove_step(&pp, &c_primary, &c_secondary).unwrap();
+        }
+
+        let (zn_primary, zn_secondary) = snark
+            .verify(&pp, num_steps, &z0_primary, &z0_secondary)
+            .unwrap();
+        let expected = (0..num_steps).fold(z0_primary.clone(), |z, _| c_primary.output(&z));
+        assert_eq!(zn_primary, expected);
+        assert_eq!(zn_secondary, z0_secondary);
+
+        assert_eq!(
+            snark.verify(&pp, num_steps + 1, &z0_primary, &z0_secondary),
+            Err(MyError::InvalidNumSteps)
+        );
+        // the hashes exposed by the last instance bind the initial inputs
+        assert_eq!(
+            snark.verify(&pp, num_steps, &[F1::from(2u64)], &z0_secondary),
+            Err(MyError::ProofVerifyError)
+        );
+    }
+
+    #[test]
+    fn test_compressed_snark() {
+        let rng = &mut test_rng();
+        let (c_primary, c_secondary) = (CubicCircuit, TrivialTestCircuit::<F2>::default());
+        let pp = PublicParams::<Bn254Grumpkin, _, _>::setup(rng, &c_primary, &c_secondary).unwrap();
+
+        let num_steps = 3;
+        let (z0_primary, z0_secondary) = (vec![F1::from(1u64)], vec![F2::from(0u64)]);
+        let mut recursive_snark =
+            RecursiveSNARK::new(&pp, &c_primary, &c_secondary, &z0_primary, &z0_secondary).unwrap();
+        for _ in 1..num_steps {
+            recursive_snark
+                .prove_step(&pp, &c_primary, &c_secondary)
+                .unwrap();
+        }
+
+        let (pk, vk) =
+            CompressedSNARK::<_, _, _, KZGEvaluationEngine<G1>, IPAEvaluationEngine<G2>>::setup(
+                &pp,
+            )
+            .unwrap();
+        let snark = CompressedSNARK::prove(&pp, &pk, &recursive_snark).unwrap();
+        let (zn_primary, zn_secondary) = snark
+            .verify(&vk, num_steps, &z0_primary, &z0_secondary)
+            .unwrap();
+        let expected = (0..num_steps).fold(z0_primary.clone(), |z, _| c_primary.output(&z));
+        assert_eq!(zn_primary, expected);
+        assert_eq!(zn_secondary, z0_secondary);
+
+        assert_eq!(
+            snark.verify(&vk, num_steps + 1, &z0_primary, &z0_secondary),
+            Err(MyError::ProofVerifyError)
+        );
+        assert_eq!(
+            snark.verify(&vk, num_steps, &[F1::from(2u64)], &z0_secondary),
+            Err(MyError::ProofVerifyError)
+        );
+    }
+}
@@ -0,0 +1,590 @@
+/// LogUp lookups folded together with the gates, following chapter 4 of protostar: https://eprint.iacr.org/2023/620.pdf
+///
+/// the values w_i of a wire at the rows of the lookup selector q all lie in the table t iff, for a random α,
+/// Σ_i q_i / (α + w_i) = Σ_j m_j / (α + t_j), with m_j the multiplicity of t_j among them.
+/// the prover commits to m, derives α from it, then commits to h_i = q_i / (α + w_i) and g_j = m_j / (α + t_j), leaving
+/// 1. h_i * (w_i + α) - q_i * u^2 = E_h[i] on every row
+/// 2. g_j * (t_j * u + α) - m_j * u = E_g[j] on every table entry
+/// 3. Σ_i h_i = Σ_j g_j
+///
+/// the first two are homogenized relations of degree 2, α being folded as any other witness, with one cross term each,
+/// while the last one is linear and holds on folded witnesses as is
+///
+use ark_ff::{batch_inversion, Field, PrimeField};
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+use crate::{
+    constants::NUM_CHALLENGE_BITS,
+    error::MyError,
+    nifs::transcript,
+    plonk::{PLONKInstance, PLONKShape, PLONKWitness, RelaxedPLONKInstance, RelaxedPLONKWitness},
+    scalar_as_limbs,
+    traits::{
+        AbsorbInROTrait, CommitmentEngineTrait, Group, ROConstantsTrait, ROTrait,
+        TranscriptReprTrait,
+    },
+    Commitment, CommitmentKey, ROConstants,
+};
+
+/// a lookup of a wire into a table, at the rows where the selector is one
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Lookup<F: PrimeField> {
+    pub(crate) wire: usize,
+    pub(crate) selector: Vec<F>,
+    pub(crate) table: Vec<F>,
+}
+
+/// the multiplicities m of the table entries, and the inverses h and g of both sides
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LookupWitness<G: Group> {
+    pub(crate) m: Vec<<G as Group>::ScalarField>,
+    pub(crate) h: Vec<<G as Group>::ScalarField>,
+    pub(crate) g: Vec<<G as Group>::ScalarField>,
+}
+
+/// commitments to the witness of a lookup, together with the challenge α derived from comm_m
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LookupInstance<G: Group> {
+    pub(crate) comm_m: Commitment<G>,
+    pub(crate) comm_h: Commitment<G>,
+    pub(crate) comm_g: Commitment<G>,
+    pub(crate) alpha: <G as Group>::ScalarField,
+}
+
+/// relaxed witness of a lookup, with the errors of the relations of h and g
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RelaxedLookupWitness<G: Group> {
+    pub(crate) m: Vec<<G as Group>::ScalarField>,
+    pub(crate) h: Vec<<G as Group>::ScalarField>,
+    pub(crate) g: Vec<<G as Group>::ScalarField>,
+    pub(crate) E_h: Vec<<G as Group>::ScalarField>,
+    pub(crate) E_g: Vec<<G as Group>::ScalarField>,
+}
+
+/// relaxed instance of a lookup, whose u is the one of the relaxed PLONK instance it is folded with
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RelaxedLookupInstance<G: Group> {
+    pub(crate) comm_m: Commitment<G>,
+    pub(crate) comm_h: Commitment<G>,
+    pub(crate) comm_g: Commitment<G>,
+    pub(crate) comm_E_h: Commitment<G>,
+    pub(crate) comm_E_g: Commitment<G>,
+    pub(crate) alpha: <G as Group>::ScalarField,
+}
+
+impl<G: Group> LookupInstance<G> {
+    /// α, bound to the PLONK instance and the multiplicities
+    pub(crate) fn challenge(
+        pp_digest: &<G as Group>::ScalarField,
+        U: &PLONKInstance<G>,
+        comm_m: &Commitment<G>,
+    ) -> <G as Group>::ScalarField {
+        let mut ro = <<G as Group>::RO as ROTrait<
+            <G as Group>::BaseField,
+            <G as Group>::ScalarField,
+        >>::new(ROConstants::<G>::new(3));
+        scalar_as_limbs::<G>(pp_digest)
+            .into_iter()
+            .for_each(|limb| ro.absorb(limb));
+        U.absorb_in_ro(&mut ro);
+        comm_m.absorb_in_ro(&mut ro);
+        ro.squeeze(NUM_CHALLENGE_BITS)
+    }
+}
+
+impl<G: Group> LookupWitness<G> {
+    /// the lookup of a PLONK instance-witness pair, failing if a looked-up value is missing from the table
+    pub fn new(
+        ck: &CommitmentKey<G>,
+        pp_digest: &<G as Group>::ScalarField,
+        S: &PLONKShape<G>,
+        U: &PLONKInstance<G>,
+        W: &PLONKWitness<G>,
+    ) -> Result<(LookupInstance<G>, LookupWitness<G>), MyError> {
+        let lookup = S.lookup.as_ref().ok_or(MyError::LookupError)?;
+        let (q, w) = (&lookup.selector, &W.W[lookup.wire]);
+
+        // a value repeated in the table counts at its first entry
+        let mut index = HashMap::new();
+        lookup.table.iter().enumerate().rev().for_each(|(j, t)| {
+            index.insert(*t, j);
+        });
+        let mut m = vec![<G as Group>::ScalarField::ZERO; lookup.table.len()];
+        for (q, w) in q.iter().zip(w) {
+            if *q == <G as Group>::ScalarField::ONE {
+                m[*index.get(w).ok_or(MyError::LookupError)?] += q;
+            }
+        }
+        let comm_m = G::CE::commit(ck, &m);
+        let alpha = LookupInstance::challenge(pp_digest, U, &comm_m);
+
+        let inverse = |values: &[<G as Group>::ScalarField],
+                       scale: &[<G as Group>::ScalarField]| {
+            let mut v = values.iter().map(|v| alpha + v).collect::<Vec<_>>();
+            if v.contains(&<G as Group>::ScalarField::ZERO) {
+                return Err(MyError::LookupError);
+            }
+            batch_inversion(&mut v);
+            Ok(v.iter().zip(scale).map(|(v, s)| *v * s).collect::<Vec<_>>())
+        };
+        let h = inverse(w, q)?;
+        let g = inverse(&lookup.table, &m)?;
+
+        Ok((
+            LookupInstance {
+                comm_m,
+                comm_h: G::CE::commit(ck, &h),
+                comm_g: G::CE::commit(ck, &g),
+                alpha,
+            },
+            LookupWitness { m, h, g },
+        ))
+    }
+}
+
+impl<G: Group> RelaxedLookupWitness<G> {
+    /// the all-zero witness, which satisfies the relations for u = 0
+    pub fn default(S: &PLONKShape<G>) -> RelaxedLookupWitness<G> {
+        let zero = <G as Group>::ScalarField::ZERO;
+        let table_len = S.lookup.as_ref().map_or(0, |l| l.table.len());
+        RelaxedLookupWitness {
+            m: vec![zero; table_len],
+            h: vec![zero; S.num_cons],
+            g: vec![zero; table_len],
+            E_h: vec![zero; S.num_cons],
+            E_g: vec![zero; table_len],
+        }
+    }
+
+    /// Initializes a new RelaxedLookupWitness from a LookupWitness
+    pub fn from_lookup_witness(witness: &LookupWitness<G>) -> RelaxedLookupWitness<G> {
+        let zero = <G as Group>::ScalarField::ZERO;
+        RelaxedLookupWitness {
+            m: witness.m.clone(),
+            h: witness.h.clone(),
+            g: witness.g.clone(),
+            E_h: vec![zero; witness.h.len()],
+            E_g: vec![zero; witness.g.len()],
+        }
+    }
+
+    /// Commits to m, h, g, E_h and E_g in this order
+    pub fn commit(&self, ck: &CommitmentKey<G>) -> [Commitment<G>; 5] {
+        [&self.m, &self.h, &self.g, &self.E_h, &self.E_g].map(|v| G::CE::commit(ck, v))
+    }
+
+    /// Folds an incoming lookup witness into the current one, given the cross terms of h and g
+    pub fn fold(
+        &self,
+        W2: &LookupWitness<G>,
+        T: &[Vec<<G as Group>::ScalarField>; 2],
+        r: &<G as Group>::ScalarField,
+    ) -> Result<RelaxedLookupWitness<G>, MyError> {
+        if self.m.len() != W2.m.len() || self.h.len() != W2.h.len() || self.g.len() != W2.g.len() {
+            return Err(MyError::WitnessError);
+        }
+        let fold = |a: &[<G as Group>::ScalarField], b: &[<G as Group>::ScalarField]| {
+            a.par_iter()
+                .zip(b)
+                .map(|(a, b)| *a + *r * b)
+                .collect::<Vec<_>>()
+        };
+
+        Ok(RelaxedLookupWitness {
+            m: fold(&self.m, &W2.m),
+            h: fold(&self.h, &W2.h),
+            g: fold(&self.g, &W2.g),
+            E_h: fold(&self.E_h, &T[0]),
+            E_g: fold(&self.E_g, &T[1]),
+        })
+    }
+}
+
+impl<G: Group> RelaxedLookupInstance<G> {
+    pub fn default(_ck: &CommitmentKey<G>, _S: &PLONKShape<G>) -> RelaxedLookupInstance<G> {
+        RelaxedLookupInstance {
+            comm_m: Commitment::<G>::default(),
+            comm_h: Commitment::<G>::default(),
+            comm_g: Commitment::<G>::default(),
+            comm_E_h: Commitment::<G>::default(),
+            comm_E_g: Commitment::<G>::default(),
+            alpha: <G as Group>::ScalarField::ZERO,
+        }
+    }
+
+    /// Initializes a new RelaxedLookupInstance from a LookupInstance
+    pub fn from_lookup_instance(instance: &LookupInstance<G>) -> RelaxedLookupInstance<G> {
+        RelaxedLookupInstance {
+            comm_m: instance.comm_m.clone(),
+            comm_h: instance.comm_h.clone(),
+            comm_g: instance.comm_g.clone(),
+            comm_E_h: Commitment::<G>::default(),
+            comm_E_g: Commitment::<G>::default(),
+            alpha: instance.alpha,
+        }
+    }
+
+    /// Folds an incoming lookup instance into the current one
+    pub fn fold(
+        &self,
+        U2: &LookupInstance<G>,
+        comm_T: &[Commitment<G>; 2],
+        r: &<G as Group>::ScalarField,
+    ) -> RelaxedLookupInstance<G> {
+        RelaxedLookupInstance {
+            comm_m: self.comm_m.clone() + U2.comm_m.clone() * *r,
+            comm_h: self.comm_h.clone() + U2.comm_h.clone() * *r,
+            comm_g: self.comm_g.clone() + U2.comm_g.clone() * *r,
+            comm_E_h: self.comm_E_h.clone() + comm_T[0].clone() * *r,
+            comm_E_g: self.comm_E_g.clone() + comm_T[1].clone() * *r,
+            alpha: self.alpha + *r * U2.alpha,
+        }
+    }
+}
+
+impl<G: Group> AbsorbInROTrait<G> for LookupInstance<G> {
+    fn absorb_in_ro(&self, ro: &mut G::RO) {
+        [&self.comm_m, &self.comm_h, &self.comm_g]
+            .into_iter()
+            .for_each(|c| c.absorb_in_ro(ro));
+        scalar_as_limbs::<G>(&self.alpha)
+            .into_iter()
+            .for_each(|limb| ro.absorb(limb));
+    }
+}
+
+impl<G: Group> AbsorbInROTrait<G> for RelaxedLookupInstance<G> {
+    fn absorb_in_ro(&self, ro: &mut G::RO) {
+        [
+            &self.comm_m,
+            &self.comm_h,
+            &self.comm_g,
+            &self.comm_E_h,
+            &self.comm_E_g,
+        ]
+        .into_iter()
+        .for_each(|c| c.absorb_in_ro(ro));
+        scalar_as_limbs::<G>(&self.alpha)
+            .into_iter()
+            .for_each(|limb| ro.absorb(limb));
+    }
+}
+
+/// the commitments followed by α, in the same order as they go into the RO
+impl<G: Group> TranscriptReprTrait<G> for RelaxedLookupInstance<G> {
+    fn to_transcript_bytes(&self) -> Vec<u8> {
+        [
+            &self.comm_m,
+            &self.comm_h,
+            &self.comm_g,
+            &self.comm_E_h,
+            &self.comm_E_g,
+        ]
+        .into_iter()
+        .flat_map(|c| c.to_transcript_bytes())
+        .chain(TranscriptReprTrait::<G>::to_transcript_bytes(&self.alpha))
+        .collect()
+    }
+}
+
+impl<G: Group> PLONKShape<G> {
+    /// the cross terms of the relations of h and g, folding a fresh lookup (u2 = 1) into a relaxed one
+    /// 1. T_h[i] = h1_i * (w2_i + α2) + h2_i * (w1_i + α1) - 2 * q_i * u1
+    /// 2. T_g[j] = g1_j * (t_j + α2) + g2_j * (t_j * u1 + α1) - m1_j - m2_j * u1
+    #[allow(clippy::too_many_arguments, clippy::type_complexity)]
+    pub fn commit_lookup_T(
+        &self,
+        ck: &CommitmentKey<G>,
+        U1: &RelaxedPLONKInstance<G>,
+        W1: &RelaxedPLONKWitness<G>,
+        L1: &RelaxedLookupInstance<G>,
+        LW1: &RelaxedLookupWitness<G>,
+        W2: &PLONKWitness<G>,
+        L2: &LookupInstance<G>,
+        LW2: &LookupWitness<G>,
+    ) -> Result<([Vec<<G as Group>::ScalarField>; 2], [Commitment<G>; 2]), MyError> {
+        let lookup = self.lookup.as_ref().ok_or(MyError::LookupError)?;
+        let (u1, alpha1, alpha2) = (U1.u, L1.alpha, L2.alpha);
+        let (w1, w2) = (&W1.W[lookup.wire], &W2.W[lookup.wire]);
+        if LW1.h.len() != self.num_cons
+            || LW2.h.len() != self.num_cons
+            || [&LW1.m, &LW1.g, &LW2.m, &LW2.g]
+                .iter()
+                .any(|v| v.len() != lookup.table.len())
+        {
+            return Err(MyError::WitnessError);
+        }
+
+        let T_h = (0..self.num_cons)
+            .into_par_iter()
+            .map(|i| {
+                LW1.h[i] * (w2[i] + alpha2) + LW2.h[i] * (w1[i] + alpha1)
+                    - lookup.selector[i].double() * u1
+            })
+            .collect::<Vec<_>>();
+        let T_g = (0..lookup.table.len())
+            .into_par_iter()
+            .map(|j| {
+                let t = lookup.table[j];
+                LW1.g[j] * (t + alpha2) + LW2.g[j] * (t * u1 + alpha1) - LW1.m[j] - LW2.m[j] * u1
+            })
+            .collect::<Vec<_>>();
+        let comm_T = [G::CE::commit(ck, &T_h), G::CE::commit(ck, &T_g)];
+
+        Ok(([T_h, T_g], comm_T))
+    }
+
+    /// Checks if the relaxed lookup is satisfied along with the relaxed PLONK instance it is folded with
+    pub fn is_sat_lookup(
+        &self,
+        ck: &CommitmentKey<G>,
+        U: &RelaxedPLONKInstance<G>,
+        W: &RelaxedPLONKWitness<G>,
+        L: &RelaxedLookupInstance<G>,
+        LW: &RelaxedLookupWitness<G>,
+    ) -> Result<(), MyError> {
+        let lookup = self.lookup.as_ref().ok_or(MyError::LookupError)?;
+        if W.W.len() != self.num_wire_types
+            || [&LW.h, &LW.E_h].iter().any(|v| v.len() != self.num_cons)
+            || [&LW.m, &LW.g, &LW.E_g]
+                .iter()
+                .any(|v| v.len() != lookup.table.len())
+        {
+            return Err(MyError::WitnessError);
+        }
+        let (u, alpha, w) = (U.u, L.alpha, &W.W[lookup.wire]);
+
+        let unsat_row = (0..self.num_cons).into_par_iter().find_first(|i| {
+            LW.h[*i] * (w[*i] + alpha) - lookup.selector[*i] * u.square() != LW.E_h[*i]
+        });
+        if let Some(row) = unsat_row {
+            return Err(MyError::UnSatLookup(row));
+        }
+        let unsat_entry = (0..lookup.table.len())
+            .into_par_iter()
+            .find_first(|j| LW.g[*j] * (lookup.table[*j] * u + alpha) - LW.m[*j] * u != LW.E_g[*j]);
+        if let Some(row) = unsat_entry {
+            return Err(MyError::UnSatTable(row));
+        }
+        if LW.h.iter().sum::<<G as Group>::ScalarField>()
+            != LW.g.iter().sum::<<G as Group>::ScalarField>()
+        {
+            return Err(MyError::UnSatLookupSum);
+        }
+
+        let comm = LW.commit(ck);
+        if [&L.comm_m, &L.comm_h, &L.comm_g, &L.comm_E_h, &L.comm_E_g]
+            .into_iter()
+            .zip(&comm)
+            .any(|(a, b)| a != b)
+        {
+            return Err(MyError::CommitmentError);
+        }
+
+        Ok(())
+    }
+}
+
+/// A NIFS proof folding a PLONK instance and its lookup into relaxed running ones under the same challenge,
+/// with the cross terms of the gate and those of h and g
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LookupNIFS<G: Group> {
+    pub(crate) comm_T: Vec<Commitment<G>>,
+    pub(crate) comm_T_lookup: [Commitment<G>; 2],
+}
+
+impl<G: Group> LookupNIFS<G> {
+    /// folds a PLONK instance-witness pair and its lookup into the relaxed running ones,
+    /// returning the proof together with both folded pairs
+    #[allow(clippy::too_many_arguments, clippy::type_complexity)]
+    pub fn prove(
+        ck: &CommitmentKey<G>,
+        pp_digest: &<G as Group>::ScalarField,
+        S: &PLONKShape<G>,
+        (U1, W1): (&RelaxedPLONKInstance<G>, &RelaxedPLONKWitness<G>),
+        (L1, LW1): (&RelaxedLookupInstance<G>, &RelaxedLookupWitness<G>),
+        (U2, W2): (&PLONKInstance<G>, &PLONKWitness<G>),
+        (L2, LW2): (&LookupInstance<G>, &LookupWitness<G>),
+    ) -> Result<
+        (
+            LookupNIFS<G>,
+            (RelaxedPLONKInstance<G>, RelaxedPLONKWitness<G>),
+            (RelaxedLookupInstance<G>, RelaxedLookupWitness<G>),
+        ),
+        MyError,
+    > {
+        let mut ro = transcript(pp_digest, U1, U2);
+        L1.absorb_in_ro(&mut ro);
+        L2.absorb_in_ro(&mut ro);
+
+        let (T, comm_T) = S.commit_T(ck, U1, W1, U2, W2)?;
+        let (T_lookup, comm_T_lookup) = S.commit_lookup_T(ck, U1, W1, L1, LW1, W2, L2, LW2)?;
+        comm_T
+            .iter()
+            .chain(&comm_T_lookup)
+            .for_each(|c| c.absorb_in_ro(&mut ro));
+
+        let r = ro.squeeze(NUM_CHALLENGE_BITS);
+
+        let U = U1.fold(U2, &comm_T, &r)?;
+        let W = W1.fold(W2, &T, &r)?;
+        let L = L1.fold(L2, &comm_T_lookup, &r);
+        let LW = LW1.fold(LW2, &T_lookup, &r)?;
+
+        Ok((
+            LookupNIFS {
+                comm_T,
+                comm_T_lookup,
+            },
+            (U, W),
+            (L, LW),
+        ))
+    }
+
+    /// checks that α of the incoming lookup is bound to its instance, then folds the instances only
+    pub fn verify(
+        &self,
+        pp_digest: &<G as Group>::ScalarField,
+        U1: &RelaxedPLONKInstance<G>,
+        L1: &RelaxedLookupInstance<G>,
+        U2: &PLONKInstance<G>,
+        L2: &LookupInstance<G>,
+    ) -> Result<(RelaxedPLONKInstance<G>, RelaxedLookupInstance<G>), MyError> {
+        if L2.alpha != LookupInstance::challenge(pp_digest, U2, &L2.comm_m) {
+            return Err(MyError::ProofVerifyError);
+        }
+
+        let mut ro = transcript(pp_digest, U1, U2);
+        L1.absorb_in_ro(&mut ro);
+        L2.absorb_in_ro(&mut ro);
+        self.comm_T
+            .iter()
+            .chain(&self.comm_T_lookup)
+            .for_each(|c| c.absorb_in_ro(&mut ro));
+
+        let r = ro.squeeze(NUM_CHALLENGE_BITS);
+
+        Ok((
+            U1.fold(U2, &self.comm_T, &r)?,
+            L1.fold(L2, &self.comm_T_lookup, &r),
+        ))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::{
+        gate::{Column, CustomGate, GateTerm},
+        provider::grumpkin::Grumpkin,
+    };
+    use ark_bn254::Bn254;
+    use ark_ff::UniformRand;
+    use jf_utils::test_rng;
+    use rand::{rngs::StdRng, Rng};
+
+    /// w_0 + w_1 = w_2, with w_0 looked up into 0..16 on all rows but the last
+    pub(crate) fn range_shape<G: Group>(num_cons: usize) -> PLONKShape<G> {
+        let (w, one) = (Column::Wire, <G as Group>::ScalarField::ONE);
+        let gate = CustomGate::new(
+            1,
+            vec![
+                GateTerm::new(one, Some(0), &[w(0)]),
+                GateTerm::new(one, Some(0), &[w(1)]),
+                GateTerm::new(-one, Some(0), &[w(2)]),
+            ],
+        );
+        let sigma = (0..3)
+            .map(|i| (0..num_cons).map(|j| i * num_cons + j).collect())
+            .collect::<Vec<Vec<usize>>>();
+        let selector = (0..num_cons)
+            .map(|i| {
+                if i + 1 < num_cons {
+                    one
+                } else {
+                    <G as Group>::ScalarField::ZERO
+                }
+            })
+            .collect();
+        let table = (0..16u64).map(<G as Group>::ScalarField::from).collect();
+        PLONKShape::new_with_gate(num_cons, 3, 0, gate, vec![vec![one; num_cons]], &sigma)
+            .unwrap()
+            .with_lookup(0, selector, table)
+            .unwrap()
+    }
+
+    fn test_lookup_fold_with<G: Group>() {
+        let rng = &mut test_rng();
+        let num_cons = 8;
+        let ck = G::CE::setup(rng, 16);
+        let pp_digest = <G as Group>::ScalarField::rand(rng);
+
+        let S = range_shape::<G>(num_cons);
+        let witness = |rng: &mut StdRng, w_0: Vec<<G as Group>::ScalarField>| {
+            let w_1 = (0..num_cons)
+                .map(|_| <G as Group>::ScalarField::rand(rng))
+                .collect::<Vec<_>>();
+            let w_2 = w_0.iter().zip(&w_1).map(|(a, b)| *a + b).collect();
+            PLONKWitness::new(&S, &[w_0, w_1, w_2]).unwrap()
+        };
+
+        let mut running_U = RelaxedPLONKInstance::default(&ck, &S);
+        let mut running_W = RelaxedPLONKWitness::default(&S);
+        let mut running_L = RelaxedLookupInstance::default(&ck, &S);
+        let mut running_LW = RelaxedLookupWitness::default(&S);
+        for _ in 0..3 {
+            // the last row is not looked up, so it may leave the range
+            let w_0 = (0..num_cons)
+                .map(|i| {
+                    let bound = if i + 1 < num_cons { 16 } else { 1 << 20 };
+                    <G as Group>::ScalarField::from(rng.gen_range(0..bound) as u64)
+                })
+                .collect();
+            let W = witness(rng, w_0);
+            let U = PLONKInstance::new(&S, &W.commit(&ck), &[]).unwrap();
+            let (L, LW) = LookupWitness::new(&ck, &pp_digest, &S, &U, &W).unwrap();
+
+            let (nifs, (folded_U, folded_W), (folded_L, folded_LW)) = LookupNIFS::prove(
+                &ck,
+                &pp_digest,
+                &S,
+                (&running_U, &running_W),
+                (&running_L, &running_LW),
+                (&U, &W),
+                (&L, &LW),
+            )
+            .unwrap();
+            assert_eq!(
+                nifs.verify(&pp_digest, &running_U, &running_L, &U, &L),
+                Ok((folded_U.clone(), folded_L.clone()))
+            );
+            S.is_sat_relaxed(&ck, &folded_U, &folded_W).unwrap();
+            S.is_sat_lookup(&ck, &folded_U, &folded_W, &folded_L, &folded_LW)
+                .unwrap();
+            (running_U, running_W, running_L, running_LW) =
+                (folded_U, folded_W, folded_L, folded_LW);
+        }
+
+        // a value out of the table has no multiplicity
+        let W = witness(rng, vec![<G as Group>::ScalarField::from(16u64); num_cons]);
+        let U = PLONKInstance::new(&S, &W.commit(&ck), &[]).unwrap();
+        assert_eq!(
+            LookupWitness::new(&ck, &pp_digest, &S, &U, &W),
+            Err(MyError::LookupError)
+        );
+
+        let mut LW = running_LW.clone();
+        LW.h[1] += <G as Group>::ScalarField::ONE;
+        assert_eq!(
+            S.is_sat_lookup(&ck, &running_U, &running_W, &running_L, &LW),
+            Err(MyError::UnSatLookup(1))
+        );
+    }
+
+    #[test]
+    fn test_lookup_fold() {
+        test_lookup_fold_with::<Bn254>();
+        test_lookup_fold_with::<Grumpkin>();
+    }
+}
@@ -0,0 +1,949 @@
+/// offline memory checking folded together with the gates, as in spice: https://eprint.iacr.org/2018/907.pdf
+///
+/// every step accesses a memory of `size` cells at the rows where the selector s is one, reading (a, v_r, t_r) and
+/// writing (a, v_w, t_w), and holds the memory right before and right after it, (j, v_init[j], t_init[j]) and
+/// (j, v_final[j], t_final[j]) at every address j. the accesses are consistent iff the multisets init ∪ writes and
+/// reads ∪ final agree, given that every write is stamped later than the read before it, t_w > t_r as integers
+/// below the modulus. the memory does not check this itself, the gate of the shape must, by t_w = t_r + 1 or a range
+/// check on t_w - t_r - 1, or the accesses are left unsound. tuples are fingerprinted as f = γ + a + β * v + β^2 * t,
+/// with a random compression β, for which distinct tuples have distinct fingerprints, and an independent random
+/// shift γ, for which the multisets agree iff, both but with negligible probability,
+/// Σ_i s_i / f(write_i) - s_i / f(read_i) + Σ_j 1 / f(init_j) - 1 / f(final_j) = 0
+/// the shift must not be tied to β, with γ = β distinct multisets may sum up alike for every β
+///
+/// the prover commits to both memories, derives γ and β from them, with δ = β^2, then commits to the inverses
+/// h_read, h_write, g_init and g_final, leaving
+/// 1. h_read[i] * (γ * u + a_i * u + β * v_r[i] + δ * t_r[i]) - s_i * u^3 = E_read[i], and the same for h_write
+/// 2. g_init[j] * (γ * u + j * u^2 + β * v_init[j] + δ * t_init[j]) - u^3 = E_init[j], and the same for g_final
+/// 3. Σ_i h_write[i] - h_read[i] + Σ_j g_init[j] - g_final[j] = 0
+///
+/// the first two are homogenized relations of degree 3, γ, β and δ being folded as any other witness, with two
+/// cross terms each, while the last one is linear. the final memory of a step is the initial one of the next, which
+/// the folding checks on their commitments, so that a relaxed instance carries the first and the last memory
+///
+use ark_ff::{batch_inversion, Field, PrimeField};
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+use crate::{
+    constants::NUM_CHALLENGE_BITS,
+    error::MyError,
+    nifs::transcript,
+    plonk::{PLONKInstance, PLONKShape, PLONKWitness, RelaxedPLONKInstance, RelaxedPLONKWitness},
+    scalar_as_limbs,
+    traits::{
+        AbsorbInROTrait, CommitmentEngineTrait, Group, ROConstantsTrait, ROTrait,
+        TranscriptReprTrait,
+    },
+    Commitment, CommitmentKey, ROConstants,
+};
+
+/// a read-write memory of the given size, accessed at the rows where the selector is one, with the wires of the
+/// address, the value and timestamp read, and the value and timestamp written
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Memory<F: PrimeField> {
+    pub(crate) wires: [usize; 5],
+    pub(crate) selector: Vec<F>,
+    pub(crate) size: usize,
+}
+
+/// the memory before and after a step, (v_init, t_init, v_final, t_final), and the inverses (h_read, h_write) of
+/// the accesses and (g_init, g_final) of the memories
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MemoryWitness<G: Group> {
+    pub(crate) state: [Vec<<G as Group>::ScalarField>; 4],
+    pub(crate) h: [Vec<<G as Group>::ScalarField>; 2],
+    pub(crate) g: [Vec<<G as Group>::ScalarField>; 2],
+}
+
+/// commitments to the witness of a memory, together with the challenges (γ, β, δ) derived from both memories
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MemoryInstance<G: Group> {
+    pub(crate) comm_state: [Commitment<G>; 4],
+    pub(crate) comm_h: [Commitment<G>; 2],
+    pub(crate) comm_g: [Commitment<G>; 2],
+    pub(crate) challenges: [<G as Group>::ScalarField; 3],
+}
+
+/// relaxed witness of a memory, with the errors of the relations of h_read, h_write, g_init and g_final
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RelaxedMemoryWitness<G: Group> {
+    pub(crate) state: [Vec<<G as Group>::ScalarField>; 4],
+    pub(crate) h: [Vec<<G as Group>::ScalarField>; 2],
+    pub(crate) g: [Vec<<G as Group>::ScalarField>; 2],
+    pub(crate) E: [Vec<<G as Group>::ScalarField>; 4],
+}
+
+/// relaxed instance of a memory, whose u is the one of the relaxed PLONK instance it is folded with,
+/// together with the commitments to the memory before the first folded step and after the last one
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RelaxedMemoryInstance<G: Group> {
+    pub(crate) comm_state: [Commitment<G>; 4],
+    pub(crate) comm_h: [Commitment<G>; 2],
+    pub(crate) comm_g: [Commitment<G>; 2],
+    pub(crate) comm_E: [Commitment<G>; 4],
+    pub(crate) challenges: [<G as Group>::ScalarField; 3],
+    pub(crate) comm_first: [Commitment<G>; 2],
+    pub(crate) comm_last: [Commitment<G>; 2],
+}
+
+/// the homogenized fingerprint γ * u + address + β * v + δ * t, with the address already homogenized
+fn fingerprint<F: Field>(challenges: &[F; 3], u: &F, address: F, v: &F, t: &F) -> F {
+    challenges[0] * u + address + challenges[1] * v + challenges[2] * t
+}
+
+impl<G: Group> MemoryInstance<G> {
+    /// (γ, β, β^2) for the shift γ and the compression β squeezed one after the other, bound to the PLONK instance
+    /// and both memories
+    pub(crate) fn challenge(
+        pp_digest: &<G as Group>::ScalarField,
+        U: &PLONKInstance<G>,
+        comm_state: &[Commitment<G>; 4],
+    ) -> [<G as Group>::ScalarField; 3] {
+        let mut ro = <<G as Group>::RO as ROTrait<
+            <G as Group>::BaseField,
+            <G as Group>::ScalarField,
+        >>::new(ROConstants::<G>::new(3));
+        scalar_as_limbs::<G>(pp_digest)
+            .into_iter()
+            .for_each(|limb| ro.absorb(limb));
+        U.absorb_in_ro(&mut ro);
+        comm_state.iter().for_each(|c| c.absorb_in_ro(&mut ro));
+        let gamma = ro.squeeze(NUM_CHALLENGE_BITS);
+        let beta: <G as Group>::ScalarField = ro.squeeze(NUM_CHALLENGE_BITS);
+        [gamma, beta, beta.square()]
+    }
+}
+
+impl<G: Group> MemoryWitness<G> {
+    /// the memory of a PLONK instance-witness pair from the memory (v_init, t_init) before the step, replaying the
+    /// accesses row by row, failing if one is out of the memory or reads another value than the last one written
+    pub fn new(
+        ck: &CommitmentKey<G>,
+        pp_digest: &<G as Group>::ScalarField,
+        S: &PLONKShape<G>,
+        U: &PLONKInstance<G>,
+        W: &PLONKWitness<G>,
+        init: &[Vec<<G as Group>::ScalarField>; 2],
+    ) -> Result<(MemoryInstance<G>, MemoryWitness<G>), MyError> {
+        let memory = S.memory.as_ref().ok_or(MyError::MemoryError)?;
+        if init.iter().any(|v| v.len() != memory.size) {
+            return Err(MyError::MemoryError);
+        }
+        let [a, v_r, t_r, v_w, t_w] = memory.wires.map(|w| &W.W[w]);
+
+        let index = (0..memory.size)
+            .map(|j| (<G as Group>::ScalarField::from(j as u64), j))
+            .collect::<HashMap<_, _>>();
+        let (mut v, mut t) = (init[0].clone(), init[1].clone());
+        for i in 0..S.num_cons {
+            if memory.selector[i] == <G as Group>::ScalarField::ONE {
+                let j = *index.get(&a[i]).ok_or(MyError::MemoryError)?;
+                if v[j] != v_r[i] || t[j] != t_r[i] {
+                    return Err(MyError::MemoryError);
+                }
+                (v[j], t[j]) = (v_w[i], t_w[i]);
+            }
+        }
+        Self::with_state(
+            ck,
+            pp_digest,
+            S,
+            U,
+            W,
+            [init[0].clone(), init[1].clone(), v, t],
+        )
+    }
+
+    /// the memory of a PLONK instance-witness pair between the given memories (v_init, t_init, v_final, t_final),
+    /// taking the accesses as they are, which `new` replays first
+    pub(crate) fn with_state(
+        ck: &CommitmentKey<G>,
+        pp_digest: &<G as Group>::ScalarField,
+        S: &PLONKShape<G>,
+        U: &PLONKInstance<G>,
+        W: &PLONKWitness<G>,
+        state: [Vec<<G as Group>::ScalarField>; 4],
+    ) -> Result<(MemoryInstance<G>, MemoryWitness<G>), MyError> {
+        let memory = S.memory.as_ref().ok_or(MyError::MemoryError)?;
+        if state.iter().any(|v| v.len() != memory.size) {
+            return Err(MyError::MemoryError);
+        }
+        let [a, v_r, t_r, v_w, t_w] = memory.wires.map(|w| &W.W[w]);
+        let comm_state = state.clone().map(|c| G::CE::commit(ck, &c));
+        let challenges = MemoryInstance::challenge(pp_digest, U, &comm_state);
+
+        let one = <G as Group>::ScalarField::ONE;
+        let inverse = |address: &dyn Fn(usize) -> <G as Group>::ScalarField,
+                       v: &[<G as Group>::ScalarField],
+                       t: &[<G as Group>::ScalarField],
+                       scale: &dyn Fn(usize) -> <G as Group>::ScalarField| {
+            let mut d = (0..v.len())
+                .map(|i| fingerprint(&challenges, &one, address(i), &v[i], &t[i]))
+                .collect::<Vec<_>>();
+            if d.contains(&<G as Group>::ScalarField::ZERO) {
+                return Err(MyError::MemoryError);
+            }
+            batch_inversion(&mut d);
+            Ok(d.iter()
+                .enumerate()
+                .map(|(i, d)| *d * scale(i))
+                .collect::<Vec<_>>())
+        };
+        let (row, entry) = (
+            |i: usize| a[i],
+            |j: usize| <G as Group>::ScalarField::from(j as u64),
+        );
+        let selector = |i: usize| memory.selector[i];
+        let h = [
+            inverse(&row, v_r, t_r, &selector)?,
+            inverse(&row, v_w, t_w, &selector)?,
+        ];
+        let g = [
+            inverse(&entry, &state[0], &state[1], &|_| one)?,
+            inverse(&entry, &state[2], &state[3], &|_| one)?,
+        ];
+
+        Ok((
+            MemoryInstance {
+                comm_state,
+                comm_h: [&h[0], &h[1]].map(|v| G::CE::commit(ck, v)),
+                comm_g: [&g[0], &g[1]].map(|v| G::CE::commit(ck, v)),
+                challenges,
+            },
+            MemoryWitness { state, h, g },
+        ))
+    }
+}
+
+impl<G: Group> RelaxedMemoryWitness<G> {
+    /// the all-zero witness, which satisfies the relations for u = 0
+    pub fn default(S: &PLONKShape<G>) -> RelaxedMemoryWitness<G> {
+        let zero = <G as Group>::ScalarField::ZERO;
+        let size = S.memory.as_ref().map_or(0, |m| m.size);
+        let (rows, entries) = (vec![zero; S.num_cons], vec![zero; size]);
+        RelaxedMemoryWitness {
+            state: [(); 4].map(|_| entries.clone()),
+            h: [rows.clone(), rows.clone()],
+            g: [entries.clone(), entries.clone()],
+            E: [rows.clone(), rows, entries.clone(), entries],
+        }
+    }
+
+    /// Initializes a new RelaxedMemoryWitness from a MemoryWitness
+    pub fn from_memory_witness(witness: &MemoryWitness<G>) -> RelaxedMemoryWitness<G> {
+        let zero = <G as Group>::ScalarField::ZERO;
+        let (rows, entries) = (
+            vec![zero; witness.h[0].len()],
+            vec![zero; witness.g[0].len()],
+        );
+        RelaxedMemoryWitness {
+            state: witness.state.clone(),
+            h: witness.h.clone(),
+            g: witness.g.clone(),
+            E: [rows.clone(), rows, entries.clone(), entries],
+        }
+    }
+
+    /// Commits to the memories, h, g and E in this order
+    pub fn commit(&self, ck: &CommitmentKey<G>) -> Vec<Commitment<G>> {
+        self.state
+            .iter()
+            .chain(&self.h)
+            .chain(&self.g)
+            .chain(&self.E)
+            .map(|v| G::CE::commit(ck, v))
+            .collect()
+    }
+
+    /// Folds an incoming memory witness into the current one, given the two cross terms of every relation
+    pub fn fold(
+        &self,
+        W2: &MemoryWitness<G>,
+        T: &[[Vec<<G as Group>::ScalarField>; 2]; 4],
+        r: &<G as Group>::ScalarField,
+    ) -> Result<RelaxedMemoryWitness<G>, MyError> {
+        if self
+            .state
+            .iter()
+            .chain(&self.h)
+            .chain(&self.g)
+            .zip(W2.state.iter().chain(&W2.h).chain(&W2.g))
+            .any(|(a, b)| a.len() != b.len())
+        {
+            return Err(MyError::WitnessError);
+        }
+        let fold = |a: &[<G as Group>::ScalarField],
+                    b: &[<G as Group>::ScalarField],
+                    r: &<G as Group>::ScalarField| {
+            a.par_iter()
+                .zip(b)
+                .map(|(a, b)| *a + *r * b)
+                .collect::<Vec<_>>()
+        };
+        let r_square = r.square();
+
+        Ok(RelaxedMemoryWitness {
+            state: [0, 1, 2, 3].map(|k| fold(&self.state[k], &W2.state[k], r)),
+            h: [0, 1].map(|k| fold(&self.h[k], &W2.h[k], r)),
+            g: [0, 1].map(|k| fold(&self.g[k], &W2.g[k], r)),
+            E: [0, 1, 2, 3].map(|k| fold(&fold(&self.E[k], &T[k][0], r), &T[k][1], &r_square)),
+        })
+    }
+}
+
+impl<G: Group> RelaxedMemoryInstance<G> {
+    /// the instance before the first step, whose first and last memories are both the given (v_init, t_init)
+    pub fn default(
+        ck: &CommitmentKey<G>,
+        S: &PLONKShape<G>,
+        init: &[Vec<<G as Group>::ScalarField>; 2],
+    ) -> Result<RelaxedMemoryInstance<G>, MyError> {
+        let memory = S.memory.as_ref().ok_or(MyError::MemoryError)?;
+        if init.iter().any(|v| v.len() != memory.size) {
+            return Err(MyError::MemoryError);
+        }
+        let comm_init = [&init[0], &init[1]].map(|v| G::CE::commit(ck, v));
+        Ok(RelaxedMemoryInstance {
+            comm_state: [(); 4].map(|_| Commitment::<G>::default()),
+            comm_h: [(); 2].map(|_| Commitment::<G>::default()),
+            comm_g: [(); 2].map(|_| Commitment::<G>::default()),
+            comm_E: [(); 4].map(|_| Commitment::<G>::default()),
+            challenges: [<G as Group>::ScalarField::ZERO; 3],
+            comm_first: comm_init.clone(),
+            comm_last: comm_init,
+        })
+    }
+
+    /// Initializes a new RelaxedMemoryInstance from a MemoryInstance
+    pub fn from_memory_instance(instance: &MemoryInstance<G>) -> RelaxedMemoryInstance<G> {
+        let [v_init, t_init, v_final, t_final] = instance.comm_state.clone();
+        RelaxedMemoryInstance {
+            comm_state: instance.comm_state.clone(),
+            comm_h: instance.comm_h.clone(),
+            comm_g: instance.comm_g.clone(),
+            comm_E: [(); 4].map(|_| Commitment::<G>::default()),
+            challenges: instance.challenges,
+            comm_first: [v_init, t_init],
+            comm_last: [v_final, t_final],
+        }
+    }
+
+    /// Folds an incoming memory instance into the current one, failing unless it starts from the last memory
+    pub fn fold(
+        &self,
+        U2: &MemoryInstance<G>,
+        comm_T: &[[Commitment<G>; 2]; 4],
+        r: &<G as Group>::ScalarField,
+    ) -> Result<RelaxedMemoryInstance<G>, MyError> {
+        if U2.comm_state[..2] != self.comm_last {
+            return Err(MyError::MemoryError);
+        }
+        let fold = |a: &Commitment<G>, b: &Commitment<G>| a.clone() + b.clone() * *r;
+        let r_square = r.square();
+
+        Ok(RelaxedMemoryInstance {
+            comm_state: [0, 1, 2, 3].map(|k| fold(&self.comm_state[k], &U2.comm_state[k])),
+            comm_h: [0, 1].map(|k| fold(&self.comm_h[k], &U2.comm_h[k])),
+            comm_g: [0, 1].map(|k| fold(&self.comm_g[k], &U2.comm_g[k])),
+            comm_E: [0, 1, 2, 3]
+                .map(|k| fold(&self.comm_E[k], &comm_T[k][0]) + comm_T[k][1].clone() * r_square),
+            challenges: [0, 1, 2].map(|k| self.challenges[k] + *r * U2.challenges[k]),
+            comm_first: self.comm_first.clone(),
+            comm_last: [U2.comm_state[2].clone(), U2.comm_state[3].clone()],
+        })
+    }
+}
+
+impl<G: Group> AbsorbInROTrait<G> for MemoryInstance<G> {
+    fn absorb_in_ro(&self, ro: &mut G::RO) {
+        self.comm_state
+            .iter()
+            .chain(&self.comm_h)
+            .chain(&self.comm_g)
+            .for_each(|c| c.absorb_in_ro(ro));
+        self.challenges.iter().for_each(|c| {
+            scalar_as_limbs::<G>(c)
+                .into_iter()
+                .for_each(|limb| ro.absorb(limb))
+        });
+    }
+}
+
+impl<G: Group> AbsorbInROTrait<G> for RelaxedMemoryInstance<G> {
+    fn absorb_in_ro(&self, ro: &mut G::RO) {
+        self.comm_state
+            .iter()
+            .chain(&self.comm_h)
+            .chain(&self.comm_g)
+            .chain(&self.comm_E)
+            .chain(&self.comm_first)
+            .chain(&self.comm_last)
+            .for_each(|c| c.absorb_in_ro(ro));
+        self.challenges.iter().for_each(|c| {
+            scalar_as_limbs::<G>(c)
+                .into_iter()
+                .for_each(|limb| ro.absorb(limb))
+        });
+    }
+}
+
+/// the commitments followed by the challenges, in the same order as they go into the RO
+impl<G: Group> TranscriptReprTrait<G> for RelaxedMemoryInstance<G> {
+    fn to_transcript_bytes(&self) -> Vec<u8> {
+        self.comm_state
+            .iter()
+            .chain(&self.comm_h)
+            .chain(&self.comm_g)
+            .chain(&self.comm_E)
+            .chain(&self.comm_first)
+            .chain(&self.comm_last)
+            .flat_map(|c| c.to_transcript_bytes())
+            .chain(
+                self.challenges
+                    .iter()
+                    .flat_map(TranscriptReprTrait::<G>::to_transcript_bytes),
+            )
+            .collect()
+    }
+}
+
+/// u, the challenges, then the accessed wires, the memories, h and g, of one relaxed assignment of a memory
+type MemoryAssignment<'a, F> = (
+    F,
+    [F; 3],
+    [&'a [F]; 5],
+    [&'a [F]; 4],
+    [&'a [F]; 2],
+    [&'a [F]; 2],
+);
+
+impl<G: Group> PLONKShape<G> {
+    /// the homogenized relations of h_read, h_write, g_init and g_final, whose values are the errors
+    fn memory_residuals(
+        &self,
+        (u, challenges, wires, state, h, g): MemoryAssignment<'_, <G as Group>::ScalarField>,
+    ) -> Result<[Vec<<G as Group>::ScalarField>; 4], MyError> {
+        let memory = self.memory.as_ref().ok_or(MyError::MemoryError)?;
+        let [a, v_r, t_r, v_w, t_w] = wires;
+        let u_cube = u.square() * u;
+        let access = |h: &[<G as Group>::ScalarField],
+                      v: &[<G as Group>::ScalarField],
+                      t: &[<G as Group>::ScalarField]| {
+            (0..self.num_cons)
+                .into_par_iter()
+                .map(|i| {
+                    h[i] * fingerprint(&challenges, &u, a[i] * u, &v[i], &t[i])
+                        - memory.selector[i] * u_cube
+                })
+                .collect::<Vec<_>>()
+        };
+        let entry = |g: &[<G as Group>::ScalarField],
+                     v: &[<G as Group>::ScalarField],
+                     t: &[<G as Group>::ScalarField]| {
+            (0..memory.size)
+                .into_par_iter()
+                .map(|j| {
+                    let address = <G as Group>::ScalarField::from(j as u64) * u.square();
+                    g[j] * fingerprint(&challenges, &u, address, &v[j], &t[j]) - u_cube
+                })
+                .collect::<Vec<_>>()
+        };
+
+        Ok([
+            access(h[0], v_r, t_r),
+            access(h[1], v_w, t_w),
+            entry(g[0], state[0], state[1]),
+            entry(g[1], state[2], state[3]),
+        ])
+    }
+
+    /// the cross terms of the relations of h_read, h_write, g_init and g_final, folding a fresh memory (u2 = 1) into
+    /// a relaxed one. every relation at the folded assignments is a polynomial of degree 3 in r, interpolated from
+    /// its values at 0, 1, 2, 3, whose coefficients of r and r^2 are the cross terms
+    #[allow(clippy::too_many_arguments, clippy::type_complexity)]
+    pub fn commit_memory_T(
+        &self,
+        ck: &CommitmentKey<G>,
+        U1: &RelaxedPLONKInstance<G>,
+        W1: &RelaxedPLONKWitness<G>,
+        M1: &RelaxedMemoryInstance<G>,
+        MW1: &RelaxedMemoryWitness<G>,
+        W2: &PLONKWitness<G>,
+        M2: &MemoryInstance<G>,
+        MW2: &MemoryWitness<G>,
+    ) -> Result<
+        (
+            [[Vec<<G as Group>::ScalarField>; 2]; 4],
+            [[Commitment<G>; 2]; 4],
+        ),
+        MyError,
+    > {
+        let memory = self.memory.as_ref().ok_or(MyError::MemoryError)?;
+        if [&MW1.h, &MW2.h]
+            .iter()
+            .flat_map(|h| h.iter())
+            .any(|v| v.len() != self.num_cons)
+            || [&MW1.state[..], &MW1.g, &MW2.state, &MW2.g]
+                .iter()
+                .flat_map(|v| v.iter())
+                .any(|v| v.len() != memory.size)
+        {
+            return Err(MyError::WitnessError);
+        }
+
+        let one = <G as Group>::ScalarField::ONE;
+        let evals = (0..4)
+            .map(|x| {
+                let x = <G as Group>::ScalarField::from(x as u64);
+                let fold = |a: &[<G as Group>::ScalarField], b: &[<G as Group>::ScalarField]| {
+                    a.iter().zip(b).map(|(a, b)| *a + x * b).collect::<Vec<_>>()
+                };
+                let wires = memory.wires.map(|w| fold(&W1.W[w], &W2.W[w]));
+                let state = [0, 1, 2, 3].map(|k| fold(&MW1.state[k], &MW2.state[k]));
+                let h = [0, 1].map(|k| fold(&MW1.h[k], &MW2.h[k]));
+                let g = [0, 1].map(|k| fold(&MW1.g[k], &MW2.g[k]));
+                self.memory_residuals((
+                    U1.u + x * one,
+                    [0, 1, 2].map(|k| M1.challenges[k] + x * M2.challenges[k]),
+                    [0, 1, 2, 3, 4].map(|k| wires[k].as_slice()),
+                    [0, 1, 2, 3].map(|k| state[k].as_slice()),
+                    [&h[0][..], &h[1]],
+                    [&g[0][..], &g[1]],
+                ))
+            })
+            .collect::<Result<Vec<_>, MyError>>()?;
+        // coefficients of the lagrange basis over 0, 1, 2, 3
+        let basis = (0..4)
+            .map(|x| {
+                let mut e = vec![<G as Group>::ScalarField::ZERO; 4];
+                e[x] = one;
+                Self::interpolate(&e)
+            })
+            .collect::<Vec<_>>();
+        let T = [0, 1, 2, 3].map(|k| {
+            [1, 2].map(|c| {
+                (0..evals[0][k].len())
+                    .into_par_iter()
+                    .map(|row| {
+                        (0..4).fold(<G as Group>::ScalarField::ZERO, |acc, x| {
+                            acc + basis[x][c] * evals[x][k][row]
+                        })
+                    })
+                    .collect::<Vec<_>>()
+            })
+        });
+        let comm_T = [0, 1, 2, 3].map(|k| [0, 1].map(|c| G::CE::commit(ck, &T[k][c])));
+
+        Ok((T, comm_T))
+    }
+
+    /// Checks if the relaxed memory is satisfied along with the relaxed PLONK instance it is folded with
+    pub fn is_sat_memory(
+        &self,
+        ck: &CommitmentKey<G>,
+        U: &RelaxedPLONKInstance<G>,
+        W: &RelaxedPLONKWitness<G>,
+        M: &RelaxedMemoryInstance<G>,
+        MW: &RelaxedMemoryWitness<G>,
+    ) -> Result<(), MyError> {
+        let memory = self.memory.as_ref().ok_or(MyError::MemoryError)?;
+        if W.W.len() != self.num_wire_types
+            || MW
+                .h
+                .iter()
+                .chain(&MW.E[..2])
+                .any(|v| v.len() != self.num_cons)
+            || MW
+                .state
+                .iter()
+                .chain(&MW.g)
+                .chain(&MW.E[2..])
+                .any(|v| v.len() != memory.size)
+        {
+            return Err(MyError::WitnessError);
+        }
+
+        let residuals = self.memory_residuals((
+            U.u,
+            M.challenges,
+            memory.wires.map(|w| W.W[w].as_slice()),
+            [0, 1, 2, 3].map(|k| MW.state[k].as_slice()),
+            [&MW.h[0][..], &MW.h[1]],
+            [&MW.g[0][..], &MW.g[1]],
+        ))?;
+        for (k, (residual, E)) in residuals.iter().zip(&MW.E).enumerate() {
+            if let Some(row) = (0..residual.len()).find(|i| residual[*i] != E[*i]) {
+                return Err(if k < 2 {
+                    MyError::UnSatMemoryAccess(row)
+                } else {
+                    MyError::UnSatMemoryState(row)
+                });
+            }
+        }
+        let sum = |v: &[<G as Group>::ScalarField]| v.iter().sum::<<G as Group>::ScalarField>();
+        if sum(&MW.h[1]) + sum(&MW.g[0]) != sum(&MW.h[0]) + sum(&MW.g[1]) {
+            return Err(MyError::UnSatMemorySum);
+        }
+
+        let comm = MW.commit(ck);
+        if M.comm_state
+            .iter()
+            .chain(&M.comm_h)
+            .chain(&M.comm_g)
+            .chain(&M.comm_E)
+            .zip(&comm)
+            .any(|(a, b)| a != b)
+        {
+            return Err(MyError::CommitmentError);
+        }
+
+        Ok(())
+    }
+}
+
+/// A NIFS proof folding a PLONK instance and its memory into relaxed running ones under the same challenge,
+/// with the cross terms of the gate and those of the memory
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MemoryNIFS<G: Group> {
+    pub(crate) comm_T: Vec<Commitment<G>>,
+    pub(crate) comm_T_memory: [[Commitment<G>; 2]; 4],
+}
+
+impl<G: Group> MemoryNIFS<G> {
+    /// folds a PLONK instance-witness pair and its memory into the relaxed running ones,
+    /// returning the proof together with both folded pairs
+    #[allow(clippy::too_many_arguments, clippy::type_complexity)]
+    pub fn prove(
+        ck: &CommitmentKey<G>,
+        pp_digest: &<G as Group>::ScalarField,
+        S: &PLONKShape<G>,
+        (U1, W1): (&RelaxedPLONKInstance<G>, &RelaxedPLONKWitness<G>),
+        (M1, MW1): (&RelaxedMemoryInstance<G>, &RelaxedMemoryWitness<G>),
+        (U2, W2): (&PLONKInstance<G>, &PLONKWitness<G>),
+        (M2, MW2): (&MemoryInstance<G>, &MemoryWitness<G>),
+    ) -> Result<
+        (
+            MemoryNIFS<G>,
+            (RelaxedPLONKInstance<G>, RelaxedPLONKWitness<G>),
+            (RelaxedMemoryInstance<G>, RelaxedMemoryWitness<G>),
+        ),
+        MyError,
+    > {
+        let mut ro = transcript(pp_digest, U1, U2);
+        M1.absorb_in_ro(&mut ro);
+        M2.absorb_in_ro(&mut ro);
+
+        let (T, comm_T) = S.commit_T(ck, U1, W1, U2, W2)?;
+        let (T_memory, comm_T_memory) = S.commit_memory_T(ck, U1, W1, M1, MW1, W2, M2, MW2)?;
+        comm_T
+            .iter()
+            .chain(comm_T_memory.iter().flatten())
+            .for_each(|c| c.absorb_in_ro(&mut ro));
+
+        let r = ro.squeeze(NUM_CHALLENGE_BITS);
+
+        let U = U1.fold(U2, &comm_T, &r)?;
+        let W = W1.fold(W2, &T, &r)?;
+        let M = M1.fold(M2, &comm_T_memory, &r)?;
+        let MW = MW1.fold(MW2, &T_memory, &r)?;
+
+        Ok((
+            MemoryNIFS {
+                comm_T,
+                comm_T_memory,
+            },
+            (U, W),
+            (M, MW),
+        ))
+    }
+
+    /// checks that the challenges of the incoming memory are bound to its instance, then folds the instances only
+    pub fn verify(
+        &self,
+        pp_digest: &<G as Group>::ScalarField,
+        U1: &RelaxedPLONKInstance<G>,
+        M1: &RelaxedMemoryInstance<G>,
+        U2: &PLONKInstance<G>,
+        M2: &MemoryInstance<G>,
+    ) -> Result<(RelaxedPLONKInstance<G>, RelaxedMemoryInstance<G>), MyError> {
+        if M2.challenges != MemoryInstance::challenge(pp_digest, U2, &M2.comm_state) {
+            return Err(MyError::ProofVerifyError);
+        }
+
+        let mut ro = transcript(pp_digest, U1, U2);
+        M1.absorb_in_ro(&mut ro);
+        M2.absorb_in_ro(&mut ro);
+        self.comm_T
+            .iter()
+            .chain(self.comm_T_memory.iter().flatten())
+            .for_each(|c| c.absorb_in_ro(&mut ro));
+
+        let r = ro.squeeze(NUM_CHALLENGE_BITS);
+
+        Ok((
+            U1.fold(U2, &self.comm_T, &r)?,
+            M1.fold(M2, &self.comm_T_memory, &r)?,
+        ))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::{
+        gate::{Column, CustomGate, GateTerm},
+        provider::grumpkin::Grumpkin,
+    };
+    use ark_bn254::Bn254;
+    use ark_ff::UniformRand;
+    use jf_utils::test_rng;
+    use rand::{rngs::StdRng, Rng};
+
+    /// a memory of counters, every access at an even row adding its address to the counter there, w_0 + w_1 = w_3,
+    /// and stamping it one later than it was read, which the next row checks on copies of both timestamps,
+    /// w_0 + 1 = w_1
+    pub(crate) fn counter_shape<G: Group>(num_cons: usize, size: usize) -> PLONKShape<G> {
+        let (w, one, zero) = (
+            Column::Wire,
+            <G as Group>::ScalarField::ONE,
+            <G as Group>::ScalarField::ZERO,
+        );
+        let gate = CustomGate::new(
+            2,
+            vec![
+                GateTerm::new(one, Some(0), &[w(0)]),
+                GateTerm::new(one, Some(0), &[w(1)]),
+                GateTerm::new(-one, Some(0), &[w(3)]),
+                GateTerm::new(one, Some(1), &[w(0)]),
+                GateTerm::new(one, Some(1), &[]),
+                GateTerm::new(-one, Some(1), &[w(1)]),
+            ],
+        );
+        // (t_r, t_w) of an access are copied to (w_0, w_1) of the row after it
+        let mut sigma = (0..5)
+            .map(|i| (0..num_cons).map(|j| i * num_cons + j).collect())
+            .collect::<Vec<Vec<usize>>>();
+        for i in (0..num_cons).step_by(2) {
+            for (from, to) in [(2, 0), (4, 1)] {
+                sigma[from][i] = to * num_cons + i + 1;
+                sigma[to][i + 1] = from * num_cons + i;
+            }
+        }
+        let (access, check): (Vec<_>, Vec<_>) = (0..num_cons)
+            .map(|i| if i % 2 == 0 { (one, zero) } else { (zero, one) })
+            .unzip();
+        PLONKShape::new_with_gate(num_cons, 5, 0, gate, vec![access.clone(), check], &sigma)
+            .unwrap()
+            .with_memory([0, 1, 2, 3, 4], access, size)
+            .unwrap()
+    }
+
+    /// the accesses of one step at random addresses, from and into the given memory
+    pub(crate) fn counter_witness<G: Group>(
+        rng: &mut StdRng,
+        S: &PLONKShape<G>,
+        memory: &mut [Vec<<G as Group>::ScalarField>; 2],
+    ) -> PLONKWitness<G> {
+        let (zero, one) = (
+            <G as Group>::ScalarField::ZERO,
+            <G as Group>::ScalarField::ONE,
+        );
+        let mut W = vec![vec![zero; S.num_cons]; 5];
+        for i in (0..S.num_cons).step_by(2) {
+            let j = rng.gen_range(0..memory[0].len());
+            let a = <G as Group>::ScalarField::from(j as u64);
+            let row = [
+                a,
+                memory[0][j],
+                memory[1][j],
+                memory[0][j] + a,
+                memory[1][j] + one,
+            ];
+            row.iter().zip(W.iter_mut()).for_each(|(v, w)| w[i] = *v);
+            (W[0][i + 1], W[1][i + 1]) = (row[2], row[4]);
+            (memory[0][j], memory[1][j]) = (row[3], row[4]);
+        }
+        PLONKWitness::new(S, &W).unwrap()
+    }
+
+    fn test_memory_fold_with<G: Group>() {
+        let rng = &mut test_rng();
+        let (num_cons, size) = (8, 4);
+        let ck = G::CE::setup(rng, num_cons);
+        let pp_digest = <G as Group>::ScalarField::rand(rng);
+
+        let S = counter_shape::<G>(num_cons, size);
+        let init = [
+            (0..size)
+                .map(|_| <G as Group>::ScalarField::rand(rng))
+                .collect::<Vec<_>>(),
+            vec![<G as Group>::ScalarField::ZERO; size],
+        ];
+        let mut memory = init.clone();
+
+        let mut running_U = RelaxedPLONKInstance::default(&ck, &S);
+        let mut running_W = RelaxedPLONKWitness::default(&S);
+        let mut running_M = RelaxedMemoryInstance::default(&ck, &S, &init).unwrap();
+        let mut running_MW = RelaxedMemoryWitness::default(&S);
+        for _ in 0..3 {
+            let before = memory.clone();
+            let W = counter_witness(rng, &S, &mut memory);
+            let U = PLONKInstance::new(&S, &W.commit(&ck), &[]).unwrap();
+            let (M, MW) = MemoryWitness::new(&ck, &pp_digest, &S, &U, &W, &before).unwrap();
+            assert_eq!(MW.state[2..], memory);
+
+            let (nifs, (folded_U, folded_W), (folded_M, folded_MW)) = MemoryNIFS::prove(
+                &ck,
+                &pp_digest,
+                &S,
+                (&running_U, &running_W),
+                (&running_M, &running_MW),
+                (&U, &W),
+                (&M, &MW),
+            )
+            .unwrap();
+            assert_eq!(
+                nifs.verify(&pp_digest, &running_U, &running_M, &U, &M),
+                Ok((folded_U.clone(), folded_M.clone()))
+            );
+            S.is_sat_relaxed(&ck, &folded_U, &folded_W).unwrap();
+            S.is_sat_memory(&ck, &folded_U, &folded_W, &folded_M, &folded_MW)
+                .unwrap();
+            (running_U, running_W, running_M, running_MW) =
+                (folded_U, folded_W, folded_M, folded_MW);
+        }
+
+        // a step reads the memory it starts from, and starts from the last memory
+        let W = counter_witness(rng, &S, &mut memory.clone());
+        let U = PLONKInstance::new(&S, &W.commit(&ck), &[]).unwrap();
+        assert_eq!(
+            MemoryWitness::new(&ck, &pp_digest, &S, &U, &W, &init).err(),
+            Some(MyError::MemoryError)
+        );
+        let mut stale = memory.clone();
+        let W = counter_witness(rng, &S, &mut stale);
+        let U = PLONKInstance::new(&S, &W.commit(&ck), &[]).unwrap();
+        let (M, MW) = MemoryWitness::new(&ck, &pp_digest, &S, &U, &W, &memory).unwrap();
+        let mut M_stale = running_M.clone();
+        M_stale.comm_last = M_stale.comm_first.clone();
+        assert_eq!(
+            MemoryNIFS::prove(
+                &ck,
+                &pp_digest,
+                &S,
+                (&running_U, &running_W),
+                (&M_stale, &running_MW),
+                (&U, &W),
+                (&M, &MW),
+            )
+            .err(),
+            Some(MyError::MemoryError)
+        );
+
+        // a write stamped no later than its read breaks the gate, its copy included
+        let mut W = counter_witness(rng, &S, &mut memory.clone());
+        (W.W[4][0], W.W[1][1]) = (W.W[2][0], W.W[2][0]);
+        let U = PLONKInstance::new(&S, &W.commit(&ck), &[]).unwrap();
+        assert_eq!(S.is_sat(&ck, &U, &W), Err(MyError::UnSatIndex(1)));
+
+        let mut MW = running_MW.clone();
+        MW.h[1][2] += <G as Group>::ScalarField::ONE;
+        assert_eq!(
+            S.is_sat_memory(&ck, &running_U, &running_W, &running_M, &MW),
+            Err(MyError::UnSatMemoryAccess(2))
+        );
+    }
+
+    #[test]
+    fn test_memory_fold() {
+        test_memory_fold_with::<Bn254>();
+        test_memory_fold_with::<Grumpkin>();
+    }
+
+    fn test_memory_fingerprint_with<G: Group>() {
+        let rng = &mut test_rng();
+        let ck = G::CE::setup(rng, 2);
+        let pp_digest = <G as Group>::ScalarField::rand(rng);
+
+        // a single address, with a gate of no terms in place of one range checking t_w - t_r - 1 over the whole
+        // field, which 1/2 = (p + 1) / 2 passes
+        let one = <G as Group>::ScalarField::ONE;
+        let sigma = (0..5)
+            .map(|i| (0..2).map(|j| i * 2 + j).collect())
+            .collect::<Vec<Vec<usize>>>();
+        let S = PLONKShape::<G>::new_with_gate(
+            2,
+            5,
+            0,
+            CustomGate::new(1, vec![]),
+            vec![vec![one; 2]],
+            &sigma,
+        )
+        .unwrap()
+        .with_memory([0, 1, 2, 3, 4], vec![one; 2], 1)
+        .unwrap();
+
+        // both reads return 1, which is never written, as the tuples (1/2, 0) and (3/2, 1/2) sum up as twice (1, 0)
+        // for every shift tied to the compression
+        let (zero, half) = (
+            <G as Group>::ScalarField::ZERO,
+            one.double().inverse().unwrap(),
+        );
+        let x = <G as Group>::ScalarField::rand(rng);
+        let W = PLONKWitness::new(
+            &S,
+            &[
+                vec![zero, zero],
+                vec![one, one],
+                vec![zero, zero],
+                vec![one + half, x],
+                vec![half, one],
+            ],
+        )
+        .unwrap();
+        let U = PLONKInstance::new(&S, &W.commit(&ck), &[]).unwrap();
+        let init = [vec![half], vec![zero]];
+        assert_eq!(
+            MemoryWitness::new(&ck, &pp_digest, &S, &U, &W, &init).err(),
+            Some(MyError::MemoryError)
+        );
+        let (M, MW) = MemoryWitness::with_state(
+            &ck,
+            &pp_digest,
+            &S,
+            &U,
+            &W,
+            [init[0].clone(), init[1].clone(), vec![x], vec![one]],
+        )
+        .unwrap();
+
+        let (_, (folded_U, folded_W), (folded_M, folded_MW)) = MemoryNIFS::prove(
+            &ck,
+            &pp_digest,
+            &S,
+            (
+                &RelaxedPLONKInstance::default(&ck, &S),
+                &RelaxedPLONKWitness::default(&S),
+            ),
+            (
+                &RelaxedMemoryInstance::default(&ck, &S, &init).unwrap(),
+                &RelaxedMemoryWitness::default(&S),
+            ),
+            (&U, &W),
+            (&M, &MW),
+        )
+        .unwrap();
+        S.is_sat_relaxed(&ck, &folded_U, &folded_W).unwrap();
+        assert_eq!(
+            S.is_sat_memory(&ck, &folded_U, &folded_W, &folded_M, &folded_MW),
+            Err(MyError::UnSatMemorySum)
+        );
+    }
+
+    #[test]
+    fn test_memory_fingerprint() {
+        test_memory_fingerprint_with::<Bn254>();
+        test_memory_fingerprint_with::<Grumpkin>();
+    }
+}
@@ -1,17 +1,46 @@
+/// Non-interactive Folding Scheme for relaxed PLONK
+///
 use crate::{
+    constants::{NUM_CHALLENGE_BITS, NUM_HASH_BITS},
     error::MyError,
     plonk::*,
-    traits::{Group, ROConstantsTrait, ROTrait},
-    Commitment, CommitmentKey,
+    scalar_as_base, scalar_as_limbs,
+    traits::{AbsorbInROTrait, CommitmentEngineTrait, Group, ROConstantsTrait, ROTrait},
+    Commitment, CommitmentKey, ROConstants,
 };
+use ark_ff::{BigInteger, PrimeField};
+use rayon::prelude::*;
 use std::marker::PhantomData;
 
+/// A folding proof of relaxed PLONK, the commitments to the cross terms of the two folded instances
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct NIFS<G: Group> {
-    pub(crate) comm_T: Commitment<G>,
+    pub(crate) comm_T: Vec<Commitment<G>>,
     _p: PhantomData<G>,
 }
 
+/// the transcript shared by prover and verifier, up to the cross terms
+pub(crate) fn transcript<G: Group>(
+    pp_digest: &<G as Group>::ScalarField,
+    U1: &impl AbsorbInROTrait<G>,
+    U2: &impl AbsorbInROTrait<G>,
+) -> G::RO {
+    let mut ro = <<G as Group>::RO as ROTrait<
+        <G as Group>::BaseField,
+        <G as Group>::ScalarField,
+    >>::new(ROConstants::<G>::new(3));
+
+    scalar_as_limbs::<G>(pp_digest)
+        .into_iter()
+        .for_each(|limb| ro.absorb(limb));
+    U1.absorb_in_ro(&mut ro);
+    U2.absorb_in_ro(&mut ro);
+    ro
+}
+
 impl<G: Group> NIFS<G> {
+    /// folds a PLONK instance-witness pair into a relaxed running one,
+    /// returning the proof together with the folded pair
     pub fn prove(
         ck: &CommitmentKey<G>,
         pp_digest: &<G as Group>::ScalarField,
@@ -21,22 +50,577 @@ impl<G: Group> NIFS<G> {
         U2: &PLONKInstance<G>,
         W2: &PLONKWitness<G>,
     ) -> Result<(NIFS<G>, (RelaxedPLONKInstance<G>, RelaxedPLONKWitness<G>)), MyError> {
-        let constants = <<G as Group>::RO as ROTrait<
-            <G as Group>::BaseField,
-            <G as Group>::ScalarField,
-        >>::Constants::new(3);
-        let ro = <<G as Group>::RO as ROTrait<
-            <G as Group>::BaseField,
-            <G as Group>::ScalarField,
-        >>::new(constants.clone());
-        todo!()
+        let mut ro = transcript(pp_digest, U1, U2);
+
+        // compute cross terms and bind their commitments into the transcript
+        let (T, comm_T) = S.commit_T(ck, U1, W1, U2, W2)?;
+        comm_T.iter().for_each(|c| c.absorb_in_ro(&mut ro));
+
+        let r = ro.squeeze(NUM_CHALLENGE_BITS);
+
+        let U = U1.fold(U2, &comm_T, &r)?;
+        let W = W1.fold(W2, &T, &r)?;
+
+        Ok((
+            NIFS {
+                comm_T,
+                _p: PhantomData,
+            },
+            (U, W),
+        ))
     }
-    pub fn verifiy(
+
+    /// recomputes the challenge from the same transcript and folds the instances only
+    pub fn verify(
         &self,
         pp_digest: &<G as Group>::ScalarField,
         U1: &RelaxedPLONKInstance<G>,
         U2: &PLONKInstance<G>,
     ) -> Result<RelaxedPLONKInstance<G>, MyError> {
-        todo!()
+        let mut ro = transcript(pp_digest, U1, U2);
+        self.comm_T.iter().for_each(|c| c.absorb_in_ro(&mut ro));
+
+        let r = ro.squeeze(NUM_CHALLENGE_BITS);
+
+        U1.fold(U2, &self.comm_T, &r)
+    }
+
+    /// folds two relaxed instance-witness pairs, as the inner nodes of a tree of folds do,
+    /// returning the proof together with the folded pair
+    #[allow(clippy::type_complexity)]
+    pub fn prove_relaxed(
+        ck: &CommitmentKey<G>,
+        pp_digest: &<G as Group>::ScalarField,
+        S: &PLONKShape<G>,
+        U1: &RelaxedPLONKInstance<G>,
+        W1: &RelaxedPLONKWitness<G>,
+        U2: &RelaxedPLONKInstance<G>,
+        W2: &RelaxedPLONKWitness<G>,
+    ) -> Result<(NIFS<G>, (RelaxedPLONKInstance<G>, RelaxedPLONKWitness<G>)), MyError> {
+        let mut ro = transcript(pp_digest, U1, U2);
+
+        let (T, comm_T) = S.commit_T_relaxed(ck, U1, W1, U2, W2)?;
+        comm_T.iter().for_each(|c| c.absorb_in_ro(&mut ro));
+
+        let r = ro.squeeze(NUM_CHALLENGE_BITS);
+
+        let U = U1.fold_relaxed(U2, &comm_T, &r)?;
+        let W = W1.fold_relaxed(W2, &T, &r)?;
+
+        Ok((
+            NIFS {
+                comm_T,
+                _p: PhantomData,
+            },
+            (U, W),
+        ))
+    }
+
+    /// the verifier of `prove_relaxed`
+    pub fn verify_relaxed(
+        &self,
+        pp_digest: &<G as Group>::ScalarField,
+        U1: &RelaxedPLONKInstance<G>,
+        U2: &RelaxedPLONKInstance<G>,
+    ) -> Result<RelaxedPLONKInstance<G>, MyError> {
+        let mut ro = transcript(pp_digest, U1, U2);
+        self.comm_T.iter().for_each(|c| c.absorb_in_ro(&mut ro));
+
+        let r = ro.squeeze(NUM_CHALLENGE_BITS);
+
+        U1.fold_relaxed(U2, &self.comm_T, &r)
+    }
+}
+
+/// the NIFS proofs of a balanced tree of folds, one level after another from the leaves up,
+/// with the proofs of a level in the order of the pairs it folds
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TreeNIFS<G: Group> {
+    pub(crate) levels: Vec<Vec<NIFS<G>>>,
+}
+
+impl<G: Group> TreeNIFS<G> {
+    /// folds N PLONK instance-witness pairs pairwise in a balanced tree, the pairs of every level in parallel,
+    /// the odd node of a level is carried up as it is
+    #[allow(clippy::type_complexity)]
+    pub fn prove(
+        ck: &CommitmentKey<G>,
+        pp_digest: &<G as Group>::ScalarField,
+        S: &PLONKShape<G>,
+        us: &[PLONKInstance<G>],
+        ws: &[PLONKWitness<G>],
+    ) -> Result<
+        (
+            TreeNIFS<G>,
+            (RelaxedPLONKInstance<G>, RelaxedPLONKWitness<G>),
+        ),
+        MyError,
+    > {
+        if us.is_empty() || us.len() != ws.len() {
+            return Err(MyError::WitnessError);
+        }
+
+        let mut nodes = us
+            .par_iter()
+            .zip(ws)
+            .map(|(u, w)| {
+                (
+                    RelaxedPLONKInstance::from_plonk_instance(ck, S, u),
+                    RelaxedPLONKWitness::from_plonk_witness(S, w),
+                )
+            })
+            .collect::<Vec<_>>();
+        let mut levels = vec![];
+        while nodes.len() > 1 {
+            let (level, next): (Vec<_>, Vec<_>) = nodes
+                .par_chunks(2)
+                .map(|pair| match pair {
+                    [(U1, W1), (U2, W2)] => NIFS::prove_relaxed(ck, pp_digest, S, U1, W1, U2, W2)
+                        .map(|(nifs, folded)| (Some(nifs), folded)),
+                    _ => Ok((None, pair[0].clone())),
+                })
+                .collect::<Result<Vec<_>, MyError>>()?
+                .into_iter()
+                .unzip();
+            levels.push(level.into_iter().flatten().collect());
+            nodes = next;
+        }
+
+        Ok((TreeNIFS { levels }, nodes.remove(0)))
+    }
+
+    /// folds the instances along the same tree, verifying every NIFS proof on the way
+    pub fn verify(
+        &self,
+        ck: &CommitmentKey<G>,
+        pp_digest: &<G as Group>::ScalarField,
+        S: &PLONKShape<G>,
+        us: &[PLONKInstance<G>],
+    ) -> Result<RelaxedPLONKInstance<G>, MyError> {
+        if us.is_empty() {
+            return Err(MyError::ProofVerifyError);
+        }
+
+        let mut nodes = us
+            .par_iter()
+            .map(|u| RelaxedPLONKInstance::from_plonk_instance(ck, S, u))
+            .collect::<Vec<_>>();
+        for level in &self.levels {
+            if nodes.len() < 2 || level.len() != nodes.len() / 2 {
+                return Err(MyError::ProofVerifyError);
+            }
+            nodes = nodes
+                .par_chunks(2)
+                .enumerate()
+                .map(|(i, pair)| match pair {
+                    [U1, U2] => level[i].verify_relaxed(pp_digest, U1, U2),
+                    _ => Ok(pair[0].clone()),
+                })
+                .collect::<Result<Vec<_>, MyError>>()?;
+        }
+        if nodes.len() != 1 {
+            return Err(MyError::ProofVerifyError);
+        }
+
+        Ok(nodes.remove(0))
+    }
+}
+
+/// NIFS in the compressed mode, where the incoming instance brings a commitment to the powers of a fresh challenge β
+/// and the cross terms of the gates shrink to field elements, so that the proof holds comm_B of the incoming instance
+/// and a single cross term commitment t, and the verifier folds comm_W, comm_B and comm_E by r alone instead of
+/// `num_wire_types - 1` error commitments by the powers of r, which is what the augmented circuit of the recursion
+/// verifies
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CompressedNIFS<G: Group> {
+    pub(crate) comm_B: Commitment<G>,
+    pub(crate) comm_t: Commitment<G>,
+    pub(crate) e_T: Vec<<G as Group>::ScalarField>,
+}
+
+/// the hash of the transcript of both instances, whose low `NUM_CHALLENGE_BITS` bits are β of the incoming instance,
+/// so that the folding challenge is drawn from the hash rather than from the instances once more
+fn beta_and_hash<G: Group>(
+    pp_digest: &<G as Group>::ScalarField,
+    U1: &CompressedRelaxedPLONKInstance<G>,
+    U2: &PLONKInstance<G>,
+) -> (<G as Group>::ScalarField, <G as Group>::ScalarField) {
+    let hash = transcript(pp_digest, U1, U2).squeeze(NUM_HASH_BITS);
+    let bits = hash.into_bigint().to_bits_le();
+    let beta = <G as Group>::ScalarField::from_bigint(
+        <<G as Group>::ScalarField as PrimeField>::BigInt::from_bits_le(
+            &bits[..NUM_CHALLENGE_BITS],
+        ),
+    )
+    .unwrap();
+    (beta, hash)
+}
+
+impl<G: Group> CompressedNIFS<G> {
+    /// absorbs the hash of the transcript and the proof, and squeezes the folding challenge
+    fn challenge(&self, hash: &<G as Group>::ScalarField) -> <G as Group>::ScalarField {
+        let mut ro = <<G as Group>::RO as ROTrait<
+            <G as Group>::BaseField,
+            <G as Group>::ScalarField,
+        >>::new(ROConstants::<G>::new(3));
+        ro.absorb(scalar_as_base::<G>(hash));
+        self.comm_B.absorb_in_ro(&mut ro);
+        self.comm_t.absorb_in_ro(&mut ro);
+        self.e_T
+            .iter()
+            .flat_map(scalar_as_limbs::<G>)
+            .for_each(|limb| ro.absorb(limb));
+        ro.squeeze(NUM_CHALLENGE_BITS)
+    }
+
+    /// folds a PLONK instance-witness pair into a compressed relaxed running one,
+    /// returning the proof together with the folded pair
+    #[allow(clippy::type_complexity)]
+    pub fn prove(
+        ck: &CommitmentKey<G>,
+        pp_digest: &<G as Group>::ScalarField,
+        S: &PLONKShape<G>,
+        U1: &CompressedRelaxedPLONKInstance<G>,
+        W1: &CompressedRelaxedPLONKWitness<G>,
+        U2: &PLONKInstance<G>,
+        W2: &PLONKWitness<G>,
+    ) -> Result<
+        (
+            CompressedNIFS<G>,
+            (
+                CompressedRelaxedPLONKInstance<G>,
+                CompressedRelaxedPLONKWitness<G>,
+            ),
+        ),
+        MyError,
+    > {
+        // β is bound to both instances, the incoming one commits to its powers
+        let (beta, hash) = beta_and_hash(pp_digest, U1, U2);
+        let B2 = S.beta_powers(&beta);
+        let comm_B = G::CE::commit(ck, B2.as_slice());
+
+        let (t, comm_t, e_T) = S.commit_compressed_T(ck, U1, W1, U2, W2, &B2)?;
+        let nifs = CompressedNIFS {
+            comm_B,
+            comm_t,
+            e_T,
+        };
+        let r = nifs.challenge(&hash);
+
+        let U = U1.fold(U2, &nifs.comm_B, &beta, &nifs.comm_t, &nifs.e_T, &r)?;
+        let W = W1.fold(W2, &B2, &t, &r)?;
+
+        Ok((nifs, (U, W)))
+    }
+
+    /// recomputes both challenges from the same transcripts and folds the instances only
+    pub fn verify(
+        &self,
+        pp_digest: &<G as Group>::ScalarField,
+        U1: &CompressedRelaxedPLONKInstance<G>,
+        U2: &PLONKInstance<G>,
+    ) -> Result<CompressedRelaxedPLONKInstance<G>, MyError> {
+        let (beta, hash) = beta_and_hash(pp_digest, U1, U2);
+        let r = self.challenge(&hash);
+
+        U1.fold(U2, &self.comm_B, &beta, &self.comm_t, &self.e_T, &r)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::{
+        gate::{Q_C, Q_ECC, Q_HASH, Q_LC, Q_MUL, Q_O},
+        provider::grumpkin::Grumpkin,
+    };
+    use ark_bn254::Bn254;
+    use ark_ff::{Field, UniformRand};
+    use jf_utils::test_rng;
+    use rand::rngs::StdRng;
+
+    /// random TurboPlonk selectors
+    pub(crate) fn random_shape<G: Group>(
+        rng: &mut StdRng,
+        num_cons: usize,
+        num_public_input: usize,
+    ) -> PLONKShape<G> {
+        let num_wire_types = 5;
+        let mut rand_col = || {
+            (0..num_cons)
+                .map(|_| <G as Group>::ScalarField::rand(rng))
+                .collect::<Vec<<G as Group>::ScalarField>>()
+        };
+        let q_lc = (0..num_wire_types - 1)
+            .map(|_| rand_col())
+            .collect::<Vec<_>>();
+        let q_mul = (0..2).map(|_| rand_col()).collect::<Vec<_>>();
+        let q_hash = (0..num_wire_types - 1)
+            .map(|_| rand_col())
+            .collect::<Vec<_>>();
+        let (q_ecc, q_o, q_c) = (rand_col(), rand_col(), rand_col());
+        // every wire position copies itself
+        let sigma = (0..num_wire_types)
+            .map(|i| (0..num_cons).map(|j| i * num_cons + j).collect())
+            .collect::<Vec<Vec<usize>>>();
+
+        PLONKShape::new(
+            num_cons,
+            num_wire_types,
+            num_public_input,
+            &q_c,
+            &q_lc,
+            &q_mul,
+            &q_ecc,
+            &q_hash,
+            &q_o,
+            &sigma,
+        )
+        .unwrap()
+    }
+
+    /// random input wires and public inputs, with the output wire solved from every gate
+    pub(crate) fn random_witness<G: Group>(
+        rng: &mut StdRng,
+        S: &PLONKShape<G>,
+    ) -> (PLONKWitness<G>, Vec<<G as Group>::ScalarField>) {
+        let mut rand_col = || {
+            (0..S.num_cons)
+                .map(|_| <G as Group>::ScalarField::rand(rng))
+                .collect::<Vec<<G as Group>::ScalarField>>()
+        };
+        let mut W = (0..S.num_wire_types - 1)
+            .map(|_| rand_col())
+            .collect::<Vec<_>>();
+        let X = rand_col()[..S.num_public_input].to_vec();
+
+        // q_o * w_o - q_ecc * w_0 * w_1 * w_2 * w_3 * w_o = PI + q_c + lc + mul + hash
+        let w_o = (0..S.num_cons)
+            .map(|i| {
+                let pi = if i < X.len() {
+                    X[i]
+                } else {
+                    <<G as Group>::ScalarField as Field>::ZERO
+                };
+                let q = |k: usize| S.selectors[k][i];
+                let lc = (0..4).fold(pi + q(Q_C), |acc, j| acc + q(Q_LC + j) * W[j][i]);
+                let mul = q(Q_MUL) * W[0][i] * W[1][i] + q(Q_MUL + 1) * W[2][i] * W[3][i];
+                let hash = (0..4).fold(<<G as Group>::ScalarField as Field>::ZERO, |acc, j| {
+                    acc + q(Q_HASH + j) * W[j][i].pow([5u64])
+                });
+                let coeff = q(Q_O) - q(Q_ECC) * W[0][i] * W[1][i] * W[2][i] * W[3][i];
+                (lc + mul + hash) * coeff.inverse().unwrap()
+            })
+            .collect::<Vec<_>>();
+        W.push(w_o);
+
+        (PLONKWitness::new(S, &W).unwrap(), X)
+    }
+
+    fn test_nifs_fold_with<G: Group>() {
+        let rng = &mut test_rng();
+        let (num_cons, num_public_input) = (8, 2);
+        let ck = G::CE::setup(rng, num_cons);
+        let pp_digest = <G as Group>::ScalarField::rand(rng);
+
+        let S = random_shape::<G>(rng, num_cons, num_public_input);
+
+        let mut running_U = RelaxedPLONKInstance::default(&ck, &S);
+        let mut running_W = RelaxedPLONKWitness::default(&S);
+        for _ in 0..3 {
+            let (W, X) = random_witness(rng, &S);
+            let U = PLONKInstance::new(&S, &W.commit(&ck), &X).unwrap();
+            S.is_sat(&ck, &U, &W).unwrap();
+            let (nifs, (folded_U, folded_W)) =
+                NIFS::prove(&ck, &pp_digest, &S, &running_U, &running_W, &U, &W).unwrap();
+            let verified_U = nifs.verify(&pp_digest, &running_U, &U).unwrap();
+            assert_eq!(verified_U, folded_U);
+
+            S.is_sat_relaxed(&ck, &folded_U, &folded_W).unwrap();
+            running_U = folded_U;
+            running_W = folded_W;
+        }
+
+        // a tampered output wire breaks the gate on its own row
+        running_W.W[4][3] += <<G as Group>::ScalarField as Field>::ONE;
+        assert_eq!(
+            S.is_sat_relaxed(&ck, &running_U, &running_W),
+            Err(MyError::UnSatIndex(3))
+        );
+    }
+
+    #[test]
+    fn test_nifs_fold() {
+        test_nifs_fold_with::<Bn254>();
+        test_nifs_fold_with::<Grumpkin>();
+    }
+
+    fn test_compressed_nifs_fold_with<G: Group>() {
+        let rng = &mut test_rng();
+        let (num_cons, num_public_input) = (8, 2);
+        let ck = G::CE::setup(rng, num_cons);
+        let pp_digest = <G as Group>::ScalarField::rand(rng);
+
+        let S = random_shape::<G>(rng, num_cons, num_public_input);
+
+        let mut running_U = CompressedRelaxedPLONKInstance::default(&ck, &S);
+        let mut running_W = CompressedRelaxedPLONKWitness::default(&S);
+        S.is_sat_compressed(&ck, &running_U, &running_W).unwrap();
+        for _ in 0..3 {
+            let (W, X) = random_witness(rng, &S);
+            let U = PLONKInstance::new(&S, &W.commit(&ck), &X).unwrap();
+            let (nifs, (folded_U, folded_W)) =
+                CompressedNIFS::prove(&ck, &pp_digest, &S, &running_U, &running_W, &U, &W).unwrap();
+            assert_eq!(nifs.e_T.len(), 5);
+            let verified_U = nifs.verify(&pp_digest, &running_U, &U).unwrap();
+            assert_eq!(verified_U, folded_U);
+
+            S.is_sat_compressed(&ck, &folded_U, &folded_W).unwrap();
+            running_U = folded_U;
+            running_W = folded_W;
+        }
+
+        // a tampered output wire breaks the compressed error
+        let mut tampered_W = running_W.clone();
+        tampered_W.W[4][3] += <<G as Group>::ScalarField as Field>::ONE;
+        assert_eq!(
+            S.is_sat_compressed(&ck, &running_U, &tampered_W),
+            Err(MyError::UnSatCompressed)
+        );
+
+        // as does a tampered power of β its recurrence
+        let mut tampered_W = running_W.clone();
+        tampered_W.B[3] += <<G as Group>::ScalarField as Field>::ONE;
+        assert_eq!(
+            S.is_sat_compressed(&ck, &running_U, &tampered_W),
+            Err(MyError::UnSatPower(2))
+        );
+
+        // an unsatisfied incoming instance leaves an unsatisfied running one
+        let (mut W, X) = random_witness(rng, &S);
+        W.W[4][0] += <<G as Group>::ScalarField as Field>::ONE;
+        let U = PLONKInstance::new(&S, &W.commit(&ck), &X).unwrap();
+        let (_, (folded_U, folded_W)) =
+            CompressedNIFS::prove(&ck, &pp_digest, &S, &running_U, &running_W, &U, &W).unwrap();
+        assert_eq!(
+            S.is_sat_compressed(&ck, &folded_U, &folded_W),
+            Err(MyError::UnSatCompressed)
+        );
+    }
+
+    fn test_relaxed_nifs_fold_with<G: Group>() {
+        let rng = &mut test_rng();
+        let (num_cons, num_public_input) = (8, 2);
+        let ck = G::CE::setup(rng, num_cons);
+        let pp_digest = <G as Group>::ScalarField::rand(rng);
+
+        let S = random_shape::<G>(rng, num_cons, num_public_input);
+
+        // four leaves, each a fresh instance folded into the default one
+        let mut leaves = (0..4)
+            .map(|_| {
+                let (W, X) = random_witness(rng, &S);
+                let U = PLONKInstance::new(&S, &W.commit(&ck), &X).unwrap();
+                let (_, folded) = NIFS::prove(
+                    &ck,
+                    &pp_digest,
+                    &S,
+                    &RelaxedPLONKInstance::default(&ck, &S),
+                    &RelaxedPLONKWitness::default(&S),
+                    &U,
+                    &W,
+                )
+                .unwrap();
+                folded
+            })
+            .collect::<Vec<_>>();
+
+        // fold them pairwise up to the root, so that inner nodes fold non-trivial u and error vectors on both sides
+        while leaves.len() > 1 {
+            leaves = leaves
+                .chunks(2)
+                .map(|pair| {
+                    let ((U1, W1), (U2, W2)) = (&pair[0], &pair[1]);
+                    let (nifs, (folded_U, folded_W)) =
+                        NIFS::prove_relaxed(&ck, &pp_digest, &S, U1, W1, U2, W2).unwrap();
+                    let verified_U = nifs.verify_relaxed(&pp_digest, U1, U2).unwrap();
+                    assert_eq!(verified_U, folded_U);
+                    S.is_sat_relaxed(&ck, &folded_U, &folded_W).unwrap();
+                    (folded_U, folded_W)
+                })
+                .collect();
+        }
+
+        // a tampered error vector of either side leaves an unsatisfied fold
+        let (U, mut W) = leaves.remove(0);
+        W.E[0][2] += <<G as Group>::ScalarField as Field>::ONE;
+        let (comm_W, comm_E) = W.commit(&ck);
+        let tampered_U = RelaxedPLONKInstance {
+            comm_W,
+            comm_E,
+            ..U.clone()
+        };
+        let (_, (folded_U, folded_W)) =
+            NIFS::prove_relaxed(&ck, &pp_digest, &S, &U, &W, &tampered_U, &W).unwrap();
+        assert_eq!(
+            S.is_sat_relaxed(&ck, &folded_U, &folded_W),
+            Err(MyError::UnSatIndex(2))
+        );
+    }
+
+    #[test]
+    fn test_relaxed_nifs_fold() {
+        test_relaxed_nifs_fold_with::<Bn254>();
+        test_relaxed_nifs_fold_with::<Grumpkin>();
+    }
+
+    fn test_tree_nifs_fold_with<G: Group>() {
+        let rng = &mut test_rng();
+        let (num_cons, num_public_input) = (8, 2);
+        let ck = G::CE::setup(rng, num_cons);
+        let pp_digest = <G as Group>::ScalarField::rand(rng);
+
+        let S = random_shape::<G>(rng, num_cons, num_public_input);
+
+        let (ws, us): (Vec<_>, Vec<_>) = (0..5)
+            .map(|_| {
+                let (W, X) = random_witness(rng, &S);
+                let U = PLONKInstance::new(&S, &W.commit(&ck), &X).unwrap();
+                (W, U)
+            })
+            .unzip();
+        for n in [1, 2, 5] {
+            let (tree, (folded_U, folded_W)) =
+                TreeNIFS::prove(&ck, &pp_digest, &S, &us[..n], &ws[..n]).unwrap();
+            // the odd leaf of five is carried up to the third level
+            let shape = tree.levels.iter().map(|l| l.len()).collect::<Vec<_>>();
+            assert_eq!(shape, [vec![], vec![1], vec![2, 1, 1]][n / 2]);
+            let verified_U = tree.verify(&ck, &pp_digest, &S, &us[..n]).unwrap();
+            assert_eq!(verified_U, folded_U);
+            S.is_sat_relaxed(&ck, &folded_U, &folded_W).unwrap();
+        }
+
+        // the proofs do not verify against other instances
+        let (tree, _) = TreeNIFS::prove(&ck, &pp_digest, &S, &us, &ws).unwrap();
+        let (folded_U, _) = TreeNIFS::prove(&ck, &pp_digest, &S, &us[1..], &ws[1..])
+            .unwrap()
+            .1;
+        assert_ne!(tree.verify(&ck, &pp_digest, &S, &us).unwrap(), folded_U);
+        assert_eq!(
+            tree.verify(&ck, &pp_digest, &S, &us[1..]),
+            Err(MyError::ProofVerifyError)
+        );
+    }
+
+    #[test]
+    fn test_tree_nifs_fold() {
+        test_tree_nifs_fold_with::<Bn254>();
+        test_tree_nifs_fold_with::<Grumpkin>();
+    }
+
+    #[test]
+    fn test_compressed_nifs_fold() {
+        test_compressed_nifs_fold_with::<Bn254>();
+        test_compressed_nifs_fold_with::<Grumpkin>();
     }
 }
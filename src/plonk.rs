@@ -3,8 +3,7 @@
 /// computation of cross terms followed from chapter 3.4 of protostar: https://eprint.iacr.org/2023/620.pdf
 ///
 // use ark_ec::pairing::Pairing;
-use ark_ff::{Field, PrimeField};
-use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial};
+use ark_ff::{BigInteger, Field, PrimeField};
 // use jf_primitives::pcs::prelude::Commitment;
 // use jf_primitives::pcs::{
 //     prelude::{PCSError, UnivariateKzgPCS, UnivariateProverParam, UnivariateUniversalParams},
@@ -12,11 +11,15 @@ use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial};
 // };
 use rand::rngs::StdRng;
 use rayon::prelude::*;
+use sha3::{Digest, Keccak256};
 
 use crate::error::MyError;
+use crate::gate::CustomGate;
+use crate::{lookup::Lookup, memory::Memory};
 // use crate::primary::kzg::gen_srs_for_testing;
 use crate::{
-    traits::{CommitmentEngineTrait, Group},
+    scalar_as_limbs,
+    traits::{AbsorbInROTrait, CommitmentEngineTrait, Group, ROTrait, TranscriptReprTrait},
     Commitment, CommitmentKey,
 };
 
@@ -36,12 +39,19 @@ pub struct PLONKShape<G: Group> {
     pub(crate) num_wire_types: usize,
     pub(crate) num_public_input: usize,
 
-    pub(crate) q_lc: Vec<Vec<<G as Group>::ScalarField>>,
-    pub(crate) q_mul: Vec<Vec<<G as Group>::ScalarField>>,
-    pub(crate) q_hash: Vec<Vec<<G as Group>::ScalarField>>,
-    pub(crate) q_ecc: Vec<<G as Group>::ScalarField>,
-    pub(crate) q_o: Vec<<G as Group>::ScalarField>,
-    pub(crate) q_c: Vec<<G as Group>::ScalarField>,
+    /// the gate every row satisfies, and the columns of its selectors
+    pub(crate) gate: CustomGate<<G as Group>::ScalarField>,
+    pub(crate) selectors: Vec<Vec<<G as Group>::ScalarField>>,
+
+    /// copy constraints, the position every wire position is wired to,
+    /// with positions flattened as `wire * num_cons + row`
+    pub(crate) sigma: Vec<Vec<usize>>,
+
+    /// the table a wire is looked up into, if any
+    pub(crate) lookup: Option<Lookup<<G as Group>::ScalarField>>,
+
+    /// the read-write memory the wires access, if any
+    pub(crate) memory: Option<Memory<<G as Group>::ScalarField>>,
 }
 
 /// A type that holds a witness for a given Plonk instance
@@ -74,13 +84,80 @@ pub struct RelaxedPLONKInstance<G: Group> {
     pub(crate) u: <G as Group>::ScalarField,
 }
 
+/// relaxed witness of the compressed mode, with B the powers of β the gates are combined with
+/// and E the error of their recurrence
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CompressedRelaxedPLONKWitness<G: Group> {
+    pub(crate) W: Vec<Vec<<G as Group>::ScalarField>>,
+    pub(crate) B: Vec<<G as Group>::ScalarField>,
+    pub(crate) E: Vec<<G as Group>::ScalarField>,
+}
+
+/// relaxed instance of the compressed mode, following chapter 3.5 of protostar:
+/// the gates are compressed into a single error e = Σ_i B_i * gate_i, while B_0 = u, B_1 = β and
+/// u * B_{i+1} - B_1 * B_i = E_i keep B the powers of β, a degree-2 relation that leaves a single error vector
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CompressedRelaxedPLONKInstance<G: Group> {
+    pub(crate) comm_W: Vec<Commitment<G>>,
+    pub(crate) comm_B: Commitment<G>,
+    pub(crate) comm_E: Commitment<G>,
+    pub(crate) X: Vec<<G as Group>::ScalarField>,
+    pub(crate) u: <G as Group>::ScalarField,
+    pub(crate) beta: <G as Group>::ScalarField,
+    pub(crate) e: <G as Group>::ScalarField,
+}
+
+/// u, W and X of a relaxed assignment
+type RelaxedAssignment<'a, G> = (
+    &'a <G as Group>::ScalarField,
+    &'a [Vec<<G as Group>::ScalarField>],
+    &'a [<G as Group>::ScalarField],
+);
+
 impl<G: Group> PLONK<G> {
     pub fn commitment_key(rng: &mut StdRng, degree: usize) -> CommitmentKey<G> {
         G::CE::setup(rng, degree)
     }
 }
 
-impl<G: Group> PLONKShape<G> {}
+impl<G: Group> PLONKShape<G> {
+    /// keccak digest over the sizes, gate, selectors, copy constraints, lookup and memory of the shape
+    pub fn digest(&self) -> [u8; 32] {
+        let mut hasher = Keccak256::new();
+        [self.num_cons, self.num_wire_types, self.num_public_input]
+            .iter()
+            .for_each(|n| hasher.update((*n as u64).to_le_bytes()));
+        hasher.update(self.gate.to_bytes());
+        self.selectors
+            .iter()
+            .flatten()
+            .for_each(|s| hasher.update(s.into_bigint().to_bytes_le()));
+        self.sigma
+            .iter()
+            .flatten()
+            .for_each(|p| hasher.update((*p as u64).to_le_bytes()));
+        if let Some(lookup) = &self.lookup {
+            hasher.update((lookup.wire as u64).to_le_bytes());
+            lookup
+                .selector
+                .iter()
+                .chain(&lookup.table)
+                .for_each(|v| hasher.update(v.into_bigint().to_bytes_le()));
+        }
+        if let Some(memory) = &self.memory {
+            memory
+                .wires
+                .iter()
+                .chain([&memory.size])
+                .for_each(|n| hasher.update((*n as u64).to_le_bytes()));
+            memory
+                .selector
+                .iter()
+                .for_each(|v| hasher.update(v.into_bigint().to_bytes_le()));
+        }
+        hasher.finalize().into()
+    }
+}
 
 impl<G: Group> PLONKWitness<G> {
     /// A method to create a witness object using a vector of scalars
@@ -100,9 +177,7 @@ impl<G: Group> PLONKWitness<G> {
         let com_W = self
             .W
             .iter()
-            .map(|w| {
-                G::CE::commit(ck, w.as_slice()).unwarp();
-            })
+            .map(|w| G::CE::commit(ck, w.as_slice()))
             .collect::<Vec<Commitment<G>>>();
         com_W
     }
@@ -133,7 +208,7 @@ impl<G: Group> RelaxedPLONKWitness<G> {
             W: (0..S.num_wire_types)
                 .map(|_| vec![<<G as Group>::ScalarField as Field>::ZERO; S.num_cons])
                 .collect::<Vec<Vec<<G as Group>::ScalarField>>>(),
-            E: (0..S.num_wire_types - 1)
+            E: (0..S.num_error_columns())
                 .map(|_| vec![<<G as Group>::ScalarField as Field>::ZERO; S.num_cons])
                 .collect::<Vec<Vec<<G as Group>::ScalarField>>>(),
         }
@@ -146,7 +221,7 @@ impl<G: Group> RelaxedPLONKWitness<G> {
     ) -> RelaxedPLONKWitness<G> {
         RelaxedPLONKWitness {
             W: witness.W.clone(),
-            E: (0..S.num_wire_types - 1)
+            E: (0..S.num_error_columns())
                 .map(|_| vec![<<G as Group>::ScalarField as Field>::ZERO; S.num_cons])
                 .collect::<Vec<Vec<<G as Group>::ScalarField>>>(),
         }
@@ -156,12 +231,7 @@ impl<G: Group> RelaxedPLONKWitness<G> {
     pub fn commit(&self, ck: &CommitmentKey<G>) -> (Vec<Commitment<G>>, Vec<Commitment<G>>) {
         let com_func = |vecs: &Vec<Vec<<G as Group>::ScalarField>>| {
             vecs.iter()
-                .map(|v| {
-                    let p = <DensePolynomial<<G as Group>::ScalarField> as DenseUVPolynomial<
-                        <G as Group>::ScalarField,
-                    >>::from_coefficients_vec(v.to_vec());
-                    UnivariateKzgPCS::<G>::commit(ck, &p).unwrap()
-                })
+                .map(|v| G::CE::commit(ck, v.as_slice()))
                 .collect::<Vec<Commitment<G>>>()
         };
 
@@ -180,30 +250,60 @@ impl<G: Group> RelaxedPLONKWitness<G> {
         let (W1, E1) = (&self.W, &self.E);
         let W2 = &W2.W;
 
-        if W1.len() != W2.len() {
+        if W1.len() != W2.len() || E1.len() != T.len() {
             return Err(MyError::WitnessError);
         }
 
         let fold_scalar_func =
             |a_vecs: &Vec<Vec<<G as Group>::ScalarField>>,
-             b_vecs: &Vec<Vec<<G as Group>::ScalarField>>| {
+             b_vecs: &Vec<Vec<<G as Group>::ScalarField>>,
+             scalars: &Vec<<G as Group>::ScalarField>| {
                 a_vecs
                     .par_iter()
                     .zip(b_vecs)
-                    .map(|(a_col, b_col)| {
+                    .zip(scalars)
+                    .map(|((a_col, b_col), s)| {
                         a_col
                             .par_iter()
                             .zip(b_col)
-                            .map(|(a, b)| *a + *r * *b)
+                            .map(|(a, b)| *a + *s * *b)
                             .collect::<Vec<<G as Group>::ScalarField>>()
                     })
                     .collect::<Vec<Vec<<G as Group>::ScalarField>>>()
             };
-        let W = fold_scalar_func(&W1, &W2);
-        let E = fold_scalar_func(&E1, T);
+        // the j-th error column absorbs the cross term of degree j + 1 in r
+        let W = fold_scalar_func(W1, W2, &vec![*r; W1.len()]);
+        let E = fold_scalar_func(E1, T, &PLONKShape::<G>::challenge_powers(r, T.len()));
 
         Ok(RelaxedPLONKWitness { W, E })
     }
+
+    /// Folds an incoming relaxed witness into the current one, whose error vectors,
+    /// the coefficient of r^d with d the degree of the gate, join the last error column
+    pub fn fold_relaxed(
+        &self,
+        W2: &RelaxedPLONKWitness<G>,
+        T: &Vec<Vec<<G as Group>::ScalarField>>,
+        r: &<G as Group>::ScalarField,
+    ) -> Result<RelaxedPLONKWitness<G>, MyError> {
+        if self.E.len() != W2.E.len() {
+            return Err(MyError::WitnessError);
+        }
+
+        let mut folded = self.fold(&PLONKWitness { W: W2.W.clone() }, T, r)?;
+        let r_d = r.pow([T.len() as u64 + 1]);
+        if let Some(last) = folded.E.last_mut() {
+            last.par_iter_mut().enumerate().for_each(|(row, e)| {
+                *e += r_d
+                    * W2.E
+                        .iter()
+                        .map(|col| col[row])
+                        .sum::<<G as Group>::ScalarField>()
+            });
+        }
+
+        Ok(folded)
+    }
 }
 
 impl<G: Group> RelaxedPLONKInstance<G> {
@@ -212,7 +312,7 @@ impl<G: Group> RelaxedPLONKInstance<G> {
             (0..S.num_wire_types)
                 .map(|_| Commitment::<G>::default())
                 .collect::<Vec<Commitment<G>>>(),
-            (0..S.num_wire_types - 1)
+            (0..S.num_error_columns())
                 .map(|_| Commitment::<G>::default())
                 .collect::<Vec<Commitment<G>>>(),
         );
@@ -253,17 +353,23 @@ impl<G: Group> RelaxedPLONKInstance<G> {
         }
     }
 
-    /// Folds an incoming RelaxedPLONKInstance into the current one
+    /// Folds an incoming PLONKInstance into the current one
     pub fn fold(
         &self,
         U2: &PLONKInstance<G>,
         comm_T: &Vec<Commitment<G>>,
         r: &<G as Group>::ScalarField,
     ) -> Result<RelaxedPLONKInstance<G>, MyError> {
-        let (X1, u1, comm_W_1, comm_E_1) =
-            (&self.X, &self.u, &self.comm_W.clone(), &self.comm_E.clone());
+        let (X1, u1, comm_W_1, comm_E_1) = (&self.X, &self.u, &self.comm_W, &self.comm_E);
         let (X2, comm_W_2) = (&U2.X, &U2.comm_W);
 
+        if X1.len() != X2.len() {
+            return Err(MyError::PublicIntputError);
+        }
+        if comm_W_1.len() != comm_W_2.len() || comm_E_1.len() != comm_T.len() {
+            return Err(MyError::CommitmentError);
+        }
+
         // weighted sum of X, comm_W, comm_E, and u
         let X = X1
             .par_iter()
@@ -271,20 +377,24 @@ impl<G: Group> RelaxedPLONKInstance<G> {
             .map(|(a, b)| *a + *r * *b)
             .collect::<Vec<<G as Group>::ScalarField>>();
 
-        let fold_comm_func = |comm_1: &Vec<Commitment<G>>, comm_2: &Vec<Commitment<G>>| {
-            comm_1
-                .par_iter()
-                .zip(comm_2)
-                .map(|(a, b)| {
-                    let a_affine: &E::G1Affine = a.as_ref();
-                    let b_affine: &E::G1Affine = b.as_ref();
-                    Commitment((*a_affine + *b_affine * *r).into_affine())
-                })
-                .collect::<Vec<Commitment<G>>>()
-        };
+        let fold_comm_func =
+            |comm_1: &Vec<Commitment<G>>,
+             comm_2: &Vec<Commitment<G>>,
+             scalars: &Vec<<G as Group>::ScalarField>| {
+                comm_1
+                    .par_iter()
+                    .zip(comm_2)
+                    .zip(scalars)
+                    .map(|((a, b), s)| a.clone() + b.clone() * *s)
+                    .collect::<Vec<Commitment<G>>>()
+            };
 
-        let comm_W = fold_comm_func(&self.comm_W, &U2.comm_W);
-        let comm_E = fold_comm_func(&self.comm_E, comm_T);
+        let comm_W = fold_comm_func(comm_W_1, comm_W_2, &vec![*r; comm_W_1.len()]);
+        let comm_E = fold_comm_func(
+            comm_E_1,
+            comm_T,
+            &PLONKShape::<G>::challenge_powers(r, comm_T.len()),
+        );
 
         let u = *u1 + *r;
 
@@ -295,6 +405,223 @@ impl<G: Group> RelaxedPLONKInstance<G> {
             u,
         })
     }
+
+    /// Folds an incoming RelaxedPLONKInstance into the current one, the same way as `RelaxedPLONKWitness::fold_relaxed`
+    pub fn fold_relaxed(
+        &self,
+        U2: &RelaxedPLONKInstance<G>,
+        comm_T: &Vec<Commitment<G>>,
+        r: &<G as Group>::ScalarField,
+    ) -> Result<RelaxedPLONKInstance<G>, MyError> {
+        if self.comm_E.len() != U2.comm_E.len() {
+            return Err(MyError::CommitmentError);
+        }
+
+        let U2_strict = PLONKInstance {
+            comm_W: U2.comm_W.clone(),
+            X: U2.X.clone(),
+        };
+        let mut folded = self.fold(&U2_strict, comm_T, r)?;
+        folded.u = self.u + *r * U2.u;
+        let r_d = r.pow([comm_T.len() as u64 + 1]);
+        if let Some(last) = folded.comm_E.last_mut() {
+            *last = U2
+                .comm_E
+                .iter()
+                .fold(last.clone(), |acc, c| acc + c.clone() * r_d);
+        }
+
+        Ok(folded)
+    }
+}
+
+impl<G: Group> AbsorbInROTrait<G> for PLONKInstance<G> {
+    fn absorb_in_ro(&self, ro: &mut G::RO) {
+        self.comm_W.iter().for_each(|c| c.absorb_in_ro(ro));
+        self.X
+            .iter()
+            .flat_map(scalar_as_limbs::<G>)
+            .for_each(|limb| ro.absorb(limb));
+    }
+}
+
+impl<G: Group> AbsorbInROTrait<G> for RelaxedPLONKInstance<G> {
+    fn absorb_in_ro(&self, ro: &mut G::RO) {
+        self.comm_W.iter().for_each(|c| c.absorb_in_ro(ro));
+        self.comm_E.iter().for_each(|c| c.absorb_in_ro(ro));
+        scalar_as_limbs::<G>(&self.u)
+            .into_iter()
+            .for_each(|limb| ro.absorb(limb));
+        self.X
+            .iter()
+            .flat_map(scalar_as_limbs::<G>)
+            .for_each(|limb| ro.absorb(limb));
+    }
+}
+
+/// the commitments, u and the public input, in the same order as they go into the RO
+impl<G: Group> TranscriptReprTrait<G> for RelaxedPLONKInstance<G> {
+    fn to_transcript_bytes(&self) -> Vec<u8> {
+        self.comm_W
+            .iter()
+            .chain(&self.comm_E)
+            .flat_map(|c| c.to_transcript_bytes())
+            .chain(TranscriptReprTrait::<G>::to_transcript_bytes(&self.u))
+            .chain(
+                self.X
+                    .iter()
+                    .flat_map(|x| TranscriptReprTrait::<G>::to_transcript_bytes(x)),
+            )
+            .collect()
+    }
+}
+
+impl<G: Group> TranscriptReprTrait<G> for CompressedRelaxedPLONKInstance<G> {
+    fn to_transcript_bytes(&self) -> Vec<u8> {
+        self.comm_W
+            .iter()
+            .chain([&self.comm_B, &self.comm_E])
+            .flat_map(|c| c.to_transcript_bytes())
+            .chain(
+                [&self.u, &self.beta, &self.e]
+                    .into_iter()
+                    .chain(&self.X)
+                    .flat_map(|x| TranscriptReprTrait::<G>::to_transcript_bytes(x)),
+            )
+            .collect()
+    }
+}
+
+impl<G: Group> CompressedRelaxedPLONKWitness<G> {
+    /// Produces a default CompressedRelaxedPLONKWitness given an PLONKShape
+    pub fn default(S: &PLONKShape<G>) -> CompressedRelaxedPLONKWitness<G> {
+        let zero = vec![<<G as Group>::ScalarField as Field>::ZERO; S.num_cons];
+        CompressedRelaxedPLONKWitness {
+            W: vec![zero.clone(); S.num_wire_types],
+            B: zero.clone(),
+            E: zero,
+        }
+    }
+
+    /// Commits to the witness using the supplied generators
+    pub fn commit(
+        &self,
+        ck: &CommitmentKey<G>,
+    ) -> (Vec<Commitment<G>>, Commitment<G>, Commitment<G>) {
+        let comm_W = self
+            .W
+            .iter()
+            .map(|w| G::CE::commit(ck, w.as_slice()))
+            .collect::<Vec<Commitment<G>>>();
+        (
+            comm_W,
+            G::CE::commit(ck, self.B.as_slice()),
+            G::CE::commit(ck, self.E.as_slice()),
+        )
+    }
+
+    /// Folds an incoming PLONKWitness, together with the powers B2 of its β, into the current one
+    pub fn fold(
+        &self,
+        W2: &PLONKWitness<G>,
+        B2: &[<G as Group>::ScalarField],
+        t: &[<G as Group>::ScalarField],
+        r: &<G as Group>::ScalarField,
+    ) -> Result<CompressedRelaxedPLONKWitness<G>, MyError> {
+        if self.W.len() != W2.W.len() || self.B.len() != B2.len() || self.E.len() != t.len() {
+            return Err(MyError::WitnessError);
+        }
+        let fold_vec = |a: &[<G as Group>::ScalarField], b: &[<G as Group>::ScalarField]| {
+            a.par_iter()
+                .zip(b)
+                .map(|(a, b)| *a + *r * *b)
+                .collect::<Vec<<G as Group>::ScalarField>>()
+        };
+        Ok(CompressedRelaxedPLONKWitness {
+            W: self
+                .W
+                .iter()
+                .zip(&W2.W)
+                .map(|(a, b)| fold_vec(a, b))
+                .collect(),
+            B: fold_vec(&self.B, B2),
+            E: fold_vec(&self.E, t),
+        })
+    }
+}
+
+impl<G: Group> CompressedRelaxedPLONKInstance<G> {
+    pub fn default(_ck: &CommitmentKey<G>, S: &PLONKShape<G>) -> CompressedRelaxedPLONKInstance<G> {
+        CompressedRelaxedPLONKInstance {
+            comm_W: vec![Commitment::<G>::default(); S.num_wire_types],
+            comm_B: Commitment::<G>::default(),
+            comm_E: Commitment::<G>::default(),
+            X: vec![<<G as Group>::ScalarField as Field>::ZERO; S.num_public_input],
+            u: <<G as Group>::ScalarField as Field>::ZERO,
+            beta: <<G as Group>::ScalarField as Field>::ZERO,
+            e: <<G as Group>::ScalarField as Field>::ZERO,
+        }
+    }
+
+    /// Folds an incoming PLONKInstance, together with the commitment to the powers of its β, into the current one,
+    /// the error e takes the cross terms e_T as the coefficients of r, r^2, ...
+    #[allow(clippy::too_many_arguments)]
+    pub fn fold(
+        &self,
+        U2: &PLONKInstance<G>,
+        comm_B2: &Commitment<G>,
+        beta2: &<G as Group>::ScalarField,
+        comm_t: &Commitment<G>,
+        e_T: &[<G as Group>::ScalarField],
+        r: &<G as Group>::ScalarField,
+    ) -> Result<CompressedRelaxedPLONKInstance<G>, MyError> {
+        if self.X.len() != U2.X.len() {
+            return Err(MyError::PublicIntputError);
+        }
+        if self.comm_W.len() != U2.comm_W.len() {
+            return Err(MyError::CommitmentError);
+        }
+
+        let X = self
+            .X
+            .iter()
+            .zip(&U2.X)
+            .map(|(a, b)| *a + *r * *b)
+            .collect::<Vec<<G as Group>::ScalarField>>();
+        let comm_W = self
+            .comm_W
+            .par_iter()
+            .zip(&U2.comm_W)
+            .map(|(a, b)| a.clone() + b.clone() * *r)
+            .collect::<Vec<Commitment<G>>>();
+        let e = PLONKShape::<G>::challenge_powers(r, e_T.len())
+            .iter()
+            .zip(e_T)
+            .fold(self.e, |acc, (r_k, c)| acc + *r_k * *c);
+
+        Ok(CompressedRelaxedPLONKInstance {
+            comm_W,
+            comm_B: self.comm_B.clone() + comm_B2.clone() * *r,
+            comm_E: self.comm_E.clone() + comm_t.clone() * *r,
+            X,
+            u: self.u + *r,
+            beta: self.beta + *r * *beta2,
+            e,
+        })
+    }
+}
+
+impl<G: Group> AbsorbInROTrait<G> for CompressedRelaxedPLONKInstance<G> {
+    fn absorb_in_ro(&self, ro: &mut G::RO) {
+        self.comm_W.iter().for_each(|c| c.absorb_in_ro(ro));
+        self.comm_B.absorb_in_ro(ro);
+        self.comm_E.absorb_in_ro(ro);
+        [&self.u, &self.beta, &self.e]
+            .into_iter()
+            .chain(self.X.iter())
+            .flat_map(scalar_as_limbs::<G>)
+            .for_each(|limb| ro.absorb(limb));
+    }
 }
 
 impl<G: Group> PLONKShape<G> {
@@ -308,6 +635,7 @@ impl<G: Group> PLONKShape<G> {
         q_ecc: &Vec<<G as Group>::ScalarField>,
         q_hash: &Vec<Vec<<G as Group>::ScalarField>>,
         q_o: &Vec<<G as Group>::ScalarField>,
+        sigma: &Vec<Vec<usize>>,
     ) -> Result<PLONKShape<G>, MyError> {
         assert!(q_lc.len() == num_wire_types - 1);
         assert!(q_mul.len() == 2);
@@ -348,138 +676,338 @@ impl<G: Group> PLONKShape<G> {
             return Err(MyError::SelectorError);
         }
 
+        let selectors = [
+            q_lc.to_owned(),
+            q_mul.to_owned(),
+            q_hash.to_owned(),
+            vec![q_ecc.to_owned(), q_o.to_owned(), q_c.to_owned()],
+        ]
+        .concat();
+        Self::new_with_gate(
+            num_cons,
+            num_wire_types,
+            num_public_input,
+            CustomGate::turbo_plonk(),
+            selectors,
+            sigma,
+        )
+    }
+
+    /// a shape of a custom gate, with the selector columns in the order the gate refers to them
+    pub fn new_with_gate(
+        num_cons: usize,
+        num_wire_types: usize,
+        num_public_input: usize,
+        gate: CustomGate<<G as Group>::ScalarField>,
+        selectors: Vec<Vec<<G as Group>::ScalarField>>,
+        sigma: &[Vec<usize>],
+    ) -> Result<PLONKShape<G>, MyError> {
+        if selectors.len() != gate.num_selectors
+            || selectors.iter().any(|q| q.len() != num_cons)
+            || gate.num_wires() > num_wire_types
+        {
+            return Err(MyError::SelectorError);
+        }
+
+        // sigma must be a permutation over all wire positions
+        let num_positions = num_wire_types * num_cons;
+        if sigma.len() != num_wire_types || sigma.iter().any(|s| s.len() != num_cons) {
+            return Err(MyError::PermutationError);
+        }
+        let mut hit = vec![false; num_positions];
+        for pos in sigma.iter().flatten() {
+            if *pos >= num_positions || hit[*pos] {
+                return Err(MyError::PermutationError);
+            }
+            hit[*pos] = true;
+        }
+
         Ok(PLONKShape {
-            num_cons: num_cons,
-            num_wire_types: num_wire_types,
-            num_public_input: num_public_input,
-            q_c: q_c.to_owned(),
-            q_lc: q_lc.to_owned(),
-            q_mul: q_mul.to_owned(),
-            q_ecc: q_ecc.to_owned(),
-            q_hash: q_hash.to_owned(),
-            q_o: q_o.to_owned(),
+            num_cons,
+            num_wire_types,
+            num_public_input,
+            gate,
+            selectors,
+            sigma: sigma.to_owned(),
+            lookup: None,
+            memory: None,
         })
     }
 
-    fn grand_product(n: usize, vec: Vec<&<G as Group>::ScalarField>) -> <G as Group>::ScalarField {
-        let first: <G as Group>::ScalarField = *vec[0];
-        if n == 1 {
-            first
-        } else {
-            vec[1..].iter().fold(first, |acc, cur| acc * *cur)
+    /// attaches a table the given wire is looked up into, at the rows where the boolean selector is one
+    pub fn with_lookup(
+        mut self,
+        wire: usize,
+        selector: Vec<<G as Group>::ScalarField>,
+        table: Vec<<G as Group>::ScalarField>,
+    ) -> Result<PLONKShape<G>, MyError> {
+        if wire >= self.num_wire_types
+            || selector.len() != self.num_cons
+            || selector.iter().any(|q| {
+                *q != <G as Group>::ScalarField::ZERO && *q != <G as Group>::ScalarField::ONE
+            })
+            || table.is_empty()
+        {
+            return Err(MyError::LookupError);
         }
+        self.lookup = Some(Lookup {
+            wire,
+            selector,
+            table,
+        });
+        Ok(self)
     }
 
-    fn compute_cross_terms(
-        degree: usize,
-        u1: <G as Group>::ScalarField,
-        u2: <G as Group>::ScalarField,
-        inst1: &Vec<Vec<<G as Group>::ScalarField>>,
-        inst2: &Vec<Vec<<G as Group>::ScalarField>>,
-    ) -> Vec<Vec<<G as Group>::ScalarField>> {
-        assert!(inst1.len() == inst2.len(), "compute cross term");
-
-        let transpose_matrix = |mat: Vec<Vec<<G as Group>::ScalarField>>| {
-            let num_row = mat[0].len();
-            let mut mut_cols: Vec<_> = mat.into_iter().map(|col| col.into_iter()).collect();
-            (0..num_row)
-                .map(|_| {
-                    mut_cols
-                        .iter_mut()
-                        .map(|n| n.next().unwrap())
-                        .collect::<Vec<<G as Group>::ScalarField>>()
-                })
-                .collect::<Vec<Vec<<G as Group>::ScalarField>>>()
-        };
-        let trans_inst1 = transpose_matrix(inst1.clone());
-        let trans_inst2 = transpose_matrix(inst2.clone());
+    /// attaches a memory of the given size, accessed at the rows where the boolean selector is one, each access
+    /// reading (address, value, timestamp) from the first three of the given wires and writing the last two values
+    /// at the same address
+    ///
+    /// the gate must enforce that every write is stamped later than its read, t_w > t_r as integers, which the
+    /// memory argument relies on and does not check
+    pub fn with_memory(
+        mut self,
+        wires: [usize; 5],
+        selector: Vec<<G as Group>::ScalarField>,
+        size: usize,
+    ) -> Result<PLONKShape<G>, MyError> {
+        if wires.iter().any(|w| *w >= self.num_wire_types)
+            || selector.len() != self.num_cons
+            || selector.iter().any(|q| {
+                *q != <G as Group>::ScalarField::ZERO && *q != <G as Group>::ScalarField::ONE
+            })
+            || size == 0
+        {
+            return Err(MyError::MemoryError);
+        }
+        self.memory = Some(Memory {
+            wires,
+            selector,
+            size,
+        });
+        Ok(self)
+    }
 
-        let max_degree = 5 as usize;
-        (1..max_degree)
-            .rev()
-            .map(|r_degree| {
-                let l_degree = max_degree - r_degree;
+    /// the column of the selector of the given name
+    pub fn selector(&self, name: &str) -> Option<&[<G as Group>::ScalarField]> {
+        self.gate
+            .selector_index(name)
+            .map(|i| self.selectors[i].as_slice())
+    }
 
-                trans_inst1
-                    .par_iter()
-                    .zip(&trans_inst2)
-                    .map(|(row_a, row_b)| {
-                        let l_vars = vec![
-                            vec![&u1; degree],
-                            row_a
-                                .into_iter()
-                                .map(|a| a)
-                                .collect::<Vec<&<G as Group>::ScalarField>>(),
-                        ]
-                        .concat();
-                        let r_vars = vec![
-                            vec![&u2; degree],
-                            row_b
-                                .into_iter()
-                                .map(|a| a)
-                                .collect::<Vec<&<G as Group>::ScalarField>>(),
-                        ]
-                        .concat();
-                        // let l_vars = vec![vec![u1; degree], row_a].concat();
-                        // let r_vars = vec![vec![u2; degree], row_b].concat();
-                        Self::grand_product(l_degree, l_vars)
-                            * Self::grand_product(r_degree, r_vars)
-                    })
-                    .collect::<Vec<<G as Group>::ScalarField>>()
+    /// the number of error vectors, one per cross term of the gate
+    pub fn num_error_columns(&self) -> usize {
+        self.gate.degree - 1
+    }
+
+    /// the homogenized gate at the given row
+    fn gate_residual(
+        &self,
+        row: usize,
+        u: &<G as Group>::ScalarField,
+        W: &[Vec<<G as Group>::ScalarField>],
+        X: &[<G as Group>::ScalarField],
+    ) -> <G as Group>::ScalarField {
+        self.gate.evaluate(&self.selectors, row, u, W, X)
+    }
+
+    /// the first position, as (wire, row), holding a value different from the one it is wired to.
+    /// copy constraints are linear in the witness, so they are preserved by folding as is
+    pub(crate) fn copy_violation(
+        &self,
+        W: &[Vec<<G as Group>::ScalarField>],
+    ) -> Option<(usize, usize)> {
+        let n = self.num_cons;
+        (0..self.num_wire_types * n)
+            .into_par_iter()
+            .find_first(|pos| {
+                let to = self.sigma[pos / n][pos % n];
+                W[pos / n][pos % n] != W[to / n][to % n]
             })
-            .rev()
-            .collect::<Vec<Vec<<G as Group>::ScalarField>>>()
+            .map(|pos| (pos / n, pos % n))
     }
 
-    fn compute_cross_terms_five_exp(
-        inst1: &Vec<<G as Group>::ScalarField>,
-        inst2: &Vec<<G as Group>::ScalarField>,
-    ) -> Vec<Vec<<G as Group>::ScalarField>> {
-        let count_combination = |n: usize, r: usize| {
-            if r > n {
-                0
-            } else {
-                (1..=r).fold(1, |acc, val| acc * (n - val + 1) / val)
-            }
-        };
-        let vec_pow = |n: usize, vec: &Vec<<G as Group>::ScalarField>| {
-            vec.par_iter()
-                .map(|v| {
-                    let first = *v;
-                    if n == 1 {
-                        first
-                    } else {
-                        vec![v; n - 1].iter().fold(first, |a, b| a * *b)
-                    }
-                })
-                .collect::<Vec<<G as Group>::ScalarField>>()
-        };
+    /// Checks if the PLONK instance is satisfiable given a witness and its shape
+    pub fn is_sat(
+        &self,
+        ck: &CommitmentKey<G>,
+        U: &PLONKInstance<G>,
+        W: &PLONKWitness<G>,
+    ) -> Result<(), MyError> {
+        if W.W.len() != self.num_wire_types || W.W.iter().any(|w| w.len() != self.num_cons) {
+            return Err(MyError::WitnessError);
+        }
+        if U.X.len() != self.num_public_input {
+            return Err(MyError::PublicIntputError);
+        }
 
-        let max_degree: usize = 5;
-        (1..max_degree)
-            .rev()
-            .map(|r_degree| {
-                let l_degree = max_degree - r_degree;
-                let const_var = count_combination(max_degree, r_degree);
-                let const_scalar = <<G as Group>::ScalarField as PrimeField>::from_bigint(
-                    <<G as Group>::ScalarField as PrimeField>::BigInt::from(const_var as u32),
-                )
-                .unwrap();
-                let ref_const_scalar = &const_scalar;
-                let l_pow = vec_pow(l_degree, inst1);
-                let r_pow = vec_pow(r_degree, inst2);
-                l_pow
-                    .iter()
-                    .zip(r_pow)
-                    .map(|(a, b)| *ref_const_scalar * a * b)
-                    .collect::<Vec<<G as Group>::ScalarField>>()
+        // verify if the gate equation holds on every row
+        let one = <<G as Group>::ScalarField as Field>::ONE;
+        let unsat_row = (0..self.num_cons).into_par_iter().find_first(|row| {
+            self.gate_residual(*row, &one, &W.W, &U.X) != <<G as Group>::ScalarField as Field>::ZERO
+        });
+        if let Some(row) = unsat_row {
+            return Err(MyError::UnSatIndex(row));
+        }
+
+        // verify if the wiring holds
+        if let Some((wire, row)) = self.copy_violation(&W.W) {
+            return Err(MyError::UnSatCopy(wire, row));
+        }
+
+        // verify if comm_W is a commitment to W
+        if U.comm_W != W.commit(ck) {
+            return Err(MyError::CommitmentError);
+        }
+
+        Ok(())
+    }
+
+    /// Checks if the relaxed PLONK instance is satisfiable given a witness and its shape,
+    /// i.e. the homogenized gate equation at every row equals the sum of the error vectors,
+    /// and the wiring holds on the folded witness
+    pub fn is_sat_relaxed(
+        &self,
+        ck: &CommitmentKey<G>,
+        U: &RelaxedPLONKInstance<G>,
+        W: &RelaxedPLONKWitness<G>,
+    ) -> Result<(), MyError> {
+        if W.W.len() != self.num_wire_types
+            || W.E.len() != self.num_error_columns()
+            || W.W
+                .iter()
+                .chain(W.E.iter())
+                .any(|v| v.len() != self.num_cons)
+        {
+            return Err(MyError::WitnessError);
+        }
+        if U.X.len() != self.num_public_input {
+            return Err(MyError::PublicIntputError);
+        }
+
+        // verify if the homogenized gate equation holds on every row
+        let unsat_row = (0..self.num_cons).into_par_iter().find_first(|row| {
+            let e =
+                W.E.iter()
+                    .fold(<<G as Group>::ScalarField as Field>::ZERO, |acc, col| {
+                        acc + col[*row]
+                    });
+            self.gate_residual(*row, &U.u, &W.W, &U.X) != e
+        });
+        if let Some(row) = unsat_row {
+            return Err(MyError::UnSatIndex(row));
+        }
+
+        // verify if the wiring holds
+        if let Some((wire, row)) = self.copy_violation(&W.W) {
+            return Err(MyError::UnSatCopy(wire, row));
+        }
+
+        // verify if comm_W and comm_E are commitments to W and E
+        let (comm_W, comm_E) = W.commit(ck);
+        if U.comm_W != comm_W || U.comm_E != comm_E {
+            return Err(MyError::CommitmentError);
+        }
+
+        Ok(())
+    }
+
+    /// r, r^2, ..., r^n
+    pub(crate) fn challenge_powers(
+        r: &<G as Group>::ScalarField,
+        n: usize,
+    ) -> Vec<<G as Group>::ScalarField> {
+        (0..n)
+            .scan(<<G as Group>::ScalarField as Field>::ONE, |acc, _| {
+                *acc *= r;
+                Some(*acc)
+            })
+            .collect()
+    }
+
+    /// expand prod_i (a_i + X * b_i) into its coefficients, from the lowest degree of X to the highest one
+    pub(crate) fn expand_product(
+        factors: &[(&<G as Group>::ScalarField, &<G as Group>::ScalarField)],
+    ) -> Vec<<G as Group>::ScalarField> {
+        factors.iter().fold(
+            vec![<<G as Group>::ScalarField as Field>::ONE],
+            |acc, (a, b)| {
+                let mut next = vec![<<G as Group>::ScalarField as Field>::ZERO; acc.len() + 1];
+                acc.iter().enumerate().for_each(|(i, c)| {
+                    next[i] += *c * *a;
+                    next[i + 1] += *c * *b;
+                });
+                next
+            },
+        )
+    }
+
+    /// coefficients of the polynomial of degree evals.len() - 1 taking the given values at 0, 1, 2, ...
+    pub(crate) fn interpolate(
+        evals: &[<G as Group>::ScalarField],
+    ) -> Vec<<G as Group>::ScalarField> {
+        let points = (0..evals.len())
+            .map(|i| <G as Group>::ScalarField::from(i as u64))
+            .collect::<Vec<_>>();
+        let one = <<G as Group>::ScalarField as Field>::ONE;
+        let mut coefficients = vec![<<G as Group>::ScalarField as Field>::ZERO; evals.len()];
+        for (j, eval) in evals.iter().enumerate() {
+            // the lagrange basis of the j-th point, prod_{m != j} (X - m) / (j - m)
+            let neg_points = points
+                .iter()
+                .enumerate()
+                .filter(|(m, _)| *m != j)
+                .map(|(_, p)| -*p)
+                .collect::<Vec<_>>();
+            let factors = neg_points.iter().map(|p| (p, &one)).collect::<Vec<_>>();
+            let denominator = points
+                .iter()
+                .enumerate()
+                .filter(|(m, _)| *m != j)
+                .fold(one, |acc, (_, p)| acc * (points[j] - p));
+            let scale = *eval * denominator.inverse().unwrap();
+            Self::expand_product(&factors)
+                .iter()
+                .zip(coefficients.iter_mut())
+                .for_each(|(b, c)| *c += scale * b);
+        }
+        coefficients
+    }
+
+    /// the homogenized gate at U1 + x * U2 on every row, for x = 0, 1, ..., num_points - 1
+    fn fold_evaluations(
+        &self,
+        U1: RelaxedAssignment<'_, G>,
+        U2: RelaxedAssignment<'_, G>,
+        num_points: usize,
+    ) -> Vec<Vec<<G as Group>::ScalarField>> {
+        (0..num_points)
+            .map(|x| {
+                let x = <G as Group>::ScalarField::from(x as u64);
+                let fold = |a: &[<G as Group>::ScalarField], b: &[<G as Group>::ScalarField]| {
+                    a.iter()
+                        .zip(b)
+                        .map(|(a, b)| *a + x * b)
+                        .collect::<Vec<<G as Group>::ScalarField>>()
+                };
+                let u = *U1.0 + x * U2.0;
+                let W =
+                    U1.1.iter()
+                        .zip(U2.1)
+                        .map(|(a, b)| fold(a, b))
+                        .collect::<Vec<_>>();
+                let X = fold(U1.2, U2.2);
+                (0..self.num_cons)
+                    .into_par_iter()
+                    .map(|row| self.gate_residual(row, &u, &W, &X))
+                    .collect()
             })
-            .rev()
-            .collect::<Vec<Vec<<G as Group>::ScalarField>>>()
+            .collect()
     }
 
     //// compute cross terms and their commitments
-    /// 1. length of cross term vector equals max_degree - 1
+    /// 1. the homogenized gate of degree d at U1 + r * U2 is a polynomial of degree d in r, interpolated on every row
+    ///    from its values at d + 1 points
+    /// 2. its coefficients of r^1, ..., r^{d-1} are the cross terms, those of r^0 and r^d belong to the two instances
     pub fn commit_T(
         &self,
         ck: &CommitmentKey<G>,
@@ -488,148 +1016,220 @@ impl<G: Group> PLONKShape<G> {
         U2: &PLONKInstance<G>,
         W2: &PLONKWitness<G>,
     ) -> Result<(Vec<Vec<<G as Group>::ScalarField>>, Vec<Commitment<G>>), MyError> {
-        assert!(W1.W.len() == self.num_wire_types - 1, "wrong wires");
-        // q_ecc operation, u^0 * q_ecc * w_0 * w_1 * w_2 * w_3 * w_o
-        let ecc_T: Vec<Vec<<G as Group>::ScalarField>> = Self::compute_cross_terms(
-            0 as usize,
-            U1.u,
-            <<G as Group>::ScalarField as Field>::ONE,
-            &W1.W,
-            &W2.W,
-        );
+        let one = <<G as Group>::ScalarField as Field>::ONE;
+        self.cross_terms(ck, (&U1.u, &W1.W, &U1.X), (&one, &W2.W, &U2.X))
+    }
 
-        // q_lc operation, u^4 * (q_lc_0 * w_0 + q_lc_1 * w_1 + q_lc_2 * w_2 + q_lc_3 * w_3)
-        let lc_T = (0..self.num_wire_types - 1)
-            .map(|i| {
-                Self::compute_cross_terms(
-                    4,
-                    U1.u,
-                    <<G as Group>::ScalarField as Field>::ONE,
-                    &W1.W[i..i + 1].to_vec(),
-                    &W2.W[i..i + 1].to_vec(),
-                )
+    /// the cross terms of two relaxed instances, the same as `commit_T` but for u2 in place of 1
+    #[allow(clippy::type_complexity)]
+    pub fn commit_T_relaxed(
+        &self,
+        ck: &CommitmentKey<G>,
+        U1: &RelaxedPLONKInstance<G>,
+        W1: &RelaxedPLONKWitness<G>,
+        U2: &RelaxedPLONKInstance<G>,
+        W2: &RelaxedPLONKWitness<G>,
+    ) -> Result<(Vec<Vec<<G as Group>::ScalarField>>, Vec<Commitment<G>>), MyError> {
+        self.cross_terms(ck, (&U1.u, &W1.W, &U1.X), (&U2.u, &W2.W, &U2.X))
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn cross_terms(
+        &self,
+        ck: &CommitmentKey<G>,
+        U1: RelaxedAssignment<'_, G>,
+        U2: RelaxedAssignment<'_, G>,
+    ) -> Result<(Vec<Vec<<G as Group>::ScalarField>>, Vec<Commitment<G>>), MyError> {
+        if U1.1.len() != self.num_wire_types || U2.1.len() != self.num_wire_types {
+            return Err(MyError::WitnessError);
+        }
+        if U1.2.len() != U2.2.len() {
+            return Err(MyError::PublicIntputError);
+        }
+
+        let degree = self.gate.degree;
+        let one = <<G as Group>::ScalarField as Field>::ONE;
+        let evals = self.fold_evaluations(U1, U2, degree + 1);
+        // coefficients of the lagrange basis over 0, 1, ..., d
+        let basis = (0..=degree)
+            .map(|x| {
+                let mut e = vec![<<G as Group>::ScalarField as Field>::ZERO; degree + 1];
+                e[x] = one;
+                Self::interpolate(&e)
             })
-            .collect::<Vec<Vec<Vec<<G as Group>::ScalarField>>>>();
-
-        // q_mul operation, u^3 * (q_mul_0 * w_0 * w_1 + q_mul_1 * w_2 * w_3)
-        let mul_T = (0..self.num_wire_types - 1)
-            .step_by(2)
-            .map(|i| {
-                Self::compute_cross_terms(
-                    3,
-                    U1.u,
-                    <<G as Group>::ScalarField as Field>::ONE,
-                    &W1.W[i..i + 2].to_vec(),
-                    &W2.W[i..i + 2].to_vec(),
-                )
+            .collect::<Vec<_>>();
+        let T = (1..degree)
+            .map(|k| {
+                (0..self.num_cons)
+                    .into_par_iter()
+                    .map(|row| {
+                        (0..=degree).fold(<<G as Group>::ScalarField as Field>::ZERO, |acc, x| {
+                            acc + basis[x][k] * evals[x][row]
+                        })
+                    })
+                    .collect::<Vec<<G as Group>::ScalarField>>()
             })
-            .collect::<Vec<Vec<Vec<<G as Group>::ScalarField>>>>();
-
-        // q_out operation, u^4 * (q_o * w_o)
-        let out_T = Self::compute_cross_terms(
-            4,
-            U1.u,
-            <<G as Group>::ScalarField as Field>::ONE,
-            &W1.W[self.num_wire_types - 1..].to_vec(),
-            &W2.W[self.num_wire_types - 1..].to_vec(),
-        );
+            .collect::<Vec<_>>();
 
-        // q_c operation, u^5 * q_c
-        let u1_vec = vec![U1.u; self.num_cons];
-        let u2_vec = vec![<<G as Group>::ScalarField as Field>::ONE; self.num_cons];
-        let const_T = Self::compute_cross_terms_five_exp(&u1_vec, &u2_vec);
-
-        // q_hash operation, u^0 * (q_hash_0 * w_0^5 + q_hash_1 * w_1^5 + q_hash_2 * w_2^5 + q_hash_3 * w_3^5)
-        let hash_T = (0..self.num_wire_types - 1)
-            .map(|i| Self::compute_cross_terms_five_exp(&W1.W[i], &W2.W[i]))
-            .collect::<Vec<Vec<Vec<<G as Group>::ScalarField>>>>();
-
-        //////////////////////////////// apply selectors on cross terms
-        let apply_selector =
-            |T: &Vec<Vec<<G as Group>::ScalarField>>, selector: &Vec<<G as Group>::ScalarField>| {
-                (0..self.num_wire_types - 1)
-                    .map(|i| {
-                        let ref_T = &T[i];
-                        ref_T
-                            .par_iter()
-                            .zip(selector)
-                            .map(|(a, b)| *a * *b)
-                            .collect::<Vec<<G as Group>::ScalarField>>()
-                    })
-                    .collect::<Vec<Vec<<G as Group>::ScalarField>>>()
-            };
+        let com_T = T
+            .iter()
+            .map(|coefficients| G::CE::commit(ck, coefficients.as_slice()))
+            .collect::<Vec<Commitment<G>>>();
+
+        Ok((T, com_T))
+    }
+}
 
-        let (ref_ecc_T, ref_out_T, ref_const_T, ref_q_ecc, ref_q_out, ref_q_const) =
-            (&ecc_T, &out_T, &const_T, &self.q_ecc, &self.q_o, &self.q_c);
-        let ecc_result = apply_selector(ref_ecc_T, ref_q_ecc);
-        let out_result = apply_selector(ref_out_T, ref_q_out);
-        let const_result = apply_selector(ref_const_T, ref_q_const);
+/// the compressed mode
+impl<G: Group> PLONKShape<G> {
+    /// 1, β, β^2, ..., β^{num_cons - 1}
+    pub fn beta_powers(&self, beta: &<G as Group>::ScalarField) -> Vec<<G as Group>::ScalarField> {
+        std::iter::once(<<G as Group>::ScalarField as Field>::ONE)
+            .chain(Self::challenge_powers(beta, self.num_cons - 1))
+            .collect()
+    }
 
-        let lc_result = (0..self.num_wire_types - 1)
-            .map(|i| {
-                let (ref_lc_T, ref_q_lc) = (&lc_T[i], &self.q_lc[i]);
-                apply_selector(ref_lc_T, ref_q_lc)
+    /// u * B_{i+1} - B_1 * B_i on every row, with nothing to constrain on the last one
+    fn power_errors(
+        u: &<G as Group>::ScalarField,
+        B: &[<G as Group>::ScalarField],
+    ) -> Vec<<G as Group>::ScalarField> {
+        (0..B.len())
+            .map(|i| match B.get(i + 1) {
+                Some(next) => *u * *next - B[1] * B[i],
+                None => <<G as Group>::ScalarField as Field>::ZERO,
             })
-            .collect::<Vec<Vec<Vec<<G as Group>::ScalarField>>>>();
+            .collect()
+    }
 
-        let hash_result = (0..self.num_wire_types - 1)
-            .map(|i| {
-                let (ref_hash_T, ref_q_hash) = (&hash_T[i], &self.q_hash[i]);
-                apply_selector(ref_hash_T, ref_q_hash)
+    /// Σ_i B_i * gate_i
+    fn compressed_error(
+        &self,
+        u: &<G as Group>::ScalarField,
+        W: &[Vec<<G as Group>::ScalarField>],
+        X: &[<G as Group>::ScalarField],
+        B: &[<G as Group>::ScalarField],
+    ) -> <G as Group>::ScalarField {
+        (0..self.num_cons)
+            .into_par_iter()
+            .map(|row| B[row] * self.gate_residual(row, u, W, X))
+            .sum()
+    }
+
+    /// compute cross terms of the compressed mode and their commitment
+    /// 1. t, the cross term of the recurrence of the powers, the only vector to be committed
+    /// 2. e_T, the coefficients of r, ..., r^max_degree of Σ_i (B1_i + r * B2_i) * gate_i(U1 + r * U2),
+    ///    interpolated from max_degree + 2 points, the highest one being zero for a satisfied incoming instance
+    #[allow(clippy::type_complexity)]
+    pub fn commit_compressed_T(
+        &self,
+        ck: &CommitmentKey<G>,
+        U1: &CompressedRelaxedPLONKInstance<G>,
+        W1: &CompressedRelaxedPLONKWitness<G>,
+        U2: &PLONKInstance<G>,
+        W2: &PLONKWitness<G>,
+        B2: &[<G as Group>::ScalarField],
+    ) -> Result<
+        (
+            Vec<<G as Group>::ScalarField>,
+            Commitment<G>,
+            Vec<<G as Group>::ScalarField>,
+        ),
+        MyError,
+    > {
+        if W1.W.len() != self.num_wire_types
+            || W2.W.len() != self.num_wire_types
+            || W1.B.len() != self.num_cons
+            || B2.len() != self.num_cons
+        {
+            return Err(MyError::WitnessError);
+        }
+        if U1.X.len() != U2.X.len() {
+            return Err(MyError::PublicIntputError);
+        }
+
+        // (u1 + r) * (B1_{i+1} + r * B2_{i+1}) - (B1_1 + r * B2_1) * (B1_i + r * B2_i), whose r^2 term vanishes
+        // on the powers B2 of the incoming instance
+        let (B1, u1) = (&W1.B, &U1.u);
+        let t = (0..self.num_cons)
+            .map(|i| match (B1.get(i + 1), B2.get(i + 1)) {
+                (Some(B1_next), Some(B2_next)) => {
+                    *u1 * *B2_next + *B1_next - B1[1] * B2[i] - B2[1] * B1[i]
+                }
+                _ => <<G as Group>::ScalarField as Field>::ZERO,
             })
-            .collect::<Vec<Vec<Vec<<G as Group>::ScalarField>>>>();
+            .collect::<Vec<_>>();
+        let comm_t = G::CE::commit(ck, t.as_slice());
 
-        let mul_result = (0..2)
-            .map(|i| {
-                let (ref_mul_T, ref_q_mul) = (&mul_T[i], &self.q_mul[i]);
-                apply_selector(ref_mul_T, ref_q_mul)
+        let max_degree = self.gate.degree;
+        let gates = self.fold_evaluations(
+            (u1, &W1.W, &U1.X),
+            (&<<G as Group>::ScalarField as Field>::ONE, &W2.W, &U2.X),
+            max_degree + 2,
+        );
+        let evals = gates
+            .iter()
+            .enumerate()
+            .map(|(x, gate)| {
+                let x = <G as Group>::ScalarField::from(x as u64);
+                gate.par_iter()
+                    .zip(B1.par_iter().zip(B2))
+                    .map(|(g, (b1, b2))| (*b1 + x * b2) * g)
+                    .sum()
             })
-            .collect::<Vec<Vec<Vec<<G as Group>::ScalarField>>>>();
-
-        ////////////////////////////////////////// add-on all cross terms
-        let apply_mat_element_add =
-            |acc: &Vec<Vec<<G as Group>::ScalarField>>,
-             cur: &Vec<Vec<<G as Group>::ScalarField>>| {
-                acc.into_iter()
-                    .zip(cur)
-                    .map(|(a_col, b_col)| {
-                        a_col
-                            .iter()
-                            .zip(b_col)
-                            .map(|(a, b)| *a + *b)
-                            .collect::<Vec<<G as Group>::ScalarField>>()
-                    })
-                    .collect::<Vec<Vec<<G as Group>::ScalarField>>>()
-            };
+            .collect::<Vec<_>>();
+        let e_T = Self::interpolate(&evals)[1..=max_degree].to_vec();
 
-        let stack_T = vec![
-            vec![&ecc_result, &out_result, &const_result],
-            lc_result
-                .iter()
-                .collect::<Vec<&Vec<Vec<<G as Group>::ScalarField>>>>(),
-            hash_result
-                .iter()
-                .collect::<Vec<&Vec<Vec<<G as Group>::ScalarField>>>>(),
-            mul_result
+        Ok((t, comm_t, e_T))
+    }
+
+    /// Checks if the compressed relaxed PLONK instance is satisfiable given a witness and its shape,
+    /// i.e. B are the powers of β up to the error E, e compresses the gates and the wiring holds
+    pub fn is_sat_compressed(
+        &self,
+        ck: &CommitmentKey<G>,
+        U: &CompressedRelaxedPLONKInstance<G>,
+        W: &CompressedRelaxedPLONKWitness<G>,
+    ) -> Result<(), MyError> {
+        if W.W.len() != self.num_wire_types
+            || W.W
                 .iter()
-                .collect::<Vec<&Vec<Vec<<G as Group>::ScalarField>>>>(),
-        ]
-        .concat();
-        let T = stack_T[1..].iter().fold(stack_T[0].clone(), |acc, cur| {
-            apply_mat_element_add(&acc, cur)
-        });
+                .chain([&W.B, &W.E])
+                .any(|v| v.len() != self.num_cons)
+        {
+            return Err(MyError::WitnessError);
+        }
+        if U.X.len() != self.num_public_input {
+            return Err(MyError::PublicIntputError);
+        }
 
-        ////////////////////////////////////////// commit T
-        let com_T = T
-            .iter()
-            .map(|coefficients| {
-                let poly = <DensePolynomial<<G as Group>::ScalarField> as DenseUVPolynomial<
-                    <G as Group>::ScalarField,
-                >>::from_coefficients_vec(coefficients.clone());
-                UnivariateKzgPCS::<G>::commit(ck, &poly).unwrap()
-            })
-            .collect::<Vec<Commitment<G>>>();
+        // verify if B are the powers of β
+        if W.B[0] != U.u {
+            return Err(MyError::UnSatPower(0));
+        }
+        if self.num_cons > 1 && W.B[1] != U.beta {
+            return Err(MyError::UnSatPower(1));
+        }
+        let errors = Self::power_errors(&U.u, &W.B);
+        if let Some(row) = (0..self.num_cons).find(|i| errors[*i] != W.E[*i]) {
+            return Err(MyError::UnSatPower(row));
+        }
 
-        Ok((T, com_T))
+        // verify if e compresses the gates
+        if self.compressed_error(&U.u, &W.W, &U.X, &W.B) != U.e {
+            return Err(MyError::UnSatCompressed);
+        }
+
+        // verify if the wiring holds
+        if let Some((wire, row)) = self.copy_violation(&W.W) {
+            return Err(MyError::UnSatCopy(wire, row));
+        }
+
+        // verify if comm_W, comm_B and comm_E are commitments to W, B and E
+        if (U.comm_W.clone(), U.comm_B.clone(), U.comm_E.clone()) != W.commit(ck) {
+            return Err(MyError::CommitmentError);
+        }
+
+        Ok(())
     }
 }
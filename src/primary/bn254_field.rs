@@ -42,7 +42,7 @@ impl PoseidonDefaultConfig<4> for FqConfig {
 pub struct FrBackend;
 
 type FrConfig = MontBackend<FrBackend, 4>;
-pub type Fr = Fp256<MontBackend<FrConfig, 4>>;
+pub type Fr = Fp256<FrConfig>;
 
 impl PoseidonDefaultConfig<4> for FrConfig {
     const PARAMS_OPT_FOR_CONSTRAINTS: [PoseidonDefaultConfigEntry; 7] = [
@@ -1 +1,286 @@
-// primary circuit implementation based BN254 curve
+/// the step circuit of an incremental computation and the augmented circuit of Nova that wraps it, generic over the
+/// curve whose instances it folds, so that the same circuit runs on both sides of a cycle
+///
+use ark_ff::{Field, PrimeField};
+use jf_relation::{constants::GATE_WIDTH, Variable};
+
+use crate::{
+    circuit::{CircuitGroup, PlonkishCircuit, ROCircuitTrait},
+    constants::NUM_HASH_BITS,
+    error::MyError,
+    gadgets::{
+        ecc::AllocatedCommitment,
+        nonnative::alloc_limbs,
+        plonk::{AllocatedCompressedRelaxedPLONKInstance, AllocatedPLONKInstance},
+    },
+    gate::CustomGate,
+    nifs::CompressedNIFS,
+    plonk::{CompressedRelaxedPLONKInstance, PLONKInstance},
+    scalar_as_limbs,
+    traits::{AbsorbInROTrait, Group, ROConstantsTrait, ROTrait},
+    Commitment, ROConstants,
+};
+
+/// every instance folded by an augmented circuit comes from an augmented circuit, of five wires and two public inputs
+const NUM_WIRE_TYPES: usize = GATE_WIDTH + 1;
+const NUM_PUBLIC_INPUT: usize = 2;
+
+/// A helper trait for a step of the incremental computation (i.e., circuit for F), over the variables of a
+/// `PlonkishCircuit`, which stands in for Jellyfish's `PlonkCircuit` with the same gadgets, as the latter has no
+/// radix-2 domain over the scalar field of Grumpkin
+pub trait StepCircuit<F: PrimeField>: Send + Sync + Clone {
+    /// Return the the number of inputs or outputs of each step
+    /// (this method is called only at circuit synthesis time)
+    /// `synthesize` and `output` methods are expected to take as
+    /// input a vector of size equal to arity and output a vector of size equal to arity
+    fn arity(&self) -> usize;
+
+    /// Sythesize the circuit for a computation step and return variables
+    /// that corresponds to the output of the step z_{i+1}
+    fn synthesize(
+        &self,
+        cs: &mut PlonkishCircuit<F>,
+        z: &[Variable],
+    ) -> Result<Vec<Variable>, MyError>;
+
+    /// return the output of the step when provided with the step's input
+    fn output(&self, z: &[F]) -> Vec<F>;
+}
+
+/// A trivial step circuit that simply returns the input
+#[derive(Clone, Debug, Default)]
+pub struct TrivialTestCircuit<F: PrimeField> {
+    _p: std::marker::PhantomData<F>,
+}
+
+impl<F: PrimeField> StepCircuit<F> for TrivialTestCircuit<F> {
+    fn arity(&self) -> usize {
+        1
+    }
+
+    fn synthesize(
+        &self,
+        _cs: &mut PlonkishCircuit<F>,
+        z: &[Variable],
+    ) -> Result<Vec<Variable>, MyError> {
+        Ok(z.to_vec())
+    }
+
+    fn output(&self, z: &[F]) -> Vec<F> {
+        z.to_vec()
+    }
+}
+
+/// the hash of the state of the recursion, H(pp_digest, i, z0, zi, U) truncated to `NUM_HASH_BITS`,
+/// with U the running instance of the other curve
+pub(crate) fn hash_state<G: Group>(
+    pp_digest: &G::ScalarField,
+    i: usize,
+    z0: &[G::BaseField],
+    zi: &[G::BaseField],
+    U: &CompressedRelaxedPLONKInstance<G>,
+) -> G::ScalarField {
+    let mut ro = <G::RO as ROTrait<G::BaseField, G::ScalarField>>::new(ROConstants::<G>::new(3));
+    scalar_as_limbs::<G>(pp_digest)
+        .into_iter()
+        .for_each(|limb| ro.absorb(limb));
+    ro.absorb(G::BaseField::from(i as u64));
+    z0.iter().chain(zi).for_each(|z| ro.absorb(*z));
+    U.absorb_in_ro(&mut ro);
+    ro.squeeze(NUM_HASH_BITS)
+}
+
+/// the in-circuit counterpart of `hash_state`
+fn hash_state_circuit<G: CircuitGroup>(
+    cs: &mut PlonkishCircuit<G::BaseField>,
+    pp_digest: &[Variable],
+    i: Variable,
+    z0: &[Variable],
+    zi: &[Variable],
+    U: &AllocatedCompressedRelaxedPLONKInstance,
+) -> Result<Variable, MyError> {
+    let mut ro = G::ROCircuit::new(ROConstants::<G>::new(3));
+    pp_digest.iter().for_each(|limb| ro.absorb(*limb));
+    ro.absorb(i);
+    z0.iter().chain(zi).for_each(|z| ro.absorb(*z));
+    U.absorb_in_ro(&mut ro);
+    let bits = ro.squeeze(cs, NUM_HASH_BITS)?;
+    cs.from_bits_le(&bits)
+}
+
+/// inputs of an augmented circuit that folds instances of `G`, all of them are absent on the first step
+#[derive(Clone, Debug)]
+pub struct AugmentedCircuitInputs<G: Group> {
+    params: G::ScalarField,
+    i: usize,
+    z0: Vec<G::BaseField>,
+    zi: Option<Vec<G::BaseField>>,
+    U: Option<CompressedRelaxedPLONKInstance<G>>,
+    u: Option<PLONKInstance<G>>,
+    nifs: Option<CompressedNIFS<G>>,
+}
+
+impl<G: Group> AugmentedCircuitInputs<G> {
+    pub fn new(
+        params: G::ScalarField,
+        i: usize,
+        z0: Vec<G::BaseField>,
+        zi: Option<Vec<G::BaseField>>,
+        U: Option<CompressedRelaxedPLONKInstance<G>>,
+        u: Option<PLONKInstance<G>>,
+        nifs: Option<CompressedNIFS<G>>,
+    ) -> Self {
+        Self {
+            params,
+            i,
+            z0,
+            zi,
+            U,
+            u,
+            nifs,
+        }
+    }
+}
+
+/// The augmented circuit F' of Nova over the base field of `G`, which runs the step circuit F and the verifier of
+/// the folding of the last instance of `G` into its running one, and exposes [u.X[1], H(pp_digest, i + 1, z0, z_{i+1}, U')]
+///
+/// on the first step the primary circuit folds nothing and its running instance becomes the default one, while the
+/// secondary circuit folds the fresh instance of the primary curve into the default one
+pub struct AugmentedCircuit<'a, G: CircuitGroup, SC: StepCircuit<G::BaseField>> {
+    is_primary_circuit: bool,
+    inputs: Option<AugmentedCircuitInputs<G>>,
+    step_circuit: &'a SC,
+}
+
+impl<'a, G: CircuitGroup, SC: StepCircuit<G::BaseField>> AugmentedCircuit<'a, G, SC> {
+    pub fn new(
+        is_primary_circuit: bool,
+        inputs: Option<AugmentedCircuitInputs<G>>,
+        step_circuit: &'a SC,
+    ) -> Self {
+        Self {
+            is_primary_circuit,
+            inputs,
+            step_circuit,
+        }
+    }
+
+    /// synthesizes the circuit and returns the variables of z_{i+1}, without inputs all values are zero
+    /// so that the shape can be taken
+    pub fn synthesize(
+        &self,
+        cs: &mut PlonkishCircuit<G::BaseField>,
+    ) -> Result<Vec<Variable>, MyError> {
+        let arity = self.step_circuit.arity();
+        let zero = G::BaseField::ZERO;
+        let default_U = CompressedRelaxedPLONKInstance::<G> {
+            comm_W: vec![Commitment::<G>::default(); NUM_WIRE_TYPES],
+            comm_B: Commitment::<G>::default(),
+            comm_E: Commitment::<G>::default(),
+            X: vec![G::ScalarField::ZERO; NUM_PUBLIC_INPUT],
+            u: G::ScalarField::ZERO,
+            beta: G::ScalarField::ZERO,
+            e: G::ScalarField::ZERO,
+        };
+        let default_u = PLONKInstance::<G> {
+            comm_W: vec![Commitment::<G>::default(); NUM_WIRE_TYPES],
+            X: vec![G::ScalarField::ZERO; NUM_PUBLIC_INPUT],
+        };
+        let (params, i, z0, zi) = match &self.inputs {
+            Some(inputs) => (
+                inputs.params,
+                inputs.i,
+                inputs.z0.clone(),
+                inputs.zi.clone().unwrap_or_else(|| inputs.z0.clone()),
+            ),
+            None => (
+                G::ScalarField::ZERO,
+                0,
+                vec![zero; arity],
+                vec![zero; arity],
+            ),
+        };
+        if z0.len() != arity || zi.len() != arity {
+            return Err(MyError::InvalidInitialInputLength);
+        }
+        let inputs = self.inputs.as_ref();
+        let U_native = inputs.and_then(|x| x.U.clone()).unwrap_or(default_U);
+        let u_native = inputs.and_then(|x| x.u.clone()).unwrap_or(default_u);
+        let nifs = inputs
+            .and_then(|x| x.nifs.clone())
+            .unwrap_or(CompressedNIFS {
+                comm_B: Commitment::<G>::default(),
+                comm_t: Commitment::<G>::default(),
+                e_T: vec![G::ScalarField::ZERO; CustomGate::<G::ScalarField>::turbo_plonk().degree],
+            });
+
+        let pp_digest = alloc_limbs(cs, &params)?;
+        let i = cs.create_variable(G::BaseField::from(i as u64))?;
+        let z_0 = z0
+            .iter()
+            .map(|z| cs.create_variable(*z))
+            .collect::<Result<Vec<_>, MyError>>()?;
+        let z_i = zi
+            .iter()
+            .map(|z| cs.create_variable(*z))
+            .collect::<Result<Vec<_>, MyError>>()?;
+        let U = AllocatedCompressedRelaxedPLONKInstance::alloc(cs, &U_native)?;
+        let u = AllocatedPLONKInstance::alloc(cs, &u_native)?;
+        let comm_B = AllocatedCommitment::alloc::<G>(cs, &nifs.comm_B)?;
+        let comm_t = AllocatedCommitment::alloc::<G>(cs, &nifs.comm_t)?;
+        let e_T = nifs
+            .e_T
+            .iter()
+            .map(|e| alloc_limbs(cs, e))
+            .collect::<Result<Vec<_>, MyError>>()?;
+
+        let is_base_case = cs.is_zero(i)?;
+        let not_base_case = cs.logic_neg(is_base_case)?;
+
+        // the last instance of the other curve exposes the hash of the state it was produced from
+        let h = hash_state_circuit::<G>(cs, &pp_digest, i, &z_0, &z_i, &U)?;
+        let h_diff = cs.sub(h, u.X[0])?;
+        cs.enforce_mul_zero(h_diff, not_base_case)?;
+
+        let U_default =
+            AllocatedCompressedRelaxedPLONKInstance::default(cs, NUM_WIRE_TYPES, NUM_PUBLIC_INPUT);
+        let U_new = if self.is_primary_circuit {
+            let U_fold = U.fold::<G>(cs, &pp_digest, &u, (&comm_B, &comm_t, &e_T))?;
+            AllocatedCompressedRelaxedPLONKInstance::conditionally_select(
+                cs,
+                is_base_case,
+                &U_fold,
+                &U_default,
+            )?
+        } else {
+            // the running instance the first primary instance is folded into is the default one, whatever is given
+            let U = AllocatedCompressedRelaxedPLONKInstance::conditionally_select(
+                cs,
+                is_base_case,
+                &U,
+                &U_default,
+            )?;
+            U.fold::<G>(cs, &pp_digest, &u, (&comm_B, &comm_t, &e_T))?
+        };
+
+        let z_input = z_i
+            .iter()
+            .zip(&z_0)
+            .map(|(z_i, z_0)| cs.conditional_select(is_base_case, *z_i, *z_0))
+            .collect::<Result<Vec<_>, MyError>>()?;
+        let z_next = self.step_circuit.synthesize(cs, &z_input)?;
+        if z_next.len() != arity {
+            return Err(MyError::CircuitError);
+        }
+
+        let one = cs.one();
+        let i_new = cs.add(i, one)?;
+        let h_new = hash_state_circuit::<G>(cs, &pp_digest, i_new, &z_0, &z_next, &U_new)?;
+
+        cs.set_variable_public(u.X[1])?;
+        cs.set_variable_public(h_new)?;
+
+        Ok(z_next)
+    }
+}
@@ -1,51 +1,11 @@
-/// Non-interactive Folding Scheme based Plonkish Nova over BN254 curve
+/// Non-interactive Folding Scheme based Plonkish Nova over the primary curve of a cycle
 ///
+/// the transcript runs over the base field of the primary curve, i.e. the native field of the
+/// secondary circuit that verifies the folding, which the cycle fixes at the type level
 ///
-use ark_crypto_primitives::sponge::poseidon::PoseidonSponge;
-use ark_crypto_primitives::sponge::CryptographicSponge;
-use ark_ec::pairing::Pairing;
-use ark_ff::{BigInt, BigInteger};
-use ark_ff::{Field, PrimeField};
-use jf_primitives::pcs::prelude::Commitment;
+use crate::traits::CurveCycle;
 
-use std::marker::PhantomData;
-
-use super::bn254_field::Fq;
-use super::plonk::{
-    CommitmentKey, PLONKInstance, PLONKShape, PLONKWitness, RelaxedPLONKInstance,
-    RelaxedPLONKWitness,
-};
-use crate::error::MyError;
-use crate::poseidon::poseidon_constants::PoseidonDefaultConfigField;
-
-pub struct NIFS<E: Pairing> {
-    pub(crate) comm_T: Commitment<E>,
-    _p: PhantomData<E>,
-}
-
-impl<E: Pairing> NIFS<E> {
-    pub fn prove(
-        ck: &CommitmentKey<E>,
-        pp_digest: &E::ScalarField,
-        S: &PLONKShape<E>,
-        U1: &RelaxedPLONKInstance<E>,
-        W1: &RelaxedPLONKWitness<E>,
-        U2: &PLONKInstance<E>,
-        W2: &PLONKWitness<E>,
-    ) -> Result<(NIFS<E>, (RelaxedPLONKInstance<E>, RelaxedPLONKWitness<E>)), MyError> {
-        let sponge_constant = Fq::get_default_poseidon_parameters(3, false).unwrap();
-        let mut sponge = PoseidonSponge::<Fq>::new(&sponge_constant);
-        todo!()
-    }
-    pub fn verifiy(
-        &self,
-        pp_digest: &E::ScalarField,
-        U1: &RelaxedPLONKInstance<E>,
-        U2: &PLONKInstance<E>,
-    ) -> Result<RelaxedPLONKInstance<E>, MyError> {
-        todo!()
-    }
-}
+pub type NIFS<C> = crate::nifs::NIFS<<C as CurveCycle>::G1>;
 
 #[cfg(test)]
 mod tests {
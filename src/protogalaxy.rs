@@ -0,0 +1,520 @@
+/// Multi-instance folding for PLONK, following protogalaxy: https://eprint.iacr.org/2023/1106.pdf
+///
+/// the accumulator holds a vector β of log(num_cons) challenges and an error e = Σ_i pow_i(β) * gate_i,
+/// with pow_i(β) the product of the β_l over the bits l of i. k incoming instances are folded at once with
+/// the lagrange basis over {0, 1, ..., k}, the accumulator taking the place of 0, so that the prover sends
+/// the coefficients of two polynomials and no commitment at all
+///
+use ark_ff::Field;
+use rayon::prelude::*;
+
+use crate::{
+    constants::NUM_CHALLENGE_BITS,
+    error::MyError,
+    plonk::{PLONKInstance, PLONKShape, PLONKWitness},
+    scalar_as_limbs,
+    traits::{AbsorbInROTrait, CommitmentEngineTrait, Group, ROConstantsTrait, ROTrait},
+    Commitment, CommitmentKey, ROConstants,
+};
+
+/// the accumulated instance, all of whose gates are folded with u = 1
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccumulatedPLONKInstance<G: Group> {
+    pub(crate) comm_W: Vec<Commitment<G>>,
+    pub(crate) X: Vec<<G as Group>::ScalarField>,
+    pub(crate) beta: Vec<<G as Group>::ScalarField>,
+    pub(crate) e: <G as Group>::ScalarField,
+}
+
+/// the accumulated witness
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccumulatedPLONKWitness<G: Group> {
+    pub(crate) W: Vec<Vec<<G as Group>::ScalarField>>,
+}
+
+/// the proof of a multi-folding step, the coefficients of F(X) but its constant one and those of K(X)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MultiNIFS<G: Group> {
+    pub(crate) F_coeffs: Vec<<G as Group>::ScalarField>,
+    pub(crate) K_coeffs: Vec<<G as Group>::ScalarField>,
+}
+
+/// pow_i(β) for every row i
+fn pow_vector<F: Field>(beta: &[F]) -> Vec<F> {
+    beta.iter().fold(vec![F::ONE], |pows, b| {
+        let high = pows.iter().map(|p| *p * b).collect::<Vec<_>>();
+        [pows, high].concat()
+    })
+}
+
+/// δ, δ^2, δ^4, ..., δ^{2^{t-1}}
+fn squares<F: Field>(delta: &F, t: usize) -> Vec<F> {
+    (0..t)
+        .scan(*delta, |acc, _| {
+            let cur = *acc;
+            acc.square_in_place();
+            Some(cur)
+        })
+        .collect()
+}
+
+/// L_0(x), ..., L_{n-1}(x) of the lagrange basis over {0, 1, ..., n - 1}
+fn lagrange_evals<F: Field>(n: usize, x: &F) -> Vec<F> {
+    let points = (0..n).map(|i| F::from(i as u64)).collect::<Vec<_>>();
+    (0..n)
+        .map(|j| {
+            points
+                .iter()
+                .enumerate()
+                .filter(|(m, _)| *m != j)
+                .fold(F::ONE, |acc, (_, p)| {
+                    acc * (*x - p) * (points[j] - p).inverse().unwrap()
+                })
+        })
+        .collect()
+}
+
+/// Σ_k coeffs_k * x^k
+fn evaluate<F: Field>(coeffs: &[F], x: &F) -> F {
+    coeffs.iter().rev().fold(F::ZERO, |acc, c| acc * x + c)
+}
+
+/// the quotient of a polynomial by a monic one, dropping the remainder
+fn divide_by_monic<F: Field>(numerator: &[F], divisor: &[F]) -> Vec<F> {
+    let d = divisor.len() - 1;
+    if numerator.len() <= d {
+        return vec![];
+    }
+    let mut remainder = numerator.to_vec();
+    let mut quotient = vec![F::ZERO; numerator.len() - d];
+    for i in (0..quotient.len()).rev() {
+        let q = remainder[i + d];
+        quotient[i] = q;
+        divisor
+            .iter()
+            .enumerate()
+            .for_each(|(j, c)| remainder[i + j] -= q * c);
+    }
+    quotient
+}
+
+impl<G: Group> AccumulatedPLONKWitness<G> {
+    /// the all-zero witness
+    pub fn default(S: &PLONKShape<G>) -> AccumulatedPLONKWitness<G> {
+        AccumulatedPLONKWitness {
+            W: vec![vec![<G as Group>::ScalarField::ZERO; S.num_cons]; S.num_wire_types],
+        }
+    }
+}
+
+impl<G: Group> AccumulatedPLONKInstance<G> {
+    /// the all-zero instance with β = 0, whose error is then the gate of the first row on zero wires
+    pub fn default(_ck: &CommitmentKey<G>, S: &PLONKShape<G>) -> AccumulatedPLONKInstance<G> {
+        let W = AccumulatedPLONKWitness::default(S);
+        let X = vec![<G as Group>::ScalarField::ZERO; S.num_public_input];
+        AccumulatedPLONKInstance {
+            comm_W: vec![Commitment::<G>::default(); S.num_wire_types],
+            e: S.gates(&W.W, &X)[0],
+            X,
+            beta: vec![<G as Group>::ScalarField::ZERO; S.num_cons.trailing_zeros() as usize],
+        }
+    }
+}
+
+impl<G: Group> AbsorbInROTrait<G> for AccumulatedPLONKInstance<G> {
+    fn absorb_in_ro(&self, ro: &mut G::RO) {
+        self.comm_W.iter().for_each(|c| c.absorb_in_ro(ro));
+        self.X
+            .iter()
+            .chain(self.beta.iter())
+            .chain([&self.e])
+            .flat_map(scalar_as_limbs::<G>)
+            .for_each(|limb| ro.absorb(limb));
+    }
+}
+
+/// the protogalaxy relation
+impl<G: Group> PLONKShape<G> {
+    /// the gate of every row with u = 1
+    fn gates(
+        &self,
+        W: &[Vec<<G as Group>::ScalarField>],
+        X: &[<G as Group>::ScalarField],
+    ) -> Vec<<G as Group>::ScalarField> {
+        let one = <G as Group>::ScalarField::ONE;
+        (0..self.num_cons)
+            .into_par_iter()
+            .map(|row| self.gate.evaluate(&self.selectors, row, &one, W, X))
+            .collect()
+    }
+
+    /// Checks if the accumulated instance is satisfiable given a witness and its shape,
+    /// i.e. e = Σ_i pow_i(β) * gate_i and the wiring holds
+    pub fn is_sat_accumulated(
+        &self,
+        ck: &CommitmentKey<G>,
+        U: &AccumulatedPLONKInstance<G>,
+        W: &AccumulatedPLONKWitness<G>,
+    ) -> Result<(), MyError> {
+        if W.W.len() != self.num_wire_types || W.W.iter().any(|w| w.len() != self.num_cons) {
+            return Err(MyError::WitnessError);
+        }
+        if U.X.len() != self.num_public_input {
+            return Err(MyError::PublicIntputError);
+        }
+        if 1 << U.beta.len() != self.num_cons {
+            return Err(MyError::InvalidBetaLength);
+        }
+
+        let e = pow_vector(&U.beta)
+            .par_iter()
+            .zip(self.gates(&W.W, &U.X))
+            .map(|(p, g)| *p * g)
+            .sum::<<G as Group>::ScalarField>();
+        if e != U.e {
+            return Err(MyError::UnSatCompressed);
+        }
+
+        if let Some((wire, row)) = self.copy_violation(&W.W) {
+            return Err(MyError::UnSatCopy(wire, row));
+        }
+
+        let comm_W =
+            W.W.iter()
+                .map(|w| G::CE::commit(ck, w.as_slice()))
+                .collect::<Vec<_>>();
+        if U.comm_W != comm_W {
+            return Err(MyError::CommitmentError);
+        }
+
+        Ok(())
+    }
+}
+
+impl<G: Group> MultiNIFS<G> {
+    /// a challenge bound to both sides of the fold and to the messages sent so far
+    fn challenge(
+        pp_digest: &<G as Group>::ScalarField,
+        U: &AccumulatedPLONKInstance<G>,
+        us: &[PLONKInstance<G>],
+        messages: &[&[<G as Group>::ScalarField]],
+    ) -> <G as Group>::ScalarField {
+        let mut ro = <<G as Group>::RO as ROTrait<
+            <G as Group>::BaseField,
+            <G as Group>::ScalarField,
+        >>::new(ROConstants::<G>::new(3));
+        scalar_as_limbs::<G>(pp_digest)
+            .into_iter()
+            .for_each(|limb| ro.absorb(limb));
+        U.absorb_in_ro(&mut ro);
+        us.iter().for_each(|u| u.absorb_in_ro(&mut ro));
+        messages
+            .iter()
+            .flat_map(|m| m.iter())
+            .flat_map(scalar_as_limbs::<G>)
+            .for_each(|limb| ro.absorb(limb));
+        ro.squeeze(NUM_CHALLENGE_BITS)
+    }
+
+    /// folds k PLONK instance-witness pairs into the accumulated one at once,
+    /// returning the proof together with the folded pair
+    #[allow(clippy::type_complexity)]
+    pub fn prove(
+        pp_digest: &<G as Group>::ScalarField,
+        S: &PLONKShape<G>,
+        U: &AccumulatedPLONKInstance<G>,
+        W: &AccumulatedPLONKWitness<G>,
+        us: &[PLONKInstance<G>],
+        ws: &[PLONKWitness<G>],
+    ) -> Result<
+        (
+            MultiNIFS<G>,
+            (AccumulatedPLONKInstance<G>, AccumulatedPLONKWitness<G>),
+        ),
+        MyError,
+    > {
+        if us.is_empty() || us.len() != ws.len() {
+            return Err(MyError::WitnessError);
+        }
+        if !S.num_cons.is_power_of_two() || 1 << U.beta.len() != S.num_cons {
+            return Err(MyError::InvalidBetaLength);
+        }
+        let k = us.len();
+        let t = U.beta.len();
+
+        // F(X) = Σ_i pow_i(β + X * δ) * gate_i(U), folding the rows pairwise along the bits of i
+        let delta = Self::challenge(pp_digest, U, us, &[]);
+        let deltas = squares(&delta, t);
+        let F_poly = U
+            .beta
+            .iter()
+            .zip(&deltas)
+            .fold(
+                S.gates(&W.W, &U.X)
+                    .into_iter()
+                    .map(|g| vec![g])
+                    .collect::<Vec<_>>(),
+                |level, (b, d)| {
+                    level
+                        .chunks(2)
+                        .map(|pair| {
+                            let mut next = pair[0].clone();
+                            next.resize(pair[1].len() + 1, <G as Group>::ScalarField::ZERO);
+                            pair[1].iter().enumerate().for_each(|(i, c)| {
+                                next[i] += *b * c;
+                                next[i + 1] += *d * c;
+                            });
+                            next
+                        })
+                        .collect()
+                },
+            )
+            .remove(0);
+        let F_coeffs = F_poly[1..].to_vec();
+
+        // G(X) = Σ_i pow_i(β*) * gate_i(Σ_j L_j(X) * ω_j) vanishes on 1, ..., k, and takes F(α) on 0
+        let alpha = Self::challenge(pp_digest, U, us, &[&F_coeffs]);
+        let F_alpha = evaluate(&F_poly, &alpha);
+        let beta_star = U
+            .beta
+            .iter()
+            .zip(&deltas)
+            .map(|(b, d)| *b + alpha * d)
+            .collect::<Vec<_>>();
+        let pows = pow_vector(&beta_star);
+
+        let Ws = std::iter::once(&W.W)
+            .chain(ws.iter().map(|w| &w.W))
+            .collect::<Vec<_>>();
+        let Xs = std::iter::once(&U.X)
+            .chain(us.iter().map(|u| &u.X))
+            .collect::<Vec<_>>();
+        let num_points = S.gate.degree * k + 1;
+        let G_evals = (0..num_points)
+            .map(|x| {
+                let L = lagrange_evals(k + 1, &<G as Group>::ScalarField::from(x as u64));
+                let W_x = (0..S.num_wire_types)
+                    .map(|col| {
+                        (0..S.num_cons)
+                            .map(|row| L.iter().zip(&Ws).map(|(l, w)| *l * w[col][row]).sum())
+                            .collect::<Vec<_>>()
+                    })
+                    .collect::<Vec<_>>();
+                let X_x = (0..U.X.len())
+                    .map(|i| L.iter().zip(&Xs).map(|(l, X)| *l * X[i]).sum())
+                    .collect::<Vec<_>>();
+                pows.par_iter()
+                    .zip(S.gates(&W_x, &X_x))
+                    .map(|(p, g)| *p * g)
+                    .sum::<<G as Group>::ScalarField>()
+            })
+            .collect::<Vec<_>>();
+
+        // K(X) = (G(X) - F(α) * L_0(X)) / Z(X), with Z(X) = prod_{j = 0}^{k} (X - j)
+        let L_0 = PLONKShape::<G>::interpolate(
+            &(0..num_points)
+                .map(|x| lagrange_evals(k + 1, &<G as Group>::ScalarField::from(x as u64))[0])
+                .collect::<Vec<_>>(),
+        );
+        let numerator = PLONKShape::<G>::interpolate(&G_evals)
+            .iter()
+            .zip(&L_0)
+            .map(|(g, l)| *g - F_alpha * l)
+            .collect::<Vec<_>>();
+        let neg_points = (0..=k)
+            .map(|j| -<G as Group>::ScalarField::from(j as u64))
+            .collect::<Vec<_>>();
+        let one = <G as Group>::ScalarField::ONE;
+        let Z = PLONKShape::<G>::expand_product(
+            &neg_points.iter().map(|p| (p, &one)).collect::<Vec<_>>(),
+        );
+        let K_coeffs = divide_by_monic(&numerator, &Z);
+
+        let nifs = MultiNIFS { F_coeffs, K_coeffs };
+        let gamma = Self::challenge(pp_digest, U, us, &[&nifs.F_coeffs, &nifs.K_coeffs]);
+        let L = lagrange_evals(k + 1, &gamma);
+        let folded_U = nifs.fold(U, us, &beta_star, &F_alpha, &gamma)?;
+        let folded_W = AccumulatedPLONKWitness {
+            W: (0..S.num_wire_types)
+                .map(|col| {
+                    (0..S.num_cons)
+                        .into_par_iter()
+                        .map(|row| L.iter().zip(&Ws).map(|(l, w)| *l * w[col][row]).sum())
+                        .collect()
+                })
+                .collect(),
+        };
+
+        Ok((nifs, (folded_U, folded_W)))
+    }
+
+    /// the folded instance, ω* = Σ_j L_j(γ) * ω_j, β* = β + α * δ and e* = F(α) * L_0(γ) + Z(γ) * K(γ)
+    fn fold(
+        &self,
+        U: &AccumulatedPLONKInstance<G>,
+        us: &[PLONKInstance<G>],
+        beta_star: &[<G as Group>::ScalarField],
+        F_alpha: &<G as Group>::ScalarField,
+        gamma: &<G as Group>::ScalarField,
+    ) -> Result<AccumulatedPLONKInstance<G>, MyError> {
+        if us.iter().any(|u| u.X.len() != U.X.len()) {
+            return Err(MyError::PublicIntputError);
+        }
+        if us.iter().any(|u| u.comm_W.len() != U.comm_W.len()) {
+            return Err(MyError::CommitmentError);
+        }
+        let k = us.len();
+        let L = lagrange_evals(k + 1, gamma);
+
+        let comm_W = (0..U.comm_W.len())
+            .map(|col| {
+                us.iter()
+                    .zip(&L[1..])
+                    .fold(U.comm_W[col].clone() * L[0], |acc, (u, l)| {
+                        acc + u.comm_W[col].clone() * *l
+                    })
+            })
+            .collect();
+        let X = (0..U.X.len())
+            .map(|i| {
+                us.iter()
+                    .zip(&L[1..])
+                    .fold(U.X[i] * L[0], |acc, (u, l)| acc + u.X[i] * l)
+            })
+            .collect();
+        let Z_gamma = (0..=k).fold(<G as Group>::ScalarField::ONE, |acc, j| {
+            acc * (*gamma - <G as Group>::ScalarField::from(j as u64))
+        });
+        let e = *F_alpha * L[0] + Z_gamma * evaluate(&self.K_coeffs, gamma);
+
+        Ok(AccumulatedPLONKInstance {
+            comm_W,
+            X,
+            beta: beta_star.to_vec(),
+            e,
+        })
+    }
+
+    /// recomputes the challenges from the same transcript and folds the instances only,
+    /// K(X) must be of degree d * k - k - 1 for the gate degree d of the shape, which the fold is sound for
+    pub fn verify(
+        &self,
+        pp_digest: &<G as Group>::ScalarField,
+        S: &PLONKShape<G>,
+        U: &AccumulatedPLONKInstance<G>,
+        us: &[PLONKInstance<G>],
+    ) -> Result<AccumulatedPLONKInstance<G>, MyError> {
+        let k = us.len();
+        if k == 0
+            || self.F_coeffs.len() != U.beta.len()
+            || self.K_coeffs.len() != S.gate.degree * k - k
+        {
+            return Err(MyError::ProofVerifyError);
+        }
+        let delta = Self::challenge(pp_digest, U, us, &[]);
+        let alpha = Self::challenge(pp_digest, U, us, &[&self.F_coeffs]);
+        let gamma = Self::challenge(pp_digest, U, us, &[&self.F_coeffs, &self.K_coeffs]);
+
+        let F_alpha = U.e + alpha * evaluate(&self.F_coeffs, &alpha);
+        let beta_star = U
+            .beta
+            .iter()
+            .zip(squares(&delta, U.beta.len()))
+            .map(|(b, d)| *b + alpha * d)
+            .collect::<Vec<_>>();
+        self.fold(U, us, &beta_star, &F_alpha, &gamma)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        nifs::tests::{random_shape, random_witness},
+        provider::grumpkin::Grumpkin,
+    };
+    use ark_bn254::Bn254;
+    use ark_ff::UniformRand;
+    use jf_utils::test_rng;
+
+    fn test_multi_nifs_fold_with<G: Group>() {
+        let rng = &mut test_rng();
+        let (num_cons, num_public_input) = (8, 2);
+        let ck = G::CE::setup(rng, num_cons);
+        let pp_digest = <G as Group>::ScalarField::rand(rng);
+
+        let S = random_shape::<G>(rng, num_cons, num_public_input);
+
+        let mut running_U = AccumulatedPLONKInstance::default(&ck, &S);
+        let mut running_W = AccumulatedPLONKWitness::default(&S);
+        S.is_sat_accumulated(&ck, &running_U, &running_W).unwrap();
+        for k in [1, 2, 3] {
+            let (ws, us): (Vec<_>, Vec<_>) = (0..k)
+                .map(|_| {
+                    let (W, X) = random_witness(rng, &S);
+                    let U = PLONKInstance::new(&S, &W.commit(&ck), &X).unwrap();
+                    (W, U)
+                })
+                .unzip();
+            let (nifs, (folded_U, folded_W)) =
+                MultiNIFS::prove(&pp_digest, &S, &running_U, &running_W, &us, &ws).unwrap();
+            // F(X) has the degree of log(num_cons), K(X) that of d * k - k - 1
+            assert_eq!(nifs.F_coeffs.len(), 3);
+            assert_eq!(nifs.K_coeffs.len(), S.gate.degree * k - k);
+            let verified_U = nifs.verify(&pp_digest, &S, &running_U, &us).unwrap();
+            assert_eq!(verified_U, folded_U);
+
+            // a proof of K(X) of another degree is rejected
+            let mut padded = nifs.clone();
+            padded.K_coeffs.push(<G as Group>::ScalarField::ZERO);
+            assert_eq!(
+                padded.verify(&pp_digest, &S, &running_U, &us),
+                Err(MyError::ProofVerifyError)
+            );
+
+            S.is_sat_accumulated(&ck, &folded_U, &folded_W).unwrap();
+            running_U = folded_U;
+            running_W = folded_W;
+        }
+
+        // a tampered output wire breaks the accumulated error
+        let mut tampered_W = running_W.clone();
+        tampered_W.W[4][3] += <<G as Group>::ScalarField as Field>::ONE;
+        assert_eq!(
+            S.is_sat_accumulated(&ck, &running_U, &tampered_W),
+            Err(MyError::UnSatCompressed)
+        );
+
+        // β of another length than log(num_cons)
+        let mut short_U = running_U.clone();
+        short_U.beta.pop();
+        assert_eq!(
+            S.is_sat_accumulated(&ck, &short_U, &running_W),
+            Err(MyError::InvalidBetaLength)
+        );
+
+        // an unsatisfied incoming instance among others leaves an unsatisfied accumulator
+        let (ws, us): (Vec<_>, Vec<_>) = (0..2)
+            .map(|i| {
+                let (mut W, X) = random_witness(rng, &S);
+                if i == 1 {
+                    W.W[4][0] += <<G as Group>::ScalarField as Field>::ONE;
+                }
+                let U = PLONKInstance::new(&S, &W.commit(&ck), &X).unwrap();
+                (W, U)
+            })
+            .unzip();
+        let (_, (folded_U, folded_W)) =
+            MultiNIFS::prove(&pp_digest, &S, &running_U, &running_W, &us, &ws).unwrap();
+        assert_eq!(
+            S.is_sat_accumulated(&ck, &folded_U, &folded_W),
+            Err(MyError::UnSatCompressed)
+        );
+    }
+
+    #[test]
+    fn test_multi_nifs_fold() {
+        test_multi_nifs_fold_with::<Bn254>();
+        test_multi_nifs_fold_with::<Grumpkin>();
+    }
+}
@@ -1,7 +1,12 @@
+use crate::circuit::CircuitGroup;
 use crate::poseidon::poseidon_constants::{PoseidonDefaultConfig, PoseidonDefaultConfigEntry};
-use crate::provider::{kzg::CommitmentEngine, poseidon::PoseidonRO};
-use crate::traits::Group;
-use ark_bn254::{Bn254, Fq, FqConfig, Fr, G1Affine};
+use crate::provider::{
+    keccak::Keccak256Transcript,
+    kzg::CommitmentEngine,
+    poseidon::{PoseidonRO, PoseidonROCircuit},
+};
+use crate::traits::{Group, PairingGroup};
+use ark_bn254::{Bn254, Fq, FqConfig, Fr, G1Affine, G1Projective};
 use ark_ff::fields::MontBackend;
 
 impl PoseidonDefaultConfig<4> for MontBackend<FqConfig, 4> {
@@ -28,7 +33,17 @@ impl PoseidonDefaultConfig<4> for MontBackend<FqConfig, 4> {
 impl Group for Bn254 {
     type BaseField = Fq;
     type ScalarField = Fr;
+    type Curve = G1Projective;
     type PreprocessedGroupElement = G1Affine;
     type RO = PoseidonRO<Fq, Fr>;
     type CE = CommitmentEngine<Self>;
+    type TE = Keccak256Transcript<Self>;
+}
+
+impl CircuitGroup for Bn254 {
+    type ROCircuit = PoseidonROCircuit<Fq>;
+}
+
+impl PairingGroup for Bn254 {
+    type Pairing = Self;
 }
@@ -0,0 +1,29 @@
+use crate::circuit::CircuitGroup;
+use crate::provider::{
+    keccak::Keccak256Transcript,
+    poseidon::{PoseidonRO, PoseidonROCircuit},
+};
+use crate::secondary::{
+    grumpkin_curve::{Affine, Projective},
+    grumpkin_field::{Fq, Fr},
+    ipa::IPACommitmentEngine,
+};
+use crate::traits::Group;
+
+/// marker of the grumpkin curve, which has no pairing, counterpart of `ark_bn254::Bn254`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Grumpkin;
+
+impl Group for Grumpkin {
+    type BaseField = Fq;
+    type ScalarField = Fr;
+    type Curve = Projective;
+    type PreprocessedGroupElement = Affine;
+    type RO = PoseidonRO<Fq, Fr>;
+    type CE = IPACommitmentEngine<Self>;
+    type TE = Keccak256Transcript<Self>;
+}
+
+impl CircuitGroup for Grumpkin {
+    type ROCircuit = PoseidonROCircuit<Fq>;
+}
@@ -0,0 +1,171 @@
+//! This module provides an implementation of TranscriptEngineTrait using keccak256
+use crate::error::MyError;
+use crate::traits::{Group, TranscriptEngineTrait, TranscriptReprTrait};
+use ark_ff::PrimeField;
+use core::marker::PhantomData;
+use sha3::{Digest, Keccak256};
+
+const PERSONA_TAG: &[u8] = b"PNTR";
+const DOM_SEP_TAG: &[u8] = b"PNDS";
+const KECCAK256_STATE_SIZE: usize = 64;
+const KECCAK256_PREFIX_CHALLENGE_LO: u8 = 0;
+const KECCAK256_PREFIX_CHALLENGE_HI: u8 = 1;
+
+/// Provides an implementation of TranscriptEngine
+#[derive(Debug, Clone)]
+pub struct Keccak256Transcript<G: Group> {
+    round: u16,
+    state: [u8; KECCAK256_STATE_SIZE],
+    transcript: Keccak256,
+    _p: PhantomData<G>,
+}
+
+/// extends the 32-byte keccak output to a 64-byte state, by hashing twice with different prefixes
+fn compute_updated_state(keccak_instance: Keccak256, input: &[u8]) -> [u8; KECCAK256_STATE_SIZE] {
+    let mut updated_instance = keccak_instance;
+    updated_instance.update(input);
+
+    let mut hasher_lo = updated_instance.clone();
+    let mut hasher_hi = updated_instance;
+    hasher_lo.update([KECCAK256_PREFIX_CHALLENGE_LO]);
+    hasher_hi.update([KECCAK256_PREFIX_CHALLENGE_HI]);
+
+    let output_lo = hasher_lo.finalize();
+    let output_hi = hasher_hi.finalize();
+
+    [output_lo, output_hi]
+        .concat()
+        .as_slice()
+        .try_into()
+        .unwrap()
+}
+
+impl<G: Group> TranscriptEngineTrait<G> for Keccak256Transcript<G> {
+    fn new(label: &'static [u8]) -> Self {
+        let keccak_instance = Keccak256::new();
+        let input = [PERSONA_TAG, label].concat();
+        let output = compute_updated_state(keccak_instance.clone(), &input);
+
+        Self {
+            round: 0u16,
+            state: output,
+            transcript: keccak_instance,
+            _p: PhantomData,
+        }
+    }
+
+    fn squeeze(&mut self, label: &'static [u8]) -> Result<<G as Group>::ScalarField, MyError> {
+        // we gather the full input from the round, preceded by the current state of the transcript
+        let input = [
+            DOM_SEP_TAG,
+            self.round.to_le_bytes().as_ref(),
+            self.state.as_ref(),
+            label,
+        ]
+        .concat();
+        let output = compute_updated_state(self.transcript.clone(), &input);
+
+        // update state
+        self.round = self.round.checked_add(1).ok_or(MyError::KeccakError)?;
+        self.state.copy_from_slice(&output);
+        self.transcript = Keccak256::new();
+
+        // 64 bytes reduced modulo the scalar field, so that the challenge is close to uniform
+        Ok(<G as Group>::ScalarField::from_le_bytes_mod_order(&output))
+    }
+
+    fn absorb<T: TranscriptReprTrait<G>>(&mut self, label: &'static [u8], o: &T) {
+        self.transcript.update(label);
+        self.transcript.update(o.to_transcript_bytes());
+    }
+
+    fn dom_sep(&mut self, bytes: &'static [u8]) {
+        self.transcript.update(DOM_SEP_TAG);
+        self.transcript.update(bytes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{provider::kzg::KZGCommitment, traits::PairingGroup};
+    use ark_bn254::{Bn254, Fq, G1Affine};
+    use ark_ec::AffineRepr;
+    use core::str::FromStr;
+    use jf_primitives::pcs::prelude::Commitment;
+
+    fn test_keccak_transcript_with<G: PairingGroup>(expected_c1: &str, expected_c2: &str) {
+        let mut transcript: Keccak256Transcript<G> = Keccak256Transcript::new(b"test");
+
+        // two scalars and a base-field element
+        transcript.absorb(b"s1", &<G as Group>::ScalarField::from(2u64));
+        transcript.absorb(b"s2", &<G as Group>::ScalarField::from(5u64));
+        transcript.absorb(b"b1", &<G as Group>::BaseField::from(7u64));
+        let c1 = transcript.squeeze(b"c1").unwrap();
+        assert_eq!(
+            c1,
+            <G as Group>::ScalarField::from_str(expected_c1)
+                .ok()
+                .unwrap()
+        );
+
+        // a commitment under a new domain
+        transcript.dom_sep(b"commitment");
+        transcript.absorb(b"comm", &KZGCommitment::<G>::default());
+        let c2 = transcript.squeeze(b"c2").unwrap();
+        assert_eq!(
+            c2,
+            <G as Group>::ScalarField::from_str(expected_c2)
+                .ok()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_keccak_transcript() {
+        test_keccak_transcript_with::<Bn254>(
+            "12286681024262551770398905054414614225859816670252880198288511737611907555974",
+            "20440683264489874879015304188523168450140689717920458260646111033564530454749",
+        );
+    }
+
+    fn to_hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{b:02x}")).collect()
+    }
+
+    #[test]
+    fn test_keccak_state_vectors() {
+        // the legacy keccak of ethereum, not the padding of NIST SHA3-256
+        assert_eq!(
+            to_hex(&Keccak256::digest(b"")),
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+        // the state of an empty input is keccak256(0x00) || keccak256(0x01)
+        let state = compute_updated_state(Keccak256::new(), b"");
+        assert_eq!(
+            to_hex(&state[..32]),
+            "bc36789e7a1e281436464229828f817d6612f7b477d66591ff96a9e064bcc98a"
+        );
+        assert_eq!(
+            to_hex(&state[32..]),
+            "5fe7f977e71dba2ea1a68e21057beebb9be2ac30c6410aa38d4f3fbe41dcffd2"
+        );
+    }
+
+    #[test]
+    fn test_keccak_commitment_repr() {
+        // a commitment is absorbed as its coordinates followed by the infinity flag
+        let g = G1Affine::generator();
+        let (x, y) = g.xy().unwrap();
+        let comm = KZGCommitment::<Bn254>(Commitment(g));
+        assert_eq!(
+            <KZGCommitment<Bn254> as TranscriptReprTrait<Bn254>>::to_transcript_bytes(&comm),
+            [
+                <Fq as TranscriptReprTrait<Bn254>>::to_transcript_bytes(x),
+                <Fq as TranscriptReprTrait<Bn254>>::to_transcript_bytes(y),
+                vec![0u8],
+            ]
+            .concat()
+        );
+    }
+}
@@ -1,10 +1,16 @@
+use core::ops::{Add, Mul};
 use rand::rngs::StdRng;
 use std::marker::PhantomData;
 
-use crate::traits::CommitmentEngineTrait;
+use crate::error::MyError;
+use crate::to_coordinates;
+use crate::traits::{
+    AbsorbInROTrait, CommitmentEngineTrait, CommitmentTrait, EvaluationEngineTrait, PairingGroup,
+    ROTrait, TranscriptEngineTrait, TranscriptReprTrait,
+};
 
-use ark_ec::{pairing::Pairing, scalar_mul::fixed_base::FixedBase, CurveGroup};
-use ark_ff::{Field, PrimeField};
+use ark_ec::{pairing::Pairing, scalar_mul::fixed_base::FixedBase, AffineRepr, CurveGroup};
+use ark_ff::{BigInteger, Field, PrimeField};
 use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial};
 use ark_std::{
     end_timer,
@@ -14,7 +20,10 @@ use ark_std::{
 
 use jf_primitives::pcs::prelude::Commitment;
 use jf_primitives::pcs::{
-    prelude::{PCSError, UnivariateKzgPCS, UnivariateProverParam, UnivariateUniversalParams},
+    prelude::{
+        PCSError, UnivariateKzgPCS, UnivariateKzgProof, UnivariateProverParam,
+        UnivariateUniversalParams, UnivariateVerifierParam,
+    },
     PolynomialCommitmentScheme, StructuredReferenceString,
 };
 
@@ -79,31 +88,233 @@ pub struct CommitmentEngine<G: Group> {
     _p: PhantomData<G>,
 }
 
+/// the powers of g, together with the verifier parameters of the same trapdoor for the evaluation engine
 #[derive(Clone, Debug, PartialEq, Eq)]
-pub struct KZGCommitmentKey<G: Pairing>(UnivariateProverParam<G>);
+pub struct KZGCommitmentKey<G: PairingGroup> {
+    pub(crate) ck: UnivariateProverParam<G::Pairing>,
+    pub(crate) vk: UnivariateVerifierParam<G::Pairing>,
+}
 
 #[derive(Clone, Debug, PartialEq, Eq)]
-pub struct KZGCommitment<G: Pairing>(Commitment<G>);
+pub struct KZGCommitment<G: PairingGroup>(pub(crate) Commitment<G::Pairing>);
+
+impl<G: PairingGroup> Default for KZGCommitment<G> {
+    fn default() -> Self {
+        KZGCommitment(Commitment(<G::Pairing as Pairing>::G1Affine::zero()))
+    }
+}
+
+impl<G: PairingGroup> Add for KZGCommitment<G> {
+    type Output = KZGCommitment<G>;
 
-// impl<G> CommitmentTrait<G> for KZGCommitment<G> where G: Group {}
+    fn add(self, other: KZGCommitment<G>) -> KZGCommitment<G> {
+        KZGCommitment(Commitment((self.0 .0 + other.0 .0).into_affine()))
+    }
+}
+
+impl<G: PairingGroup> Mul<<G as Group>::ScalarField> for KZGCommitment<G> {
+    type Output = KZGCommitment<G>;
+
+    fn mul(self, scalar: <G as Group>::ScalarField) -> KZGCommitment<G> {
+        KZGCommitment(Commitment((self.0 .0 * scalar).into_affine()))
+    }
+}
+
+/// absorb the affine coordinates together with the infinity flag
+impl<G: PairingGroup> AbsorbInROTrait<G> for KZGCommitment<G> {
+    fn absorb_in_ro(&self, ro: &mut G::RO) {
+        let (x, y, is_infinity) = to_coordinates::<G>(&self.0 .0);
+        ro.absorb(x);
+        ro.absorb(y);
+        ro.absorb(if is_infinity {
+            <G as Group>::BaseField::ONE
+        } else {
+            <G as Group>::BaseField::ZERO
+        });
+    }
+}
 
-impl<G> CommitmentEngineTrait<G> for CommitmentEngine<G>
-where
-    G: Group,
-{
+/// the affine coordinates followed by the infinity flag, same as what goes into the RO
+impl<G: PairingGroup> TranscriptReprTrait<G> for KZGCommitment<G> {
+    fn to_transcript_bytes(&self) -> Vec<u8> {
+        let (x, y, is_infinity) = to_coordinates::<G>(&self.0 .0);
+        [
+            x.into_bigint().to_bytes_le(),
+            y.into_bigint().to_bytes_le(),
+            vec![is_infinity as u8],
+        ]
+        .concat()
+    }
+}
+
+impl<G: PairingGroup> CommitmentTrait<G> for KZGCommitment<G> {
+    fn to_coordinates(&self) -> (G::BaseField, G::BaseField, bool) {
+        to_coordinates::<G>(&self.0 .0)
+    }
+}
+
+impl<G: PairingGroup> CommitmentEngineTrait<G> for CommitmentEngine<G> {
     type CommitmentKey = KZGCommitmentKey<G>;
     type Commitment = KZGCommitment<G>;
 
     fn setup(rng: &mut StdRng, degree: usize) -> Self::CommitmentKey {
-        let pp: UnivariateUniversalParams<G> = gen_srs_for_testing(rng, degree, 1).unwrap();
-        let (ck, _) = pp.trim(degree).unwrap();
-        KZGCommitmentKey(ck)
+        let pp: UnivariateUniversalParams<G::Pairing> =
+            gen_srs_for_testing(rng, degree, 1).unwrap();
+        let (ck, vk) = pp.trim(degree).unwrap();
+        KZGCommitmentKey { ck, vk }
     }
 
-    fn commit(ck: &Self::CommitmentKey, v: &[<G as Pairing>::ScalarField]) -> Self::Commitment {
-        let poly = <DensePolynomial<<G as Pairing>::ScalarField> as DenseUVPolynomial<
-            <G as Pairing>::ScalarField,
+    fn commit(ck: &Self::CommitmentKey, v: &[<G as Group>::ScalarField]) -> Self::Commitment {
+        let poly = <DensePolynomial<<G as Group>::ScalarField> as DenseUVPolynomial<
+            <G as Group>::ScalarField,
         >>::from_coefficients_vec(v.to_vec());
-        KZGCommitment(UnivariateKzgPCS::<G>::commit(&ck.0, &poly).unwrap())
+        KZGCommitment(UnivariateKzgPCS::<G::Pairing>::commit(&ck.ck, &poly).unwrap())
+    }
+}
+
+/// KZG openings of committed vectors read as the coefficients of a univariate polynomial
+#[derive(Clone)]
+pub struct KZGEvaluationEngine<G: PairingGroup> {
+    _p: PhantomData<G>,
+}
+
+/// the prover opens with the powers of g in the commitment key alone
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KZGProverKey<G: PairingGroup> {
+    _p: PhantomData<G>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KZGVerifierKey<G: PairingGroup> {
+    vk: UnivariateVerifierParam<G::Pairing>,
+}
+
+/// the commitment to the quotient (p(X) - p(x)) / (X - x)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KZGEvaluationArgument<G: PairingGroup> {
+    proof: UnivariateKzgProof<G::Pairing>,
+}
+
+impl<G: PairingGroup> KZGEvaluationEngine<G> {
+    /// binds the claim into the transcript, so that it runs alongside the other evaluation engines
+    fn bind_claim(
+        transcript: &mut G::TE,
+        comm: &KZGCommitment<G>,
+        point: &<G as Group>::ScalarField,
+        eval: &<G as Group>::ScalarField,
+    ) {
+        transcript.dom_sep(b"KZG");
+        transcript.absorb(b"C", comm);
+        transcript.absorb(b"x", point);
+        transcript.absorb(b"y", eval);
+    }
+}
+
+impl<G: PairingGroup> EvaluationEngineTrait<G> for KZGEvaluationEngine<G> {
+    type CE = CommitmentEngine<G>;
+    type ProverKey = KZGProverKey<G>;
+    type VerifierKey = KZGVerifierKey<G>;
+    type EvaluationArgument = KZGEvaluationArgument<G>;
+
+    fn setup(ck: &KZGCommitmentKey<G>) -> (Self::ProverKey, Self::VerifierKey) {
+        (
+            KZGProverKey { _p: PhantomData },
+            KZGVerifierKey { vk: ck.vk },
+        )
+    }
+
+    fn prove(
+        ck: &KZGCommitmentKey<G>,
+        _pk: &Self::ProverKey,
+        transcript: &mut G::TE,
+        comm: &KZGCommitment<G>,
+        poly: &[<G as Group>::ScalarField],
+        point: &<G as Group>::ScalarField,
+        eval: &<G as Group>::ScalarField,
+    ) -> Result<Self::EvaluationArgument, MyError> {
+        if poly.len() > ck.ck.powers_of_g.len() {
+            return Err(MyError::InvalidInputLength);
+        }
+        Self::bind_claim(transcript, comm, point, eval);
+
+        let poly = <DensePolynomial<<G as Group>::ScalarField> as DenseUVPolynomial<
+            <G as Group>::ScalarField,
+        >>::from_coefficients_vec(poly.to_vec());
+        let (proof, value) = UnivariateKzgPCS::<G::Pairing>::open(&ck.ck, &poly, point)
+            .map_err(|_| MyError::InvalidPCS)?;
+        if value != *eval {
+            return Err(MyError::InvalidPCS);
+        }
+
+        Ok(KZGEvaluationArgument { proof })
+    }
+
+    fn verify(
+        vk: &Self::VerifierKey,
+        transcript: &mut G::TE,
+        comm: &KZGCommitment<G>,
+        point: &<G as Group>::ScalarField,
+        eval: &<G as Group>::ScalarField,
+        arg: &Self::EvaluationArgument,
+    ) -> Result<(), MyError> {
+        Self::bind_claim(transcript, comm, point, eval);
+
+        match UnivariateKzgPCS::<G::Pairing>::verify(&vk.vk, &comm.0, point, eval, &arg.proof) {
+            Ok(true) => Ok(()),
+            _ => Err(MyError::InvalidPCS),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::keccak::Keccak256Transcript;
+    use ark_bn254::Bn254;
+    use ark_ff::UniformRand;
+    use jf_utils::test_rng;
+
+    fn test_kzg_evaluation_with<G: PairingGroup<TE = Keccak256Transcript<G>>>() {
+        let rng = &mut test_rng();
+        let degree = 6;
+        let ck = CommitmentEngine::<G>::setup(rng, degree);
+        let (pk, vk) = KZGEvaluationEngine::<G>::setup(&ck);
+
+        let poly = (0..degree + 1)
+            .map(|_| <G as Group>::ScalarField::rand(rng))
+            .collect::<Vec<_>>();
+        let point = <G as Group>::ScalarField::rand(rng);
+        let eval = poly
+            .iter()
+            .rev()
+            .fold(<G as Group>::ScalarField::ZERO, |acc, c| acc * point + c);
+        let comm = CommitmentEngine::<G>::commit(&ck, &poly);
+
+        let mut transcript = Keccak256Transcript::<G>::new(b"test");
+        let arg =
+            KZGEvaluationEngine::<G>::prove(&ck, &pk, &mut transcript, &comm, &poly, &point, &eval)
+                .unwrap();
+
+        let mut transcript = Keccak256Transcript::<G>::new(b"test");
+        KZGEvaluationEngine::<G>::verify(&vk, &mut transcript, &comm, &point, &eval, &arg).unwrap();
+
+        // a wrong evaluation is rejected
+        let mut transcript = Keccak256Transcript::<G>::new(b"test");
+        assert_eq!(
+            KZGEvaluationEngine::<G>::verify(
+                &vk,
+                &mut transcript,
+                &comm,
+                &point,
+                &(eval + <G as Group>::ScalarField::ONE),
+                &arg
+            ),
+            Err(MyError::InvalidPCS)
+        );
+    }
+
+    #[test]
+    fn test_kzg_evaluation() {
+        test_kzg_evaluation_with::<Bn254>();
     }
 }
@@ -1,4 +1,18 @@
 pub mod bn254;
+pub mod grumpkin;
 pub mod keccak;
 pub mod kzg;
 pub mod poseidon;
+
+use crate::traits::CurveCycle;
+use ark_bn254::Bn254;
+use grumpkin::Grumpkin;
+
+/// BN254 with KZG on the primary side, Grumpkin with IPA on the secondary side
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Bn254Grumpkin;
+
+impl CurveCycle for Bn254Grumpkin {
+    type G1 = Bn254;
+    type G2 = Grumpkin;
+}
@@ -1,9 +1,12 @@
 use ark_crypto_primitives::sponge::poseidon::{PoseidonConfig, PoseidonSponge};
-use ark_crypto_primitives::sponge::{Absorb, CryptographicSponge};
-use ark_ff::PrimeField;
+use ark_crypto_primitives::sponge::{Absorb, CryptographicSponge, FieldBasedCryptographicSponge};
+use ark_ff::{BigInteger, PrimeField};
+use jf_relation::{constants::GATE_WIDTH, Variable};
 
 use std::marker::PhantomData;
 
+use crate::circuit::{PlonkishCircuit, ROCircuitTrait};
+use crate::error::MyError;
 use crate::poseidon::poseidon_constants::PoseidonDefaultConfigField;
 use crate::traits::{ROConstantsTrait, ROTrait};
 
@@ -47,7 +50,166 @@ where
         self.ro.absorb(&e);
     }
 
-    fn squeeze(&mut self) -> ScalarField {
-        self.ro.squeeze_field_elements::<ScalarField>(1 as usize)[0]
+    fn squeeze(&mut self, num_bits: usize) -> ScalarField {
+        // keep the low `num_bits` bits of a native squeeze, so that the challenge fits into both fields of the cycle
+        let e = self.ro.squeeze_native_field_elements(1)[0];
+        let bits = e.into_bigint().to_bits_le();
+        ScalarField::from_bigint(<ScalarField as PrimeField>::BigInt::from_bits_le(
+            &bits[..num_bits],
+        ))
+        .unwrap()
+    }
+}
+
+/// the in-circuit counterpart of `PoseidonRO`, running the same duplex sponge over circuit variables
+///
+/// absorbed variables are buffered and the sponge runs on squeeze, one permutation per `rate` of them,
+/// which matches the lazy permutations of the native sponge for a single squeeze
+///
+/// the state is kept with the round constants of the next round already added, so that every round takes
+/// a single q_hash gate per element of the state, which therefore has to be `GATE_WIDTH` wide
+pub struct PoseidonROCircuit<BaseField: PrimeField> {
+    constants: PoseidonConstants<BaseField>,
+    absorbed: Vec<Variable>,
+}
+
+impl<BaseField: PrimeField> PoseidonROCircuit<BaseField> {
+    /// the permutation of a state with the constants of the first round added, i.e. ark + s-box + mds per round
+    fn permute(
+        &self,
+        cs: &mut PlonkishCircuit<BaseField>,
+        state: &[Variable; GATE_WIDTH],
+    ) -> Result<[Variable; GATE_WIDTH], MyError> {
+        let config = &self.constants.0;
+        let num_rounds = config.full_rounds + config.partial_rounds;
+        let full_rounds_over_2 = config.full_rounds / 2;
+        let mut state = *state;
+        for i in 0..num_rounds {
+            let is_full_round =
+                i < full_rounds_over_2 || i >= full_rounds_over_2 + config.partial_rounds;
+            let mut next = state;
+            for (j, next_j) in next.iter_mut().enumerate() {
+                let mut q_lc = [BaseField::ZERO; GATE_WIDTH];
+                let mut q_hash = [BaseField::ZERO; GATE_WIDTH];
+                for (k, m) in config.mds[j].iter().enumerate() {
+                    if is_full_round || k == 0 {
+                        q_hash[k] = *m;
+                    } else {
+                        q_lc[k] = *m;
+                    }
+                }
+                let q_c = config.ark.get(i + 1).map_or(BaseField::ZERO, |ark| ark[j]);
+                *next_j = cs.lc_power_5(&state, &q_lc, &q_hash, &q_c)?;
+            }
+            state = next;
+        }
+        Ok(state)
+    }
+}
+
+impl<BaseField> ROCircuitTrait<BaseField> for PoseidonROCircuit<BaseField>
+where
+    BaseField: PrimeField + PoseidonDefaultConfigField + Absorb,
+{
+    type Constants = PoseidonConstants<BaseField>;
+
+    fn new(constants: Self::Constants) -> Self {
+        Self {
+            constants,
+            absorbed: vec![],
+        }
+    }
+
+    fn absorb(&mut self, e: Variable) {
+        self.absorbed.push(e);
+    }
+
+    fn squeeze(
+        &mut self,
+        cs: &mut PlonkishCircuit<BaseField>,
+        num_bits: usize,
+    ) -> Result<Vec<Variable>, MyError> {
+        let config = &self.constants.0;
+        if config.alpha != 5 || config.rate + config.capacity != GATE_WIDTH {
+            return Err(MyError::HashError);
+        }
+        let (rate, capacity) = (config.rate, config.capacity);
+        let zero = cs.zero();
+        let mut state = [zero; GATE_WIDTH];
+        let chunks = if self.absorbed.is_empty() {
+            vec![&self.absorbed[..]]
+        } else {
+            self.absorbed.chunks(rate).collect()
+        };
+        for chunk in chunks {
+            // s + e + ark[0], with e = 0 outside of the rate part and past the end of the chunk
+            for (j, s) in state.iter_mut().enumerate() {
+                let e = j
+                    .checked_sub(capacity)
+                    .and_then(|k| chunk.get(k))
+                    .copied()
+                    .unwrap_or(zero);
+                *s = cs.lc_power_5(
+                    &[*s, e, zero, zero],
+                    &[
+                        BaseField::ONE,
+                        BaseField::ONE,
+                        BaseField::ZERO,
+                        BaseField::ZERO,
+                    ],
+                    &[BaseField::ZERO; GATE_WIDTH],
+                    &config.ark[0][j],
+                )?;
+            }
+            state = self.permute(cs, &state)?;
+        }
+
+        // the same low bits as the native squeeze, out of the canonical bits of the first rate element
+        let bits = cs.unpack_strict(state[capacity])?;
+        Ok(bits[..num_bits].to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{circuit::CircuitGroup, provider::grumpkin::Grumpkin, ROConstants};
+    use ark_bn254::Bn254;
+    use ark_ff::UniformRand;
+    use jf_utils::test_rng;
+    use rand::Rng;
+
+    fn test_poseidon_ro_circuit_with<G: CircuitGroup>() {
+        let rng = &mut test_rng();
+        // nothing absorbed, less than, exactly and more than a rate of elements
+        for num_absorbed in [0, 1, 3, 4, 7, 12] {
+            let num_bits = rng.gen_range(1..G::ScalarField::MODULUS_BIT_SIZE as usize);
+            let elements = (0..num_absorbed)
+                .map(|_| G::BaseField::rand(rng))
+                .collect::<Vec<_>>();
+
+            let mut ro = G::RO::new(ROConstants::<G>::new(3));
+            elements.iter().for_each(|e| ro.absorb(*e));
+            let expected = ro.squeeze(num_bits).into_bigint().to_bits_le();
+
+            let mut cs = PlonkishCircuit::<G::BaseField>::new();
+            let mut ro_circuit = G::ROCircuit::new(ROConstants::<G>::new(3));
+            for e in elements {
+                ro_circuit.absorb(cs.create_variable(e).unwrap());
+            }
+            let bits = ro_circuit.squeeze(&mut cs, num_bits).unwrap();
+            cs.check_circuit_satisfiability().unwrap();
+
+            assert_eq!(bits.len(), num_bits);
+            for (bit, expected) in bits.iter().zip(expected) {
+                assert_eq!(cs.witness(*bit).unwrap(), G::BaseField::from(expected));
+            }
+        }
+    }
+
+    #[test]
+    fn test_poseidon_ro_circuit() {
+        test_poseidon_ro_circuit_with::<Bn254>();
+        test_poseidon_ro_circuit_with::<Grumpkin>();
     }
 }
@@ -1 +1,115 @@
 // grumpkin curve implementaion for PCS
+//
+// y^2 = x^3 - 17 over the bn254 scalar field, whose group order is the bn254 base field modulus,
+// so that bn254 and grumpkin form a 2-cycle
+use ark_ec::{
+    models::CurveConfig,
+    short_weierstrass::{self as sw, SWCurveConfig},
+};
+use ark_ff::{Field, MontFp};
+
+use super::grumpkin_field::{Fq, Fr};
+
+pub type Affine = sw::Affine<Config>;
+pub type Projective = sw::Projective<Config>;
+
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct Config;
+
+impl CurveConfig for Config {
+    type BaseField = Fq;
+    type ScalarField = Fr;
+
+    /// COFACTOR = 1
+    const COFACTOR: &'static [u64] = &[0x1];
+
+    /// COFACTOR_INV = COFACTOR^{-1} mod r = 1
+    const COFACTOR_INV: Fr = Fr::ONE;
+}
+
+impl SWCurveConfig for Config {
+    /// COEFF_A = 0
+    const COEFF_A: Fq = Fq::ZERO;
+
+    /// COEFF_B = -17
+    const COEFF_B: Fq = MontFp!("-17");
+
+    /// GENERATOR = (G_GENERATOR_X, G_GENERATOR_Y)
+    const GENERATOR: Affine = Affine::new_unchecked(G_GENERATOR_X, G_GENERATOR_Y);
+
+    #[inline(always)]
+    fn mul_by_a(_: Self::BaseField) -> Self::BaseField {
+        Self::BaseField::ZERO
+    }
+}
+
+/// G_GENERATOR_X = 1
+pub const G_GENERATOR_X: Fq = MontFp!("1");
+
+/// G_GENERATOR_Y = sqrt(-16)
+pub const G_GENERATOR_Y: Fq =
+    MontFp!("17631683881184975370165255887551781615748388533673675138860");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::poseidon::poseidon_constants::PoseidonDefaultConfigField;
+    use ark_ec::{AffineRepr, CurveGroup};
+    use ark_ff::PrimeField;
+    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+    use ark_std::{UniformRand, Zero};
+    use jf_utils::test_rng;
+
+    #[test]
+    fn test_cycle() {
+        // the fields are swapped
+        assert_eq!(
+            <Fq as PrimeField>::MODULUS,
+            <ark_bn254::Fr as PrimeField>::MODULUS
+        );
+        assert_eq!(
+            <Fr as PrimeField>::MODULUS,
+            <ark_bn254::Fq as PrimeField>::MODULUS
+        );
+
+        // and each curve has as many points as the base field of the other one
+        let g = Affine::generator();
+        assert!(g.is_on_curve() && g.is_in_correct_subgroup_assuming_on_curve());
+        assert!(!g.is_zero());
+        assert!(g.mul_bigint(<Fr as PrimeField>::MODULUS).is_zero());
+        assert!(ark_bn254::G1Affine::generator()
+            .mul_bigint(<ark_bn254::Fr as PrimeField>::MODULUS)
+            .is_zero());
+    }
+
+    #[test]
+    fn test_serialization() {
+        let rng = &mut test_rng();
+        for _ in 0..10 {
+            let p = Projective::rand(rng).into_affine();
+            assert!(p.is_on_curve());
+
+            let mut bytes = vec![];
+            p.serialize_compressed(&mut bytes).unwrap();
+            assert_eq!(Affine::deserialize_compressed(&bytes[..]).unwrap(), p);
+
+            let mut bytes = vec![];
+            p.serialize_uncompressed(&mut bytes).unwrap();
+            assert_eq!(Affine::deserialize_uncompressed(&bytes[..]).unwrap(), p);
+        }
+
+        let mut bytes = vec![];
+        Affine::zero().serialize_compressed(&mut bytes).unwrap();
+        assert!(Affine::deserialize_compressed(&bytes[..])
+            .unwrap()
+            .is_zero());
+    }
+
+    #[test]
+    fn test_poseidon_params() {
+        // same number of rounds as the bn254 base field, both fields are of 254 bits
+        let params = Fq::get_default_poseidon_parameters(3, false).unwrap();
+        assert_eq!((params.full_rounds, params.partial_rounds), (8, 56));
+        assert_eq!(params.ark.len(), 8 + 56);
+    }
+}
@@ -1,2 +1,28 @@
-pub use crate::primary::bn254_field::{Fq as Fr, FqBackend as FrBackend};
-pub use crate::primary::bn254_field::{Fr as Fq, FrBackend as FqBackend};
+// configurations for grumpkin field, which swaps the base and scalar fields of bn254
+use crate::poseidon::poseidon_constants::{PoseidonDefaultConfig, PoseidonDefaultConfigEntry};
+use ark_ff::fields::MontBackend;
+
+pub use ark_bn254::{Fq as Fr, FqConfig as FrBackend};
+pub use ark_bn254::{Fr as Fq, FrConfig as FqBackend};
+
+/// for grumpkin base field Fq, i.e. bn254 scalar field
+impl PoseidonDefaultConfig<4> for MontBackend<FqBackend, 4> {
+    const PARAMS_OPT_FOR_CONSTRAINTS: [PoseidonDefaultConfigEntry; 7] = [
+        PoseidonDefaultConfigEntry::new(2, 17, 8, 31, 0),
+        PoseidonDefaultConfigEntry::new(3, 5, 8, 56, 0),
+        PoseidonDefaultConfigEntry::new(4, 5, 8, 56, 0),
+        PoseidonDefaultConfigEntry::new(5, 5, 8, 57, 0),
+        PoseidonDefaultConfigEntry::new(6, 5, 8, 57, 0),
+        PoseidonDefaultConfigEntry::new(7, 5, 8, 57, 0),
+        PoseidonDefaultConfigEntry::new(8, 5, 8, 57, 0),
+    ];
+    const PARAMS_OPT_FOR_WEIGHTS: [PoseidonDefaultConfigEntry; 7] = [
+        PoseidonDefaultConfigEntry::new(2, 257, 8, 13, 0),
+        PoseidonDefaultConfigEntry::new(3, 257, 8, 13, 0),
+        PoseidonDefaultConfigEntry::new(4, 257, 8, 13, 0),
+        PoseidonDefaultConfigEntry::new(5, 257, 8, 13, 0),
+        PoseidonDefaultConfigEntry::new(6, 257, 8, 13, 0),
+        PoseidonDefaultConfigEntry::new(7, 257, 8, 13, 0),
+        PoseidonDefaultConfigEntry::new(8, 257, 8, 13, 0),
+    ];
+}
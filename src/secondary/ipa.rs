@@ -1 +1,382 @@
 // ipa commitment scheme for grumpkin curve which is not fully pairing-friendly
+//
+// a bulletproofs-style inner product argument over pedersen vector commitments, with generators hashed
+// onto the curve so that the setup is transparent. a committed vector is read as the coefficients of a
+// univariate polynomial, same as KZG on the primary side, and its evaluation at x is <a, (1, x, x^2, ...)>
+use core::ops::{Add, Mul};
+use rand::rngs::StdRng;
+use rayon::prelude::*;
+use sha3::{Digest, Keccak256};
+use std::marker::PhantomData;
+
+use ark_ec::{AffineRepr, CurveGroup, VariableBaseMSM};
+use ark_ff::{BigInteger, Field, PrimeField};
+
+use crate::error::MyError;
+use crate::to_coordinates;
+use crate::traits::{
+    AbsorbInROTrait, CommitmentEngineTrait, CommitmentTrait, EvaluationEngineTrait, Group, ROTrait,
+    TranscriptEngineTrait, TranscriptReprTrait,
+};
+
+type Affine<G> = <<G as Group>::Curve as CurveGroup>::Affine;
+
+const IPA_GENERATORS_LABEL: &[u8] = b"PNova-IPA-generators";
+const IPA_INNER_PRODUCT_LABEL: &[u8] = b"PNova-IPA-inner-product";
+
+/// try-and-increment hashing of `label || index || counter` onto the curve
+fn hash_to_curve<A: AffineRepr>(label: &[u8], n: usize) -> Vec<A> {
+    (0..n as u64)
+        .into_par_iter()
+        .map(|i| {
+            (0u32..)
+                .find_map(|counter| {
+                    let digest = Keccak256::new()
+                        .chain_update(label)
+                        .chain_update(i.to_le_bytes())
+                        .chain_update(counter.to_le_bytes())
+                        .finalize();
+                    A::from_random_bytes(&digest)
+                        .map(|p| p.clear_cofactor())
+                        .filter(|p| !p.is_zero())
+                })
+                .unwrap()
+        })
+        .collect()
+}
+
+/// <a, b>
+fn inner_product<F: Field>(a: &[F], b: &[F]) -> F {
+    a.iter()
+        .zip(b.iter())
+        .fold(F::ZERO, |acc, (a, b)| acc + *a * b)
+}
+
+/// pedersen vector commitments over transparent generators, opened by the inner product argument below
+pub struct IPACommitmentEngine<G: Group> {
+    _p: PhantomData<G>,
+}
+
+/// transparent generators of pedersen vector commitments
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IPACommitmentKey<G: Group> {
+    pub(crate) ck: Vec<Affine<G>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IPACommitment<G: Group>(pub(crate) Affine<G>);
+
+impl<G: Group> Default for IPACommitment<G> {
+    fn default() -> Self {
+        IPACommitment(Affine::<G>::zero())
+    }
+}
+
+impl<G: Group> Add for IPACommitment<G> {
+    type Output = IPACommitment<G>;
+
+    fn add(self, other: IPACommitment<G>) -> IPACommitment<G> {
+        IPACommitment((self.0 + other.0).into_affine())
+    }
+}
+
+impl<G: Group> Mul<<G as Group>::ScalarField> for IPACommitment<G> {
+    type Output = IPACommitment<G>;
+
+    fn mul(self, scalar: <G as Group>::ScalarField) -> IPACommitment<G> {
+        IPACommitment((self.0 * scalar).into_affine())
+    }
+}
+
+/// absorb the affine coordinates together with the infinity flag
+impl<G: Group> AbsorbInROTrait<G> for IPACommitment<G> {
+    fn absorb_in_ro(&self, ro: &mut G::RO) {
+        let (x, y, is_infinity) = to_coordinates::<G>(&self.0);
+        ro.absorb(x);
+        ro.absorb(y);
+        ro.absorb(if is_infinity {
+            <G as Group>::BaseField::ONE
+        } else {
+            <G as Group>::BaseField::ZERO
+        });
+    }
+}
+
+/// the affine coordinates followed by the infinity flag, same as what goes into the RO
+impl<G: Group> TranscriptReprTrait<G> for IPACommitment<G> {
+    fn to_transcript_bytes(&self) -> Vec<u8> {
+        let (x, y, is_infinity) = to_coordinates::<G>(&self.0);
+        [
+            x.into_bigint().to_bytes_le(),
+            y.into_bigint().to_bytes_le(),
+            vec![is_infinity as u8],
+        ]
+        .concat()
+    }
+}
+
+impl<G: Group> CommitmentTrait<G> for IPACommitment<G> {
+    fn to_coordinates(&self) -> (G::BaseField, G::BaseField, bool) {
+        to_coordinates::<G>(&self.0)
+    }
+}
+
+impl<G: Group> CommitmentEngineTrait<G> for IPACommitmentEngine<G> {
+    type CommitmentKey = IPACommitmentKey<G>;
+    type Commitment = IPACommitment<G>;
+
+    /// generators are derived from a fixed label, the randomness is not needed
+    fn setup(_rng: &mut StdRng, degree: usize) -> Self::CommitmentKey {
+        IPACommitmentKey {
+            ck: hash_to_curve(IPA_GENERATORS_LABEL, (degree + 1).next_power_of_two()),
+        }
+    }
+
+    fn commit(ck: &Self::CommitmentKey, v: &[<G as Group>::ScalarField]) -> Self::Commitment {
+        assert!(ck.ck.len() >= v.len());
+        IPACommitment(G::Curve::msm_unchecked(&ck.ck[..v.len()], v).into_affine())
+    }
+}
+
+/// inner product argument for evaluations of committed vectors
+#[derive(Clone)]
+pub struct IPAEvaluationEngine<G: Group> {
+    _p: PhantomData<G>,
+}
+
+/// the extra generator binding the inner product
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IPAProverKey<G: Group> {
+    u: Affine<G>,
+}
+
+/// the verifier folds the generators on its own
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IPAVerifierKey<G: Group> {
+    ck: Vec<Affine<G>>,
+    u: Affine<G>,
+}
+
+/// cross commitments of each halving round, and the final folded scalar
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IPAEvaluationArgument<G: Group> {
+    L_vec: Vec<IPACommitment<G>>,
+    R_vec: Vec<IPACommitment<G>>,
+    a_hat: <G as Group>::ScalarField,
+}
+
+impl<G: Group> IPAEvaluationEngine<G> {
+    /// binds the claim into the transcript and returns the generator scaled by a fresh challenge
+    fn bind_claim(
+        transcript: &mut G::TE,
+        u: &Affine<G>,
+        comm: &IPACommitment<G>,
+        point: &<G as Group>::ScalarField,
+        eval: &<G as Group>::ScalarField,
+    ) -> Result<G::Curve, MyError> {
+        transcript.dom_sep(b"IPA");
+        transcript.absorb(b"C", comm);
+        transcript.absorb(b"x", point);
+        transcript.absorb(b"y", eval);
+        let r = transcript.squeeze(b"r")?;
+        Ok(*u * r)
+    }
+
+    /// 1, x, x^2, ..., x^{n-1}
+    fn powers(x: &<G as Group>::ScalarField, n: usize) -> Vec<<G as Group>::ScalarField> {
+        (0..n)
+            .scan(<G as Group>::ScalarField::ONE, |acc, _| {
+                let p = *acc;
+                *acc *= x;
+                Some(p)
+            })
+            .collect()
+    }
+
+    /// G_L * x^{-1} + G_R * x
+    fn fold_generators(
+        G_vec: &[Affine<G>],
+        x: &<G as Group>::ScalarField,
+        x_inv: &<G as Group>::ScalarField,
+    ) -> Vec<Affine<G>> {
+        let n = G_vec.len() / 2;
+        let folded = (0..n)
+            .into_par_iter()
+            .map(|i| G_vec[i] * x_inv + G_vec[n + i] * x)
+            .collect::<Vec<G::Curve>>();
+        G::Curve::normalize_batch(&folded)
+    }
+
+    /// a_L * x + a_R * x^{-1}
+    fn fold_scalars(
+        v: &[<G as Group>::ScalarField],
+        x: &<G as Group>::ScalarField,
+        x_inv: &<G as Group>::ScalarField,
+    ) -> Vec<<G as Group>::ScalarField> {
+        let n = v.len() / 2;
+        (0..n).map(|i| v[i] * x + v[n + i] * x_inv).collect()
+    }
+}
+
+impl<G: Group> EvaluationEngineTrait<G> for IPAEvaluationEngine<G> {
+    type CE = IPACommitmentEngine<G>;
+    type ProverKey = IPAProverKey<G>;
+    type VerifierKey = IPAVerifierKey<G>;
+    type EvaluationArgument = IPAEvaluationArgument<G>;
+
+    fn setup(ck: &IPACommitmentKey<G>) -> (Self::ProverKey, Self::VerifierKey) {
+        let u = hash_to_curve::<Affine<G>>(IPA_INNER_PRODUCT_LABEL, 1)[0];
+        (
+            IPAProverKey { u },
+            IPAVerifierKey {
+                ck: ck.ck.clone(),
+                u,
+            },
+        )
+    }
+
+    fn prove(
+        ck: &IPACommitmentKey<G>,
+        pk: &Self::ProverKey,
+        transcript: &mut G::TE,
+        comm: &IPACommitment<G>,
+        poly: &[<G as Group>::ScalarField],
+        point: &<G as Group>::ScalarField,
+        eval: &<G as Group>::ScalarField,
+    ) -> Result<Self::EvaluationArgument, MyError> {
+        let n = poly.len().next_power_of_two();
+        if ck.ck.len() < n {
+            return Err(MyError::InvalidInputLength);
+        }
+        let U = Self::bind_claim(transcript, &pk.u, comm, point, eval)?;
+
+        let mut a_vec = poly.to_vec();
+        a_vec.resize(n, <G as Group>::ScalarField::ZERO);
+        let mut b_vec = Self::powers(point, n);
+        let mut G_vec = ck.ck[..n].to_vec();
+
+        let (mut L_vec, mut R_vec) = (vec![], vec![]);
+        while a_vec.len() > 1 {
+            let m = a_vec.len() / 2;
+            let (a_L, a_R) = a_vec.split_at(m);
+            let (b_L, b_R) = b_vec.split_at(m);
+            let (G_L, G_R) = G_vec.split_at(m);
+
+            let L = IPACommitment(
+                (G::Curve::msm_unchecked(G_R, a_L) + U * inner_product(a_L, b_R)).into_affine(),
+            );
+            let R = IPACommitment(
+                (G::Curve::msm_unchecked(G_L, a_R) + U * inner_product(a_R, b_L)).into_affine(),
+            );
+            transcript.absorb(b"L", &L);
+            transcript.absorb(b"R", &R);
+            let x = transcript.squeeze(b"x")?;
+            let x_inv = x.inverse().ok_or(MyError::InvalidPCS)?;
+
+            a_vec = Self::fold_scalars(&a_vec, &x, &x_inv);
+            b_vec = Self::fold_scalars(&b_vec, &x_inv, &x);
+            G_vec = Self::fold_generators(&G_vec, &x, &x_inv);
+            L_vec.push(L);
+            R_vec.push(R);
+        }
+
+        Ok(IPAEvaluationArgument {
+            L_vec,
+            R_vec,
+            a_hat: a_vec[0],
+        })
+    }
+
+    fn verify(
+        vk: &Self::VerifierKey,
+        transcript: &mut G::TE,
+        comm: &IPACommitment<G>,
+        point: &<G as Group>::ScalarField,
+        eval: &<G as Group>::ScalarField,
+        arg: &Self::EvaluationArgument,
+    ) -> Result<(), MyError> {
+        if arg.L_vec.len() != arg.R_vec.len() || arg.L_vec.len() >= usize::BITS as usize {
+            return Err(MyError::InvalidPCS);
+        }
+        let n = 1usize << arg.L_vec.len();
+        if vk.ck.len() < n {
+            return Err(MyError::InvalidInputLength);
+        }
+        let U = Self::bind_claim(transcript, &vk.u, comm, point, eval)?;
+
+        // P = <a, G> + <a, b> * U, folded along with the prover
+        let mut P = comm.0 + U * eval;
+        let mut b_vec = Self::powers(point, n);
+        let mut G_vec = vk.ck[..n].to_vec();
+        for (L, R) in arg.L_vec.iter().zip(arg.R_vec.iter()) {
+            transcript.absorb(b"L", L);
+            transcript.absorb(b"R", R);
+            let x = transcript.squeeze(b"x")?;
+            let x_inv = x.inverse().ok_or(MyError::InvalidPCS)?;
+
+            P += L.0 * x.square() + R.0 * x_inv.square();
+            b_vec = Self::fold_scalars(&b_vec, &x_inv, &x);
+            G_vec = Self::fold_generators(&G_vec, &x, &x_inv);
+        }
+
+        if P == G_vec[0] * arg.a_hat + U * (arg.a_hat * b_vec[0]) {
+            Ok(())
+        } else {
+            Err(MyError::InvalidPCS)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::{grumpkin::Grumpkin, keccak::Keccak256Transcript};
+    use ark_bn254::Bn254;
+    use ark_ff::UniformRand;
+    use jf_utils::test_rng;
+
+    fn test_ipa_evaluation_with<G: Group<TE = Keccak256Transcript<G>>>() {
+        let rng = &mut test_rng();
+        let degree = 6;
+        let ck = IPACommitmentEngine::<G>::setup(rng, degree);
+        let (pk, vk) = IPAEvaluationEngine::<G>::setup(&ck);
+
+        let poly = (0..degree + 1)
+            .map(|_| <G as Group>::ScalarField::rand(rng))
+            .collect::<Vec<_>>();
+        let point = <G as Group>::ScalarField::rand(rng);
+        let eval = poly
+            .iter()
+            .rev()
+            .fold(<G as Group>::ScalarField::ZERO, |acc, c| acc * point + c);
+        let comm = IPACommitmentEngine::<G>::commit(&ck, &poly);
+
+        let mut transcript = Keccak256Transcript::<G>::new(b"test");
+        let arg =
+            IPAEvaluationEngine::<G>::prove(&ck, &pk, &mut transcript, &comm, &poly, &point, &eval)
+                .unwrap();
+
+        let mut transcript = Keccak256Transcript::<G>::new(b"test");
+        IPAEvaluationEngine::<G>::verify(&vk, &mut transcript, &comm, &point, &eval, &arg).unwrap();
+
+        // a wrong evaluation is rejected
+        let mut transcript = Keccak256Transcript::<G>::new(b"test");
+        assert_eq!(
+            IPAEvaluationEngine::<G>::verify(
+                &vk,
+                &mut transcript,
+                &comm,
+                &point,
+                &(eval + <G as Group>::ScalarField::ONE),
+                &arg
+            ),
+            Err(MyError::InvalidPCS)
+        );
+    }
+
+    #[test]
+    fn test_ipa_evaluation() {
+        test_ipa_evaluation_with::<Grumpkin>();
+        test_ipa_evaluation_with::<Bn254>();
+    }
+}
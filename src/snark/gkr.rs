@@ -0,0 +1,117 @@
+/// GKR over fractional sums, as in https://eprint.iacr.org/2023/1284.pdf
+///
+/// a layered circuit adds up fractions pairwise, p / q = p_0 / q_0 + p_1 / q_1 = (p_0 * q_1 + p_1 * q_0) / (q_0 * q_1),
+/// from the leaves to a single root. a claim on the multilinear extensions of a layer reduces by a sumcheck over
+/// eq(s, x) * (λ * (p_0 * q_1 + p_1 * q_0) + q_0 * q_1) to one on the next layer, down to a claim on the leaves
+///
+use ark_ff::{Field, PrimeField};
+use rayon::prelude::*;
+
+use super::{
+    multilinear::{eq_eval, eq_table},
+    sumcheck::SumcheckProof,
+};
+use crate::{
+    error::MyError,
+    traits::{Group, TranscriptEngineTrait},
+};
+
+/// the root of the fractional sum, then the sumcheck of every layer with the values of both children at its point
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct FractionalSumProof<F: Field> {
+    pub(crate) root: (F, F),
+    layers: Vec<(SumcheckProof<F>, [F; 4])>,
+}
+
+impl<F: PrimeField> FractionalSumProof<F> {
+    /// proves the sum of p / q over the hypercube, returning the point of the leaves with p and q there
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn prove<G: Group<ScalarField = F>>(
+        transcript: &mut G::TE,
+        p: Vec<F>,
+        q: Vec<F>,
+    ) -> Result<(Self, Vec<F>, F, F), MyError> {
+        if p.len() != q.len() || !p.len().is_power_of_two() {
+            return Err(MyError::InvalidInputLength);
+        }
+
+        let mut circuit = vec![(p, q)];
+        while circuit.last().unwrap().0.len() > 1 {
+            let (p, q) = circuit.last().unwrap();
+            let layer = (0..p.len() / 2)
+                .into_par_iter()
+                .map(|i| {
+                    let (p_0, p_1, q_0, q_1) = (p[2 * i], p[2 * i + 1], q[2 * i], q[2 * i + 1]);
+                    (p_0 * q_1 + p_1 * q_0, q_0 * q_1)
+                })
+                .unzip();
+            circuit.push(layer);
+        }
+
+        let root = (circuit.last().unwrap().0[0], circuit.last().unwrap().1[0]);
+        transcript.absorb(b"root_p", &root.0);
+        transcript.absorb(b"root_q", &root.1);
+
+        let (mut point, mut claim) = (vec![], root);
+        let mut layers = vec![];
+        for (p, q) in circuit.iter().rev().skip(1) {
+            let lambda = transcript.squeeze(b"lambda")?;
+            let split =
+                |v: &[F], parity: usize| v.iter().skip(parity).step_by(2).copied().collect();
+            let tables = vec![
+                eq_table(&point),
+                split(p, 0),
+                split(q, 0),
+                split(p, 1),
+                split(q, 1),
+            ];
+            let (sc_proof, s, values) = SumcheckProof::prove::<G>(transcript, tables, 3, |v| {
+                v[0] * (lambda * (v[1] * v[4] + v[3] * v[2]) + v[2] * v[4])
+            })?;
+
+            let children = [values[1], values[2], values[3], values[4]];
+            children.iter().for_each(|c| transcript.absorb(b"child", c));
+            let mu = transcript.squeeze(b"mu")?;
+            claim = (
+                children[0] + mu * (children[2] - children[0]),
+                children[1] + mu * (children[3] - children[1]),
+            );
+            point = [vec![mu], s].concat();
+            layers.push((sc_proof, children));
+        }
+
+        Ok((Self { root, layers }, point, claim.0, claim.1))
+    }
+
+    /// reduces the root to a claim on the leaves, returning the root, the point of the leaves and p and q there
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn verify<G: Group<ScalarField = F>>(
+        &self,
+        transcript: &mut G::TE,
+        num_vars: usize,
+    ) -> Result<((F, F), Vec<F>, F, F), MyError> {
+        if self.layers.len() != num_vars {
+            return Err(MyError::ProofVerifyError);
+        }
+        transcript.absorb(b"root_p", &self.root.0);
+        transcript.absorb(b"root_q", &self.root.1);
+
+        let (mut point, mut claim) = (vec![], self.root);
+        for (sc_proof, children) in &self.layers {
+            let lambda = transcript.squeeze(b"lambda")?;
+            let (value, s) =
+                sc_proof.verify::<G>(transcript, lambda * claim.0 + claim.1, point.len(), 3)?;
+            let [p_0, q_0, p_1, q_1] = *children;
+            if eq_eval(&point, &s) * (lambda * (p_0 * q_1 + p_1 * q_0) + q_0 * q_1) != value {
+                return Err(MyError::InvalidSumcheck);
+            }
+
+            children.iter().for_each(|c| transcript.absorb(b"child", c));
+            let mu = transcript.squeeze(b"mu")?;
+            claim = (p_0 + mu * (p_1 - p_0), q_0 + mu * (q_1 - q_0));
+            point = [vec![mu], s].concat();
+        }
+
+        Ok((self.root, point, claim.0, claim.1))
+    }
+}
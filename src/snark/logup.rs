@@ -0,0 +1,89 @@
+/// the LogUp identity of a folded lookup by the fractional sums of GKR, as in https://eprint.iacr.org/2023/1284.pdf
+///
+/// the relaxed lookup holds iff Σ_i (E_h[i] + q_i * u^2) / (w_i + α) = Σ_j (E_g[j] + m_j * u) / (t_j * u + α),
+/// so that its leaves are made of committed columns alone, and the inverses h and g are never opened
+///
+use ark_ff::{Field, PrimeField};
+use rayon::prelude::*;
+
+use super::{gkr::FractionalSumProof, multilinear::evaluate};
+use crate::{
+    error::MyError,
+    traits::{Group, TranscriptEngineTrait},
+};
+
+/// the fractional sum of a relaxed lookup, over the rows followed by the table padded to as many entries,
+/// together with the evaluations of q, t, m, E_h, E_g and w at the point of the leaves
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct LogUpProof<F: Field> {
+    gkr: FractionalSumProof<F>,
+    pub(crate) evals: Vec<F>,
+}
+
+/// p and q of the leaves, from the values of q, t, m, E_h, E_g and w, on the rows if `s` is 0 and the table if 1
+fn leaves<F: Field>(v: &[F], u: &F, alpha: &F, s: &F) -> (F, F) {
+    let [q, t, m, E_h, E_g, w] = [v[0], v[1], v[2], v[3], v[4], v[5]];
+    let (p_rows, q_rows) = (E_h + q * u.square(), w + alpha);
+    let (p_table, q_table) = (-(E_g + m * u), t * u + alpha);
+    (
+        p_rows + *s * (p_table - p_rows),
+        q_rows + *s * (q_table - q_rows),
+    )
+}
+
+impl<F: PrimeField> LogUpProof<F> {
+    /// proves the identity over the columns q, t, m, E_h, E_g and w of as many rows each,
+    /// returning the point of the rows the columns are evaluated at
+    pub(crate) fn prove<G: Group<ScalarField = F>>(
+        transcript: &mut G::TE,
+        columns: [&[F]; 6],
+        u: &F,
+        alpha: &F,
+    ) -> Result<(Self, Vec<F>), MyError> {
+        let n = columns[0].len();
+        let row = |i: usize| columns.map(|c| c[i]);
+        let (p, q): (Vec<F>, Vec<F>) = (0..2 * n)
+            .into_par_iter()
+            .map(|i| leaves(&row(i % n), u, alpha, &F::from((i / n) as u64)))
+            .unzip();
+
+        let (gkr, point, _, _) = FractionalSumProof::prove::<G>(transcript, p, q)?;
+        if gkr.root.0 != F::ZERO || gkr.root.1 == F::ZERO {
+            return Err(MyError::UnSatLookupSum);
+        }
+        let point = point[..point.len() - 1].to_vec();
+        let evals = columns
+            .iter()
+            .map(|c| evaluate(c, &point))
+            .collect::<Vec<_>>();
+        evals.iter().for_each(|e| transcript.absorb(b"eval", e));
+
+        Ok((Self { gkr, evals }, point))
+    }
+
+    /// checks the identity down to the leaves, returning the point of the rows and the evaluations of the columns
+    /// there, which are left to the caller
+    pub(crate) fn verify<G: Group<ScalarField = F>>(
+        &self,
+        transcript: &mut G::TE,
+        num_vars: usize,
+        u: &F,
+        alpha: &F,
+    ) -> Result<(Vec<F>, &[F]), MyError> {
+        if self.evals.len() != 6 {
+            return Err(MyError::ProofVerifyError);
+        }
+        let (root, point, p, q) = self.gkr.verify::<G>(transcript, num_vars + 1)?;
+        if root.0 != F::ZERO || root.1 == F::ZERO {
+            return Err(MyError::ProofVerifyError);
+        }
+        if leaves(&self.evals, u, alpha, &point[num_vars]) != (p, q) {
+            return Err(MyError::ProofVerifyError);
+        }
+        self.evals
+            .iter()
+            .for_each(|e| transcript.absorb(b"eval", e));
+
+        Ok((point[..num_vars].to_vec(), &self.evals))
+    }
+}
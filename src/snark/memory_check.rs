@@ -0,0 +1,120 @@
+/// the final check of a folded memory by the fractional sums of GKR, as in https://eprint.iacr.org/2023/1284.pdf
+///
+/// the relaxed memory holds iff Σ_i (E_write[i] + s_i * u^3) / f(write_i) - (E_read[i] + s_i * u^3) / f(read_i) +
+/// Σ_j (E_init[j] + u^3) / f(init_j) - (E_final[j] + u^3) / f(final_j) = 0, with the homogenized fingerprints
+/// f = γ * u + a + β * v + δ * t of the folded challenges (γ, β, δ), so that its leaves are made of committed columns
+/// alone, and the inverses are never opened. the leaves are the reads, the writes, the initial and the final memory,
+/// in this order, each padded to as many rows, with a mask of the addresses in the memory
+///
+use ark_ff::{Field, PrimeField};
+use rayon::prelude::*;
+
+use super::{
+    gkr::FractionalSumProof,
+    multilinear::{eq_table, evaluate},
+};
+use crate::{
+    error::MyError,
+    traits::{Group, TranscriptEngineTrait},
+};
+
+/// the fractional sum of a relaxed memory, together with the evaluations of s, the mask, E_read, E_write, v_init,
+/// t_init, v_final, t_final, E_init, E_final, and the wires a, v_r, t_r, v_w, t_w at the point of the rows
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct MemoryCheckProof<F: Field> {
+    gkr: FractionalSumProof<F>,
+    pub(crate) evals: Vec<F>,
+}
+
+/// p and q of the leaves of the k-th group, from the values of the columns and of the address j at one row
+fn leaves<F: Field>(k: usize, v: &[F; 15], j: &F, u: &F, challenges: &[F; 3]) -> (F, F) {
+    let [s, mask, E_read, E_write, v_init, t_init, v_final, t_final, E_init, E_final, a, v_r, t_r, v_w, t_w] =
+        *v;
+    let u_cube = u.square() * u;
+    let fingerprint = |address: F, v: F, t: F| {
+        challenges[0] * u + address + challenges[1] * v + challenges[2] * t
+    };
+    match k {
+        0 => (-(E_read + s * u_cube), fingerprint(a * u, v_r, t_r)),
+        1 => (E_write + s * u_cube, fingerprint(a * u, v_w, t_w)),
+        2 => (
+            E_init + mask * u_cube,
+            fingerprint(*j * u.square(), v_init, t_init),
+        ),
+        _ => (
+            -(E_final + mask * u_cube),
+            fingerprint(*j * u.square(), v_final, t_final),
+        ),
+    }
+}
+
+impl<F: PrimeField> MemoryCheckProof<F> {
+    /// proves the identity over the columns in the order of the evaluations, of as many rows each,
+    /// returning the point of the rows the columns are evaluated at
+    pub(crate) fn prove<G: Group<ScalarField = F>>(
+        transcript: &mut G::TE,
+        columns: [&[F]; 15],
+        u: &F,
+        challenges: &[F; 3],
+    ) -> Result<(Self, Vec<F>), MyError> {
+        let n = columns[0].len();
+        let (p, q): (Vec<F>, Vec<F>) = (0..4 * n)
+            .into_par_iter()
+            .map(|i| {
+                let row = columns.map(|c| c[i % n]);
+                leaves(i / n, &row, &F::from((i % n) as u64), u, challenges)
+            })
+            .unzip();
+
+        let (gkr, point, _, _) = FractionalSumProof::prove::<G>(transcript, p, q)?;
+        if gkr.root.0 != F::ZERO || gkr.root.1 == F::ZERO {
+            return Err(MyError::UnSatMemorySum);
+        }
+        let point = point[..point.len() - 2].to_vec();
+        let evals = columns
+            .iter()
+            .map(|c| evaluate(c, &point))
+            .collect::<Vec<_>>();
+        evals.iter().for_each(|e| transcript.absorb(b"eval", e));
+
+        Ok((Self { gkr, evals }, point))
+    }
+
+    /// checks the identity down to the leaves, returning the point of the rows and the evaluations of the columns
+    /// there, which are left to the caller
+    pub(crate) fn verify<G: Group<ScalarField = F>>(
+        &self,
+        transcript: &mut G::TE,
+        num_vars: usize,
+        u: &F,
+        challenges: &[F; 3],
+    ) -> Result<(Vec<F>, &[F]), MyError> {
+        let evals: &[F; 15] = self
+            .evals
+            .as_slice()
+            .try_into()
+            .map_err(|_| MyError::ProofVerifyError)?;
+        let (root, point, p, q) = self.gkr.verify::<G>(transcript, num_vars + 2)?;
+        if root.0 != F::ZERO || root.1 == F::ZERO {
+            return Err(MyError::ProofVerifyError);
+        }
+        // the address at the point of the rows is Σ_k 2^k * r_k
+        let (rows, z) = point.split_at(num_vars);
+        let j = rows.iter().rev().fold(F::ZERO, |acc, r| acc.double() + r);
+        let leaves = eq_table(z)
+            .iter()
+            .enumerate()
+            .fold((F::ZERO, F::ZERO), |(p, q), (k, e)| {
+                let (p_k, q_k) = leaves(k, evals, &j, u, challenges);
+                (p + *e * p_k, q + *e * q_k)
+            });
+        if leaves != (p, q) {
+            return Err(MyError::ProofVerifyError);
+        }
+        self.evals
+            .iter()
+            .for_each(|e| transcript.absorb(b"eval", e));
+
+        Ok((rows.to_vec(), &self.evals))
+    }
+}
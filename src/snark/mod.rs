@@ -0,0 +1,1482 @@
+/// A SNARK proving the knowledge of a witness satisfying a relaxed PLONK instance, the decider of the folding scheme,
+/// generic over the evaluation engine of committed vectors
+///
+/// every column is read as a multilinear polynomial over the hypercube of log(num_cons) variables, as in hyperplonk:
+/// https://eprint.iacr.org/2022/1355.pdf
+/// 1. the gate of every row equals the sum of its error vectors, a zero-check against eq(τ, x)
+/// 2. the wiring holds, i.e. the multisets {(w, id)} and {(w, σ)} agree, by their logarithmic derivatives summed up
+///    by the fractional sums of GKR, so that no helper column is committed
+/// 3. the lookup and the memory, if any, hold by the fractional sums of GKR as well
+/// 4. the claims of all of them on the columns at the points of their leaves are batched against eq(ρ, x) of every
+///    point
+/// 5. all go into one sumcheck, whose final claim is checked against the evaluations of all the columns at its
+///    point, opened at once by a multilinear evaluation argument over their random linear combination
+///
+/// rotated wires are committed as columns of their own, tied to the wires they rotate by the same wiring.
+/// the proof is not zero-knowledge
+///
+/// a compressed instance trades 1. for Σ_x B(x) * gate(x) = e, B(0) = u and B(1) = β, batched with the claims of 4.,
+/// and the zero-check of u * B(x + 1) - β * B(x) = E(x) on all rows but the last, with B rotated by one under the
+/// wiring like the rotated wires
+///
+mod gkr;
+mod logup;
+mod memory_check;
+pub mod multilinear;
+mod permutation;
+pub mod sumcheck;
+
+use ark_ff::{Field, PrimeField};
+use rayon::prelude::*;
+use sha3::{Digest, Keccak256};
+
+use crate::{
+    error::MyError,
+    gate::{Column, CustomGate},
+    lookup::{RelaxedLookupInstance, RelaxedLookupWitness},
+    memory::{RelaxedMemoryInstance, RelaxedMemoryWitness},
+    plonk::{
+        CompressedRelaxedPLONKInstance, CompressedRelaxedPLONKWitness, PLONKShape,
+        RelaxedPLONKInstance, RelaxedPLONKWitness,
+    },
+    traits::{
+        CommitmentEngineTrait, EvaluationEngineTrait, Group, TranscriptEngineTrait,
+        TranscriptReprTrait,
+    },
+    Commitment, CommitmentKey,
+};
+use logup::LogUpProof;
+use memory_check::MemoryCheckProof;
+use multilinear::{eq_eval, eq_table, MultilinearEvaluationArgument};
+use permutation::PermutationProof;
+use sumcheck::SumcheckProof;
+
+/// the layout of the columns in the sumcheck: eq(τ, x), the public input, eq(ρ, x) of the wiring, the lookup and the
+/// memory, the indicators of the first, second and last rows of a compressed instance, then the committed columns,
+/// selectors, σ, wires, errors, rotated wires, the columns of the lookup, q, t, m, E_h and E_g, and those of the
+/// memory, s, the mask, E_read, E_write, v_init, t_init, v_final, t_final, E_init and E_final, in this order
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Layout {
+    num_selectors: usize,
+    num_wires: usize,
+    num_errors: usize,
+    /// (wire, rotation) of every rotated wire, with rotations in 1..num_cons
+    rotations: Vec<(usize, usize)>,
+    /// whether the errors are B and E of a compressed instance, B being wire num_wires under the wiring
+    compressed: bool,
+    /// the looked-up wire, if any
+    lookup: Option<usize>,
+    /// the wires accessing the memory, if any
+    memory: Option<[usize; 5]>,
+}
+
+impl Layout {
+    fn new<F: PrimeField>(
+        gate: &CustomGate<F>,
+        num_wires: usize,
+        num_cons: usize,
+        lookup: Option<usize>,
+        memory: Option<[usize; 5]>,
+        compressed: bool,
+    ) -> Self {
+        let mut rotations = gate
+            .terms
+            .iter()
+            .flat_map(|t| t.columns.iter())
+            .filter_map(|c| match c {
+                Column::RotatedWire(i, rotation) => {
+                    Some((*i, rotation.rem_euclid(num_cons as isize) as usize))
+                }
+                _ => None,
+            })
+            .filter(|(_, rotation)| *rotation != 0)
+            .chain(compressed.then_some((num_wires, 1)))
+            .collect::<Vec<_>>();
+        rotations.sort();
+        rotations.dedup();
+        Self {
+            num_selectors: gate.num_selectors,
+            num_wires,
+            num_errors: if compressed { 2 } else { gate.degree - 1 },
+            rotations,
+            lookup,
+            memory,
+            compressed,
+        }
+    }
+
+    /// the wires under the wiring, followed by B of a compressed instance
+    fn num_perm_wires(&self) -> usize {
+        self.num_wires + usize::from(self.compressed)
+    }
+
+    /// the columns under the wiring, the wires followed by the rotated ones
+    fn num_perm(&self) -> usize {
+        self.num_perm_wires() + self.rotations.len()
+    }
+
+    const EQ: usize = 0;
+    const PI: usize = 1;
+    const EQ_PERM: usize = 2;
+    fn eq_lookup(&self) -> usize {
+        3
+    }
+    fn eq_memory(&self) -> usize {
+        self.eq_lookup() + self.lookup.iter().count()
+    }
+    fn rows(&self) -> usize {
+        self.eq_memory() + self.memory.iter().count()
+    }
+    fn committed(&self) -> usize {
+        self.rows() + 3 * usize::from(self.compressed)
+    }
+    fn selector(&self, s: usize) -> usize {
+        self.committed() + s
+    }
+    fn sigma(&self, c: usize) -> usize {
+        self.selector(self.num_selectors) + c
+    }
+    fn wire(&self, i: usize) -> usize {
+        self.sigma(self.num_perm()) + i
+    }
+    fn error(&self, j: usize) -> usize {
+        self.wire(self.num_wires) + j
+    }
+    fn rotated(&self, t: usize) -> usize {
+        self.error(self.num_errors) + t
+    }
+    fn perm(&self, c: usize) -> usize {
+        if c < self.num_wires {
+            self.wire(c)
+        } else if c < self.num_perm_wires() {
+            self.error(0)
+        } else {
+            self.rotated(c - self.num_perm_wires())
+        }
+    }
+    fn lookup_column(&self, k: usize) -> usize {
+        self.rotated(self.rotations.len()) + k
+    }
+    fn memory_column(&self, k: usize) -> usize {
+        self.lookup_column(5 * self.lookup.iter().count()) + k
+    }
+    fn len(&self) -> usize {
+        self.memory_column(10 * self.memory.iter().count())
+    }
+
+    /// the degree of the sumcheck polynomial in each variable
+    fn degree<F: PrimeField>(&self, gate: &CustomGate<F>) -> usize {
+        gate.terms
+            .iter()
+            .map(|t| t.selectors.len() + t.columns.len() + 1)
+            .max()
+            .unwrap_or(0)
+            .max(2 + usize::from(self.compressed))
+    }
+
+    /// the composed polynomial over the values v of all the columns at one point, i.e.
+    /// eq(τ, x) * (gate - Σ errors) + eq(ρ_perm, x) * Σ_k λ^{k+1} * (w_0, ..., w_{C-1}, σ_0, ..., σ_{C-1})_k +
+    /// eq(ρ_lookup, x) * Σ_k λ^{2C+k+1} * (q, t, m, E_h, E_g, w)_k + eq(ρ_memory, x) * Σ_k λ^{2C+6+k+1} *
+    /// (s, mask, E_read, E_write, v_init, t_init, v_final, t_final, E_init, E_final, a, v_r, t_r, v_w, t_w)_k,
+    /// the last two terms with a lookup and a memory only, the powers of λ following on from the missing ones.
+    /// a compressed instance replaces the first term with eq(τ, x) * (1 - L_last) * (u * B[+1] - β * B - E) and
+    /// adds (B * gate, L_0 * B, L_1 * B) with the last three powers of λ
+    #[allow(clippy::too_many_arguments)]
+    fn compose<F: PrimeField>(
+        &self,
+        gate: &CustomGate<F>,
+        num_cons: usize,
+        u: &F,
+        beta: &F,
+        lambda_powers: &[F],
+        v: &[F],
+    ) -> F {
+        let gate_value = gate.evaluate_with(
+            u,
+            |s| v[self.selector(s)],
+            |c| match c {
+                Column::Wire(i) => v[self.wire(*i)],
+                Column::RotatedWire(i, rotation) => {
+                    let rotation = rotation.rem_euclid(num_cons as isize) as usize;
+                    match self.rotations.iter().position(|r| *r == (*i, rotation)) {
+                        Some(t) => v[self.rotated(t)],
+                        None => v[self.wire(*i)],
+                    }
+                }
+                Column::PublicInput => v[Self::PI],
+            },
+        );
+        let (perm_powers, lookup_powers) = lambda_powers.split_at(2 * self.num_perm());
+        let (lookup_powers, memory_powers) = lookup_powers.split_at(6 * self.lookup.iter().count());
+        let (memory_powers, compressed_powers) =
+            memory_powers.split_at(15 * self.memory.iter().count());
+        let relation = if self.compressed {
+            let (b, e) = (v[self.error(0)], v[self.error(1)]);
+            let b_next = v[self.rotated(self.rotations.len() - 1)];
+            let [l_0, l_1, l_last] = [0, 1, 2].map(|k| v[self.rows() + k]);
+            v[Self::EQ] * (F::ONE - l_last) * (*u * b_next - *beta * b - e)
+                + compressed_powers[0] * b * gate_value
+                + compressed_powers[1] * l_0 * b
+                + compressed_powers[2] * l_1 * b
+        } else {
+            let errors = (0..self.num_errors).map(|j| v[self.error(j)]).sum::<F>();
+            v[Self::EQ] * (gate_value - errors)
+        };
+        let perm = (0..self.num_perm())
+            .map(|c| v[self.perm(c)])
+            .chain((0..self.num_perm()).map(|c| v[self.sigma(c)]))
+            .zip(perm_powers)
+            .map(|(c, l)| c * l)
+            .sum::<F>();
+        let lookup = self.lookup.map_or(F::ZERO, |wire| {
+            let columns = (0..5).map(|k| v[self.lookup_column(k)]);
+            v[self.eq_lookup()]
+                * columns
+                    .chain([v[self.wire(wire)]])
+                    .zip(lookup_powers)
+                    .map(|(c, l)| c * l)
+                    .sum::<F>()
+        });
+        let memory = self.memory.map_or(F::ZERO, |wires| {
+            let columns = (0..10).map(|k| v[self.memory_column(k)]);
+            v[self.eq_memory()]
+                * columns
+                    .chain(wires.map(|w| v[self.wire(w)]))
+                    .zip(memory_powers)
+                    .map(|(c, l)| c * l)
+                    .sum::<F>()
+        });
+        relation + v[Self::EQ_PERM] * perm + lookup + memory
+    }
+}
+
+/// the shape, with the wiring extended over the rotated wires, the selector and table of the lookup, and the selector
+/// and address mask of the memory
+#[derive(Clone, Debug)]
+pub struct ProverKey<G: Group, EE: EvaluationEngineTrait<G, CE = G::CE>> {
+    pk_ee: EE::ProverKey,
+    S: PLONKShape<G>,
+    layout: Layout,
+    sigma: Vec<Vec<<G as Group>::ScalarField>>,
+    lookup: Vec<Vec<<G as Group>::ScalarField>>,
+    memory: Vec<Vec<<G as Group>::ScalarField>>,
+    comm_selectors: Vec<Commitment<G>>,
+    comm_sigma: Vec<Commitment<G>>,
+    comm_lookup: Vec<Commitment<G>>,
+    comm_memory: Vec<Commitment<G>>,
+    vk_digest: <G as Group>::ScalarField,
+}
+
+/// commitments to the selectors, the extended wiring, the selector and table of the lookup, and the selector and
+/// address mask of the memory
+#[derive(Clone, Debug)]
+pub struct VerifierKey<G: Group, EE: EvaluationEngineTrait<G, CE = G::CE>> {
+    vk_ee: EE::VerifierKey,
+    num_cons: usize,
+    num_public_input: usize,
+    gate: CustomGate<<G as Group>::ScalarField>,
+    layout: Layout,
+    comm_selectors: Vec<Commitment<G>>,
+    comm_sigma: Vec<Commitment<G>>,
+    comm_lookup: Vec<Commitment<G>>,
+    comm_memory: Vec<Commitment<G>>,
+    digest: <G as Group>::ScalarField,
+}
+
+/// the commitments to the rotated wires, the fractional sums of the wiring, the lookup and the memory, the sumcheck,
+/// the evaluations of all the committed columns at its point and their joint evaluation argument
+#[derive(Clone, Debug)]
+pub struct RelaxedPLONKSNARK<G: Group, EE: EvaluationEngineTrait<G, CE = G::CE>> {
+    comm_rotated: Vec<Commitment<G>>,
+    perm: PermutationProof<<G as Group>::ScalarField>,
+    logup: Option<LogUpProof<<G as Group>::ScalarField>>,
+    memory: Option<MemoryCheckProof<<G as Group>::ScalarField>>,
+    sc_proof: SumcheckProof<<G as Group>::ScalarField>,
+    evals: Vec<<G as Group>::ScalarField>,
+    eval_arg: MultilinearEvaluationArgument<G, EE>,
+}
+
+/// the position every wire position is wired to, extended over B of a compressed instance and the rotated wires, so
+/// that every cell of a rotated wire joins the cycle of the cell it reads
+fn extend_sigma(sigma: &[Vec<usize>], layout: &Layout, n: usize) -> Vec<usize> {
+    let mut extended = sigma
+        .iter()
+        .flatten()
+        .copied()
+        .chain(layout.num_wires * n..layout.num_perm() * n)
+        .collect::<Vec<_>>();
+    for (t, (wire, rotation)) in layout.rotations.iter().enumerate() {
+        for row in 0..n {
+            let p = (layout.num_perm_wires() + t) * n + row;
+            let q = wire * n + (row + rotation) % n;
+            extended[p] = extended[q];
+            extended[q] = p;
+        }
+    }
+    extended
+}
+
+/// the running instance the decider proves, whose errors are E_j of a relaxed instance, or B and E of a compressed one
+#[derive(Clone, Copy, Debug)]
+enum RunningInstance<'a, G: Group> {
+    Relaxed(&'a RelaxedPLONKInstance<G>),
+    Compressed(&'a CompressedRelaxedPLONKInstance<G>),
+}
+
+impl<'a, G: Group> RunningInstance<'a, G> {
+    fn is_compressed(&self) -> bool {
+        matches!(self, Self::Compressed(_))
+    }
+
+    fn comm_W(&self) -> &'a [Commitment<G>] {
+        match self {
+            Self::Relaxed(U) => &U.comm_W,
+            Self::Compressed(U) => &U.comm_W,
+        }
+    }
+
+    fn comm_errors(&self) -> Vec<&'a Commitment<G>> {
+        match self {
+            Self::Relaxed(U) => U.comm_E.iter().collect(),
+            Self::Compressed(U) => vec![&U.comm_B, &U.comm_E],
+        }
+    }
+
+    fn X(&self) -> &'a [<G as Group>::ScalarField] {
+        match self {
+            Self::Relaxed(U) => &U.X,
+            Self::Compressed(U) => &U.X,
+        }
+    }
+
+    /// u, and β of a compressed instance or zero
+    fn u_beta(&self) -> (<G as Group>::ScalarField, <G as Group>::ScalarField) {
+        match self {
+            Self::Relaxed(U) => (U.u, <G as Group>::ScalarField::ZERO),
+            Self::Compressed(U) => (U.u, U.beta),
+        }
+    }
+
+    /// the claims of a compressed instance on Σ_x B(x) * gate(x), B(0) and B(1)
+    fn claims(&self) -> Vec<<G as Group>::ScalarField> {
+        match self {
+            Self::Relaxed(_) => vec![],
+            Self::Compressed(U) => vec![U.e, U.u, U.beta],
+        }
+    }
+
+    fn absorb_in(&self, transcript: &mut G::TE) {
+        match self {
+            Self::Relaxed(U) => transcript.absorb(b"U", *U),
+            Self::Compressed(U) => transcript.absorb(b"U", *U),
+        }
+    }
+}
+
+/// the indicators of the first, second and last rows
+fn row_indicators<F: Field>(n: usize) -> Vec<Vec<F>> {
+    [0, 1, n - 1]
+        .map(|row| {
+            let mut column = vec![F::ZERO; n];
+            column[row] = F::ONE;
+            column
+        })
+        .to_vec()
+}
+
+/// the vector padded with zeros to the given length, which leaves its commitment as it is
+fn pad<F: Field>(v: &[F], n: usize) -> Vec<F> {
+    let mut padded = v.to_vec();
+    padded.resize(n, F::ZERO);
+    padded
+}
+
+impl<G: Group, EE: EvaluationEngineTrait<G, CE = G::CE>> RelaxedPLONKSNARK<G, EE> {
+    /// the prover and verifier keys of a shape, whose number of constraints is a power of two
+    /// not below the size of its lookup table nor that of its memory
+    #[allow(clippy::type_complexity)]
+    pub fn setup(
+        ck: &CommitmentKey<G>,
+        S: &PLONKShape<G>,
+    ) -> Result<(ProverKey<G, EE>, VerifierKey<G, EE>), MyError> {
+        Self::setup_inner(ck, S, false)
+    }
+
+    /// the prover and verifier keys of a shape for its compressed instances
+    #[allow(clippy::type_complexity)]
+    pub fn setup_compressed(
+        ck: &CommitmentKey<G>,
+        S: &PLONKShape<G>,
+    ) -> Result<(ProverKey<G, EE>, VerifierKey<G, EE>), MyError> {
+        Self::setup_inner(ck, S, true)
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn setup_inner(
+        ck: &CommitmentKey<G>,
+        S: &PLONKShape<G>,
+        compressed: bool,
+    ) -> Result<(ProverKey<G, EE>, VerifierKey<G, EE>), MyError> {
+        let n = S.num_cons;
+        if n < 2 || !n.is_power_of_two() {
+            return Err(MyError::InvalidInputLength);
+        }
+        if S.lookup.as_ref().is_some_and(|l| l.table.len() > n) {
+            return Err(MyError::LookupError);
+        }
+        if S.memory.as_ref().is_some_and(|m| m.size > n) {
+            return Err(MyError::MemoryError);
+        }
+        let (pk_ee, vk_ee) = EE::setup(ck);
+        let layout = Layout::new(
+            &S.gate,
+            S.num_wire_types,
+            n,
+            S.lookup.as_ref().map(|l| l.wire),
+            S.memory.as_ref().map(|m| m.wires),
+            compressed,
+        );
+        let sigma = extend_sigma(&S.sigma, &layout, n)
+            .chunks(n)
+            .map(|c| {
+                c.iter()
+                    .map(|p| <G as Group>::ScalarField::from(*p as u64))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let lookup = S
+            .lookup
+            .iter()
+            .flat_map(|l| [l.selector.clone(), pad(&l.table, n)])
+            .collect::<Vec<_>>();
+        let memory = S
+            .memory
+            .iter()
+            .flat_map(|m| {
+                let mask = vec![<G as Group>::ScalarField::ONE; m.size];
+                [m.selector.clone(), pad(&mask, n)]
+            })
+            .collect::<Vec<_>>();
+
+        let commit = |columns: &[Vec<<G as Group>::ScalarField>]| {
+            columns
+                .par_iter()
+                .map(|c| G::CE::commit(ck, c))
+                .collect::<Vec<_>>()
+        };
+        let (comm_selectors, comm_sigma, comm_lookup, comm_memory) = (
+            commit(&S.selectors),
+            commit(&sigma),
+            commit(&lookup),
+            commit(&memory),
+        );
+
+        let mut hasher = Keccak256::new();
+        hasher.update(S.digest());
+        comm_selectors
+            .iter()
+            .chain(&comm_sigma)
+            .chain(&comm_lookup)
+            .chain(&comm_memory)
+            .for_each(|c| hasher.update(c.to_transcript_bytes()));
+        let digest = <G as Group>::ScalarField::from_le_bytes_mod_order(&hasher.finalize());
+
+        Ok((
+            ProverKey {
+                pk_ee,
+                S: S.clone(),
+                layout: layout.clone(),
+                sigma,
+                lookup,
+                memory,
+                comm_selectors: comm_selectors.clone(),
+                comm_sigma: comm_sigma.clone(),
+                comm_lookup: comm_lookup.clone(),
+                comm_memory: comm_memory.clone(),
+                vk_digest: digest,
+            },
+            VerifierKey {
+                vk_ee,
+                num_cons: n,
+                num_public_input: S.num_public_input,
+                gate: S.gate.clone(),
+                layout,
+                comm_selectors,
+                comm_sigma,
+                comm_lookup,
+                comm_memory,
+                digest,
+            },
+        ))
+    }
+
+    /// proves that the relaxed instance is satisfied by the given witness, for a shape without lookup
+    pub fn prove(
+        ck: &CommitmentKey<G>,
+        pk: &ProverKey<G, EE>,
+        U: &RelaxedPLONKInstance<G>,
+        W: &RelaxedPLONKWitness<G>,
+    ) -> Result<Self, MyError> {
+        Self::prove_inner(
+            ck,
+            pk,
+            RunningInstance::Relaxed(U),
+            (&W.W, &W.E),
+            None,
+            None,
+        )
+    }
+
+    /// proves that the compressed instance is satisfied by the given witness, with keys of `setup_compressed`
+    pub fn prove_compressed(
+        ck: &CommitmentKey<G>,
+        pk: &ProverKey<G, EE>,
+        U: &CompressedRelaxedPLONKInstance<G>,
+        W: &CompressedRelaxedPLONKWitness<G>,
+    ) -> Result<Self, MyError> {
+        Self::prove_inner(
+            ck,
+            pk,
+            RunningInstance::Compressed(U),
+            (&W.W, &[W.B.clone(), W.E.clone()]),
+            None,
+            None,
+        )
+    }
+
+    /// proves that the relaxed instance and the relaxed lookup folded with it are satisfied by the given witnesses
+    pub fn prove_with_lookup(
+        ck: &CommitmentKey<G>,
+        pk: &ProverKey<G, EE>,
+        U: &RelaxedPLONKInstance<G>,
+        W: &RelaxedPLONKWitness<G>,
+        L: &RelaxedLookupInstance<G>,
+        LW: &RelaxedLookupWitness<G>,
+    ) -> Result<Self, MyError> {
+        Self::prove_inner(
+            ck,
+            pk,
+            RunningInstance::Relaxed(U),
+            (&W.W, &W.E),
+            Some((L, LW)),
+            None,
+        )
+    }
+
+    /// proves that the relaxed instance and the relaxed memory folded with it are satisfied by the given witnesses
+    pub fn prove_with_memory(
+        ck: &CommitmentKey<G>,
+        pk: &ProverKey<G, EE>,
+        U: &RelaxedPLONKInstance<G>,
+        W: &RelaxedPLONKWitness<G>,
+        M: &RelaxedMemoryInstance<G>,
+        MW: &RelaxedMemoryWitness<G>,
+    ) -> Result<Self, MyError> {
+        Self::prove_inner(
+            ck,
+            pk,
+            RunningInstance::Relaxed(U),
+            (&W.W, &W.E),
+            None,
+            Some((M, MW)),
+        )
+    }
+
+    #[allow(clippy::type_complexity)]
+    /// the witness is given as its wires and its errors, E_j of a relaxed instance or B and E of a compressed one
+    fn prove_inner(
+        ck: &CommitmentKey<G>,
+        pk: &ProverKey<G, EE>,
+        U: RunningInstance<G>,
+        (W, E): (
+            &[Vec<<G as Group>::ScalarField>],
+            &[Vec<<G as Group>::ScalarField>],
+        ),
+        lookup: Option<(&RelaxedLookupInstance<G>, &RelaxedLookupWitness<G>)>,
+        memory: Option<(&RelaxedMemoryInstance<G>, &RelaxedMemoryWitness<G>)>,
+    ) -> Result<Self, MyError> {
+        let (S, layout) = (&pk.S, &pk.layout);
+        let n = S.num_cons;
+        if layout.compressed != U.is_compressed()
+            || W.len() != layout.num_wires
+            || E.len() != layout.num_errors
+            || W.iter().chain(E).any(|c| c.len() != n)
+        {
+            return Err(MyError::WitnessError);
+        }
+        if U.X().len() != S.num_public_input {
+            return Err(MyError::PublicIntputError);
+        }
+        if layout.lookup.is_some() != lookup.is_some() {
+            return Err(MyError::LookupError);
+        }
+        if layout.memory.is_some() != memory.is_some() {
+            return Err(MyError::MemoryError);
+        }
+        let F = |x: usize| <G as Group>::ScalarField::from(x as u64);
+        let commit = |columns: &[Vec<<G as Group>::ScalarField>]| {
+            columns
+                .par_iter()
+                .map(|c| G::CE::commit(ck, c))
+                .collect::<Vec<_>>()
+        };
+
+        let (u, beta_U) = U.u_beta();
+
+        let mut transcript = G::TE::new(b"RelaxedPLONKSNARK");
+        transcript.absorb(b"vk", &pk.vk_digest);
+        U.absorb_in(&mut transcript);
+        if let Some((L, _)) = lookup {
+            transcript.absorb(b"L", L);
+        }
+        if let Some((M, _)) = memory {
+            transcript.absorb(b"M", M);
+        }
+
+        // the wires under the wiring, with B of a compressed instance
+        let perm_wires = W
+            .iter()
+            .chain(E.iter().take(usize::from(layout.compressed)))
+            .collect::<Vec<_>>();
+        let rotated = layout
+            .rotations
+            .iter()
+            .map(|(wire, rotation)| {
+                (0..n)
+                    .map(|row| perm_wires[*wire][(row + rotation) % n])
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let comm_rotated = commit(&rotated);
+        comm_rotated
+            .iter()
+            .for_each(|c| transcript.absorb(b"comm_rotated", c));
+
+        // the fractional sums of the wiring, leaving claims on the wires and σ at ρ
+        let beta = transcript.squeeze(b"beta")?;
+        let gamma = transcript.squeeze(b"gamma")?;
+        let perm_columns = perm_wires
+            .into_iter()
+            .chain(&rotated)
+            .map(|c| c.as_slice())
+            .collect::<Vec<_>>();
+        let id = (0..layout.num_perm())
+            .map(|c| (0..n).map(|row| F(c * n + row)).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let (perm, rho_perm) = PermutationProof::prove::<G>(
+            &mut transcript,
+            &perm_columns,
+            &id,
+            &pk.sigma,
+            &beta,
+            &gamma,
+        )?;
+
+        // the fractional sums of the lookup, leaving claims on q, t, m, E_h, E_g and w at ρ
+        let (logup, lookup_columns, eq_lookup) = match (lookup, layout.lookup) {
+            (Some((L, LW)), Some(wire)) => {
+                let table_len = pk.S.lookup.as_ref().map_or(0, |l| l.table.len());
+                if LW.E_h.len() != n || [&LW.m, &LW.E_g].iter().any(|v| v.len() != table_len) {
+                    return Err(MyError::WitnessError);
+                }
+                let columns = [
+                    pk.lookup[0].clone(),
+                    pk.lookup[1].clone(),
+                    pad(&LW.m, n),
+                    LW.E_h.clone(),
+                    pad(&LW.E_g, n),
+                ];
+                let (logup, rho) = LogUpProof::prove::<G>(
+                    &mut transcript,
+                    [
+                        &columns[0],
+                        &columns[1],
+                        &columns[2],
+                        &columns[3],
+                        &columns[4],
+                        &W[wire],
+                    ],
+                    &u,
+                    &L.alpha,
+                )?;
+                (Some(logup), columns.to_vec(), vec![eq_table(&rho)])
+            }
+            _ => (None, vec![], vec![]),
+        };
+
+        // the fractional sums of the memory, leaving claims on its columns and the accessing wires at ρ
+        let (memory_check, memory_columns, eq_memory) = match (memory, layout.memory) {
+            (Some((M, MW)), Some(wires)) => {
+                let size = pk.S.memory.as_ref().map_or(0, |m| m.size);
+                if MW.E[..2].iter().any(|v| v.len() != n)
+                    || MW.state.iter().chain(&MW.E[2..]).any(|v| v.len() != size)
+                {
+                    return Err(MyError::WitnessError);
+                }
+                let columns = [
+                    pk.memory[0].clone(),
+                    pk.memory[1].clone(),
+                    MW.E[0].clone(),
+                    MW.E[1].clone(),
+                    pad(&MW.state[0], n),
+                    pad(&MW.state[1], n),
+                    pad(&MW.state[2], n),
+                    pad(&MW.state[3], n),
+                    pad(&MW.E[2], n),
+                    pad(&MW.E[3], n),
+                ];
+                let mut slices = columns.iter().map(|c| c.as_slice()).collect::<Vec<_>>();
+                slices.extend(wires.map(|w| W[w].as_slice()));
+                let (memory_check, rho) = MemoryCheckProof::prove::<G>(
+                    &mut transcript,
+                    slices.try_into().map_err(|_| MyError::WitnessError)?,
+                    &u,
+                    &M.challenges,
+                )?;
+                (Some(memory_check), columns.to_vec(), vec![eq_table(&rho)])
+            }
+            _ => (None, vec![], vec![]),
+        };
+        let lambda = transcript.squeeze(b"lambda")?;
+        let lambda_powers = PLONKShape::<G>::challenge_powers(
+            &lambda,
+            perm.evals.len()
+                + logup.as_ref().map_or(0, |l| l.evals.len())
+                + memory_check.as_ref().map_or(0, |m| m.evals.len())
+                + U.claims().len(),
+        );
+
+        // one sumcheck of the zero-check and the batched claims of the fractional sums
+        let tau = (0..n.trailing_zeros())
+            .map(|_| transcript.squeeze(b"tau"))
+            .collect::<Result<Vec<_>, MyError>>()?;
+
+        let mut pi = U.X().to_vec();
+        pi.resize(n, <G as Group>::ScalarField::ZERO);
+        let rows = if layout.compressed {
+            row_indicators(n)
+        } else {
+            vec![]
+        };
+        let committed = S
+            .selectors
+            .iter()
+            .chain(&pk.sigma)
+            .chain(W)
+            .chain(E)
+            .chain(&rotated)
+            .chain(&lookup_columns)
+            .chain(&memory_columns)
+            .cloned()
+            .collect::<Vec<_>>();
+        let tables = [
+            vec![eq_table(&tau), pi, eq_table(&rho_perm)],
+            eq_lookup,
+            eq_memory,
+            rows,
+            committed.clone(),
+        ]
+        .concat();
+        let (sc_proof, point, values) =
+            SumcheckProof::prove::<G>(&mut transcript, tables, layout.degree(&S.gate), |v| {
+                layout.compose(&S.gate, n, &u, &beta_U, &lambda_powers, v)
+            })?;
+
+        // the committed columns at the point of the sumcheck, opened at once
+        let evals = values[layout.committed()..].to_vec();
+        evals.iter().for_each(|e| transcript.absorb(b"eval", e));
+        let rho = transcript.squeeze(b"rho")?;
+        let rho_powers = [
+            vec![<G as Group>::ScalarField::ONE],
+            PLONKShape::<G>::challenge_powers(&rho, evals.len() - 1),
+        ]
+        .concat();
+        let joint_table = (0..n)
+            .into_par_iter()
+            .map(|row| {
+                committed
+                    .iter()
+                    .zip(&rho_powers)
+                    .map(|(c, p)| c[row] * p)
+                    .sum()
+            })
+            .collect::<Vec<_>>();
+        let joint_eval = evals.iter().zip(&rho_powers).map(|(e, p)| *e * p).sum();
+        let comms = Self::committed_comms(
+            (
+                &pk.comm_selectors,
+                &pk.comm_sigma,
+                &pk.comm_lookup,
+                &pk.comm_memory,
+            ),
+            (U, lookup.map(|(L, _)| L), memory.map(|(M, _)| M)),
+            &comm_rotated,
+        );
+        let eval_arg = MultilinearEvaluationArgument::prove(
+            ck,
+            &pk.pk_ee,
+            &mut transcript,
+            &Self::joint_comm(&comms, &rho_powers),
+            &joint_table,
+            &point,
+            &joint_eval,
+        )?;
+
+        Ok(Self {
+            comm_rotated,
+            perm,
+            logup,
+            memory: memory_check,
+            sc_proof,
+            evals,
+            eval_arg,
+        })
+    }
+
+    /// the commitments to the committed columns, in the order of the layout
+    #[allow(clippy::type_complexity)]
+    fn committed_comms(
+        (comm_selectors, comm_sigma, comm_lookup, comm_memory): (
+            &[Commitment<G>],
+            &[Commitment<G>],
+            &[Commitment<G>],
+            &[Commitment<G>],
+        ),
+        (U, L, M): (
+            RunningInstance<G>,
+            Option<&RelaxedLookupInstance<G>>,
+            Option<&RelaxedMemoryInstance<G>>,
+        ),
+        comm_rotated: &[Commitment<G>],
+    ) -> Vec<Commitment<G>> {
+        comm_selectors
+            .iter()
+            .chain(comm_sigma)
+            .chain(U.comm_W())
+            .chain(U.comm_errors())
+            .chain(comm_rotated)
+            .chain(comm_lookup)
+            .chain(
+                L.into_iter()
+                    .flat_map(|L| [&L.comm_m, &L.comm_E_h, &L.comm_E_g]),
+            )
+            .chain(comm_memory)
+            .chain(M.into_iter().flat_map(|M| {
+                [&M.comm_E[0], &M.comm_E[1]]
+                    .into_iter()
+                    .chain(&M.comm_state)
+                    .chain(&M.comm_E[2..])
+            }))
+            .cloned()
+            .collect()
+    }
+
+    /// Σ_i ρ^i * comm_i
+    fn joint_comm(
+        comms: &[Commitment<G>],
+        rho_powers: &[<G as Group>::ScalarField],
+    ) -> Commitment<G> {
+        comms
+            .iter()
+            .zip(rho_powers)
+            .fold(Commitment::<G>::default(), |acc, (c, p)| {
+                acc + c.clone() * *p
+            })
+    }
+
+    /// verifies the proof against the relaxed instance, for a shape without lookup
+    pub fn verify(
+        &self,
+        vk: &VerifierKey<G, EE>,
+        U: &RelaxedPLONKInstance<G>,
+    ) -> Result<(), MyError> {
+        self.verify_inner(vk, RunningInstance::Relaxed(U), None, None)
+    }
+
+    /// verifies the proof against the compressed instance, with keys of `setup_compressed`
+    pub fn verify_compressed(
+        &self,
+        vk: &VerifierKey<G, EE>,
+        U: &CompressedRelaxedPLONKInstance<G>,
+    ) -> Result<(), MyError> {
+        self.verify_inner(vk, RunningInstance::Compressed(U), None, None)
+    }
+
+    /// verifies the proof against the relaxed instance and the relaxed lookup folded with it
+    pub fn verify_with_lookup(
+        &self,
+        vk: &VerifierKey<G, EE>,
+        U: &RelaxedPLONKInstance<G>,
+        L: &RelaxedLookupInstance<G>,
+    ) -> Result<(), MyError> {
+        self.verify_inner(vk, RunningInstance::Relaxed(U), Some(L), None)
+    }
+
+    /// verifies the proof against the relaxed instance and the relaxed memory folded with it, whose first memory
+    /// must be the one committed to in comm_init, (v_init, t_init), which the verifier knows on its own
+    pub fn verify_with_memory(
+        &self,
+        vk: &VerifierKey<G, EE>,
+        U: &RelaxedPLONKInstance<G>,
+        M: &RelaxedMemoryInstance<G>,
+        comm_init: &[Commitment<G>; 2],
+    ) -> Result<(), MyError> {
+        if M.comm_first != *comm_init {
+            return Err(MyError::MemoryError);
+        }
+        self.verify_inner(vk, RunningInstance::Relaxed(U), None, Some(M))
+    }
+
+    fn verify_inner(
+        &self,
+        vk: &VerifierKey<G, EE>,
+        U: RunningInstance<G>,
+        L: Option<&RelaxedLookupInstance<G>>,
+        M: Option<&RelaxedMemoryInstance<G>>,
+    ) -> Result<(), MyError> {
+        let layout = &vk.layout;
+        let n = vk.num_cons;
+        if U.X().len() != vk.num_public_input {
+            return Err(MyError::PublicIntputError);
+        }
+        if layout.lookup.is_some() != L.is_some() {
+            return Err(MyError::LookupError);
+        }
+        if layout.memory.is_some() != M.is_some() {
+            return Err(MyError::MemoryError);
+        }
+        if layout.compressed != U.is_compressed()
+            || U.comm_W().len() != layout.num_wires
+            || U.comm_errors().len() != layout.num_errors
+            || self.comm_rotated.len() != layout.rotations.len()
+            || self.logup.is_some() != L.is_some()
+            || self.memory.is_some() != M.is_some()
+            || self.evals.len() != layout.len() - layout.committed()
+        {
+            return Err(MyError::ProofVerifyError);
+        }
+
+        let (u, beta_U) = U.u_beta();
+
+        let mut transcript = G::TE::new(b"RelaxedPLONKSNARK");
+        transcript.absorb(b"vk", &vk.digest);
+        U.absorb_in(&mut transcript);
+        if let Some(L) = L {
+            transcript.absorb(b"L", L);
+        }
+        if let Some(M) = M {
+            transcript.absorb(b"M", M);
+        }
+        self.comm_rotated
+            .iter()
+            .for_each(|c| transcript.absorb(b"comm_rotated", c));
+
+        // id at a point r of the rows is c * n + Σ_k 2^k * r_k for the c-th column
+        let num_vars = n.trailing_zeros() as usize;
+        let id = |r: &[<G as Group>::ScalarField]| {
+            let index = r
+                .iter()
+                .rev()
+                .fold(<G as Group>::ScalarField::ZERO, |acc, r| acc.double() + r);
+            (0..layout.num_perm())
+                .map(|c| <G as Group>::ScalarField::from((c * n) as u64) + index)
+                .collect::<Vec<_>>()
+        };
+        let beta = transcript.squeeze(b"beta")?;
+        let gamma = transcript.squeeze(b"gamma")?;
+        let (rho_perm, perm_evals) = self.perm.verify::<G>(
+            &mut transcript,
+            num_vars,
+            layout.num_perm(),
+            id,
+            &beta,
+            &gamma,
+        )?;
+
+        let (rho_lookup, lookup_evals) = match (&self.logup, L) {
+            (Some(logup), Some(L)) => {
+                let (rho, evals) = logup.verify::<G>(&mut transcript, num_vars, &u, &L.alpha)?;
+                (Some(rho), evals)
+            }
+            _ => (None, &[][..]),
+        };
+        let (rho_memory, memory_evals) = match (&self.memory, M) {
+            (Some(memory), Some(M)) => {
+                let (rho, evals) =
+                    memory.verify::<G>(&mut transcript, num_vars, &u, &M.challenges)?;
+                (Some(rho), evals)
+            }
+            _ => (None, &[][..]),
+        };
+        let lambda = transcript.squeeze(b"lambda")?;
+        let claims = U.claims();
+        let lambda_powers = PLONKShape::<G>::challenge_powers(
+            &lambda,
+            perm_evals.len() + lookup_evals.len() + memory_evals.len() + claims.len(),
+        );
+        let claim = perm_evals
+            .iter()
+            .chain(lookup_evals)
+            .chain(memory_evals)
+            .chain(&claims)
+            .zip(&lambda_powers)
+            .map(|(e, l)| *e * l)
+            .sum();
+
+        let tau = (0..num_vars)
+            .map(|_| transcript.squeeze(b"tau"))
+            .collect::<Result<Vec<_>, MyError>>()?;
+
+        let (value, point) =
+            self.sc_proof
+                .verify::<G>(&mut transcript, claim, num_vars, layout.degree(&vk.gate))?;
+
+        // eq(τ, r), the public input at r, eq(ρ, r) of the wiring, the lookup and the memory, and the indicators of
+        // the rows of a compressed instance at r
+        let indicator = |row: usize| {
+            let bits = (0..num_vars)
+                .map(|k| <G as Group>::ScalarField::from(((row >> k) & 1) as u64))
+                .collect::<Vec<_>>();
+            eq_eval(&bits, &point)
+        };
+        let pi = U
+            .X()
+            .iter()
+            .enumerate()
+            .fold(<G as Group>::ScalarField::ZERO, |acc, (row, x)| {
+                acc + *x * indicator(row)
+            });
+        let eq_lookup = rho_lookup
+            .iter()
+            .chain(&rho_memory)
+            .map(|rho| eq_eval(rho, &point))
+            .collect::<Vec<_>>();
+        let rows = if layout.compressed {
+            [0, 1, n - 1].map(indicator).to_vec()
+        } else {
+            vec![]
+        };
+        let values = [
+            vec![eq_eval(&tau, &point), pi, eq_eval(&rho_perm, &point)],
+            eq_lookup,
+            rows,
+            self.evals.clone(),
+        ]
+        .concat();
+        if layout.compose(&vk.gate, n, &u, &beta_U, &lambda_powers, &values) != value {
+            return Err(MyError::ProofVerifyError);
+        }
+
+        self.evals
+            .iter()
+            .for_each(|e| transcript.absorb(b"eval", e));
+        let rho = transcript.squeeze(b"rho")?;
+        let rho_powers = [
+            vec![<G as Group>::ScalarField::ONE],
+            PLONKShape::<G>::challenge_powers(&rho, self.evals.len() - 1),
+        ]
+        .concat();
+        let joint_eval = self
+            .evals
+            .iter()
+            .zip(&rho_powers)
+            .map(|(e, p)| *e * p)
+            .sum();
+        let comms = Self::committed_comms(
+            (
+                &vk.comm_selectors,
+                &vk.comm_sigma,
+                &vk.comm_lookup,
+                &vk.comm_memory,
+            ),
+            (U, L, M),
+            &self.comm_rotated,
+        );
+        self.eval_arg.verify(
+            &vk.vk_ee,
+            &mut transcript,
+            &Self::joint_comm(&comms, &rho_powers),
+            &point,
+            &joint_eval,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        gate::GateTerm,
+        lookup::{tests::range_shape, LookupNIFS, LookupWitness},
+        memory::{
+            tests::{counter_shape, counter_witness},
+            MemoryNIFS, MemoryWitness,
+        },
+        nifs::{
+            tests::{random_shape, random_witness},
+            CompressedNIFS, NIFS,
+        },
+        plonk::{PLONKInstance, PLONKWitness},
+        provider::{grumpkin::Grumpkin, kzg::KZGEvaluationEngine},
+        secondary::ipa::IPAEvaluationEngine,
+    };
+    use ark_bn254::Bn254;
+    use ark_ff::UniformRand;
+    use jf_utils::test_rng;
+    use rand::Rng;
+
+    fn test_relaxed_plonk_snark_with<G: Group, EE: EvaluationEngineTrait<G, CE = G::CE>>() {
+        let rng = &mut test_rng();
+        let (num_cons, num_public_input) = (8, 2);
+        let ck = G::CE::setup(rng, num_cons);
+        let pp_digest = <G as Group>::ScalarField::rand(rng);
+
+        let S = random_shape::<G>(rng, num_cons, num_public_input);
+        let (pk, vk) = RelaxedPLONKSNARK::<G, EE>::setup(&ck, &S).unwrap();
+
+        let mut running_U = RelaxedPLONKInstance::default(&ck, &S);
+        let mut running_W = RelaxedPLONKWitness::default(&S);
+        for _ in 0..3 {
+            let (W, X) = random_witness(rng, &S);
+            let U = PLONKInstance::new(&S, &W.commit(&ck), &X).unwrap();
+            (_, (running_U, running_W)) =
+                NIFS::prove(&ck, &pp_digest, &S, &running_U, &running_W, &U, &W).unwrap();
+        }
+        S.is_sat_relaxed(&ck, &running_U, &running_W).unwrap();
+
+        let snark = RelaxedPLONKSNARK::prove(&ck, &pk, &running_U, &running_W).unwrap();
+        snark.verify(&vk, &running_U).unwrap();
+
+        // neither another u nor another public input verifies
+        let mut U = running_U.clone();
+        U.u += <G as Group>::ScalarField::ONE;
+        assert!(snark.verify(&vk, &U).is_err());
+        let mut U = running_U.clone();
+        U.X[0] += <G as Group>::ScalarField::ONE;
+        assert!(snark.verify(&vk, &U).is_err());
+    }
+
+    #[test]
+    fn test_relaxed_plonk_snark() {
+        test_relaxed_plonk_snark_with::<Bn254, KZGEvaluationEngine<Bn254>>();
+        test_relaxed_plonk_snark_with::<Grumpkin, IPAEvaluationEngine<Grumpkin>>();
+    }
+
+    fn test_compressed_plonk_snark_with<G: Group, EE: EvaluationEngineTrait<G, CE = G::CE>>() {
+        let rng = &mut test_rng();
+        let (num_cons, num_public_input) = (8, 2);
+        let ck = G::CE::setup(rng, num_cons);
+        let pp_digest = <G as Group>::ScalarField::rand(rng);
+
+        let S = random_shape::<G>(rng, num_cons, num_public_input);
+        let (pk, vk) = RelaxedPLONKSNARK::<G, EE>::setup_compressed(&ck, &S).unwrap();
+
+        let mut running_U = CompressedRelaxedPLONKInstance::default(&ck, &S);
+        let mut running_W = CompressedRelaxedPLONKWitness::default(&S);
+        for _ in 0..3 {
+            let (W, X) = random_witness(rng, &S);
+            let U = PLONKInstance::new(&S, &W.commit(&ck), &X).unwrap();
+            (_, (running_U, running_W)) =
+                CompressedNIFS::prove(&ck, &pp_digest, &S, &running_U, &running_W, &U, &W).unwrap();
+        }
+        S.is_sat_compressed(&ck, &running_U, &running_W).unwrap();
+
+        let snark = RelaxedPLONKSNARK::prove_compressed(&ck, &pk, &running_U, &running_W).unwrap();
+        snark.verify_compressed(&vk, &running_U).unwrap();
+
+        // neither another β nor another e verifies
+        let mut U = running_U.clone();
+        U.beta += <G as Group>::ScalarField::ONE;
+        assert!(snark.verify_compressed(&vk, &U).is_err());
+        let mut U = running_U.clone();
+        U.e += <G as Group>::ScalarField::ONE;
+        assert!(snark.verify_compressed(&vk, &U).is_err());
+
+        // the keys of relaxed instances take no compressed one
+        let (pk_relaxed, _) = RelaxedPLONKSNARK::<G, EE>::setup(&ck, &S).unwrap();
+        assert_eq!(
+            RelaxedPLONKSNARK::prove_compressed(&ck, &pk_relaxed, &running_U, &running_W).err(),
+            Some(MyError::WitnessError)
+        );
+
+        // B no longer follows its recurrence on the first row
+        let (mut U, mut W) = (running_U.clone(), running_W.clone());
+        W.E[0] += <G as Group>::ScalarField::ONE;
+        U.comm_E = G::CE::commit(&ck, &W.E);
+        assert_eq!(
+            S.is_sat_compressed(&ck, &U, &W),
+            Err(MyError::UnSatPower(0))
+        );
+        let snark = RelaxedPLONKSNARK::prove_compressed(&ck, &pk, &U, &W).unwrap();
+        assert!(snark.verify_compressed(&vk, &U).is_err());
+    }
+
+    #[test]
+    fn test_compressed_plonk_snark() {
+        test_compressed_plonk_snark_with::<Bn254, KZGEvaluationEngine<Bn254>>();
+        test_compressed_plonk_snark_with::<Grumpkin, IPAEvaluationEngine<Grumpkin>>();
+    }
+
+    /// fibonacci over the rows: q_add * (w_0 + w_1 - w_2) + q_next * (w_0[+1] - w_1), with w_2 wired to w_1 of the
+    /// next row
+    fn fibonacci_shape<G: Group>(num_cons: usize) -> PLONKShape<G> {
+        let (w, one) = (Column::Wire, <G as Group>::ScalarField::ONE);
+        let gate = CustomGate::new(
+            2,
+            vec![
+                GateTerm::new(one, Some(0), &[w(0)]),
+                GateTerm::new(one, Some(0), &[w(1)]),
+                GateTerm::new(-one, Some(0), &[w(2)]),
+                GateTerm::new(one, Some(1), &[Column::RotatedWire(0, 1)]),
+                GateTerm::new(-one, Some(1), &[w(1)]),
+            ],
+        );
+        let q_next = (0..num_cons)
+            .map(|i| {
+                if i + 1 < num_cons {
+                    one
+                } else {
+                    <G as Group>::ScalarField::ZERO
+                }
+            })
+            .collect();
+        let mut sigma = (0..3)
+            .map(|i| (0..num_cons).map(|j| i * num_cons + j).collect())
+            .collect::<Vec<Vec<usize>>>();
+        for i in 0..num_cons - 1 {
+            sigma[2][i] = num_cons + i + 1;
+            sigma[1][i + 1] = 2 * num_cons + i;
+        }
+        PLONKShape::new_with_gate(
+            num_cons,
+            3,
+            0,
+            gate,
+            vec![vec![one; num_cons], q_next],
+            &sigma,
+        )
+        .unwrap()
+    }
+
+    fn test_rotated_wire_snark_with<G: Group, EE: EvaluationEngineTrait<G, CE = G::CE>>() {
+        let rng = &mut test_rng();
+        let num_cons = 4;
+        let ck = G::CE::setup(rng, num_cons);
+        let pp_digest = <G as Group>::ScalarField::rand(rng);
+
+        let S = fibonacci_shape::<G>(num_cons);
+        let (pk, vk) = RelaxedPLONKSNARK::<G, EE>::setup(&ck, &S).unwrap();
+        let fibonacci = |a: <G as Group>::ScalarField, b: <G as Group>::ScalarField| {
+            let (mut w_0, mut w_1) = (vec![a], vec![b]);
+            for i in 1..num_cons {
+                w_0.push(w_1[i - 1]);
+                w_1.push(w_0[i - 1] + w_1[i - 1]);
+            }
+            let w_2 = w_0.iter().zip(&w_1).map(|(a, b)| *a + b).collect();
+            PLONKWitness::new(&S, &[w_0, w_1, w_2]).unwrap()
+        };
+        let fold = |W: &PLONKWitness<G>| {
+            let U = PLONKInstance::new(&S, &W.commit(&ck), &[]).unwrap();
+            let (running_U, running_W) = (
+                RelaxedPLONKInstance::default(&ck, &S),
+                RelaxedPLONKWitness::default(&S),
+            );
+            let (_, (U, W)) =
+                NIFS::prove(&ck, &pp_digest, &S, &running_U, &running_W, &U, W).unwrap();
+            (U, W)
+        };
+
+        let (U, W) = fold(&fibonacci(
+            <G as Group>::ScalarField::rand(rng),
+            <G as Group>::ScalarField::rand(rng),
+        ));
+        S.is_sat_relaxed(&ck, &U, &W).unwrap();
+        let snark = RelaxedPLONKSNARK::prove(&ck, &pk, &U, &W).unwrap();
+        snark.verify(&vk, &U).unwrap();
+
+        // the last row still satisfies the gate, but w_1 no longer copies w_2 of the row above
+        let mut W = fibonacci(
+            <G as Group>::ScalarField::rand(rng),
+            <G as Group>::ScalarField::rand(rng),
+        );
+        W.W[1][num_cons - 1] += <G as Group>::ScalarField::ONE;
+        W.W[2][num_cons - 1] += <G as Group>::ScalarField::ONE;
+        let (U, W) = fold(&W);
+        assert!(S.is_sat_relaxed(&ck, &U, &W).is_err());
+        let snark = RelaxedPLONKSNARK::prove(&ck, &pk, &U, &W).unwrap();
+        assert!(snark.verify(&vk, &U).is_err());
+    }
+
+    #[test]
+    fn test_rotated_wire_snark() {
+        test_rotated_wire_snark_with::<Bn254, KZGEvaluationEngine<Bn254>>();
+        test_rotated_wire_snark_with::<Grumpkin, IPAEvaluationEngine<Grumpkin>>();
+    }
+
+    fn test_lookup_snark_with<G: Group, EE: EvaluationEngineTrait<G, CE = G::CE>>() {
+        let rng = &mut test_rng();
+        let num_cons = 16;
+        let ck = G::CE::setup(rng, num_cons);
+        let pp_digest = <G as Group>::ScalarField::rand(rng);
+
+        let S = range_shape::<G>(num_cons);
+        let (pk, vk) = RelaxedPLONKSNARK::<G, EE>::setup(&ck, &S).unwrap();
+
+        let mut running_U = RelaxedPLONKInstance::default(&ck, &S);
+        let mut running_W = RelaxedPLONKWitness::default(&S);
+        let mut running_L = RelaxedLookupInstance::default(&ck, &S);
+        let mut running_LW = RelaxedLookupWitness::default(&S);
+        for _ in 0..3 {
+            let w_0 = (0..num_cons)
+                .map(|_| <G as Group>::ScalarField::from(rng.gen_range(0..16u64)))
+                .collect::<Vec<_>>();
+            let w_1 = (0..num_cons)
+                .map(|_| <G as Group>::ScalarField::rand(rng))
+                .collect::<Vec<_>>();
+            let w_2 = w_0.iter().zip(&w_1).map(|(a, b)| *a + b).collect();
+            let W = PLONKWitness::new(&S, &[w_0, w_1, w_2]).unwrap();
+            let U = PLONKInstance::new(&S, &W.commit(&ck), &[]).unwrap();
+            let (L, LW) = LookupWitness::new(&ck, &pp_digest, &S, &U, &W).unwrap();
+            (_, (running_U, running_W), (running_L, running_LW)) = LookupNIFS::prove(
+                &ck,
+                &pp_digest,
+                &S,
+                (&running_U, &running_W),
+                (&running_L, &running_LW),
+                (&U, &W),
+                (&L, &LW),
+            )
+            .unwrap();
+        }
+
+        let snark = RelaxedPLONKSNARK::prove_with_lookup(
+            &ck,
+            &pk,
+            &running_U,
+            &running_W,
+            &running_L,
+            &running_LW,
+        )
+        .unwrap();
+        snark
+            .verify_with_lookup(&vk, &running_U, &running_L)
+            .unwrap();
+
+        // the lookup is neither dropped nor taken with another α
+        assert_eq!(
+            RelaxedPLONKSNARK::<G, EE>::prove(&ck, &pk, &running_U, &running_W).err(),
+            Some(MyError::LookupError)
+        );
+        assert!(snark.verify(&vk, &running_U).is_err());
+        let mut L = running_L.clone();
+        L.alpha += <G as Group>::ScalarField::ONE;
+        assert!(snark.verify_with_lookup(&vk, &running_U, &L).is_err());
+
+        // an error term off the sum leaves a root other than zero
+        let mut LW = running_LW.clone();
+        LW.E_h[0] += <G as Group>::ScalarField::ONE;
+        assert_eq!(
+            RelaxedPLONKSNARK::<G, EE>::prove_with_lookup(
+                &ck, &pk, &running_U, &running_W, &running_L, &LW
+            )
+            .err(),
+            Some(MyError::UnSatLookupSum)
+        );
+    }
+
+    #[test]
+    fn test_lookup_snark() {
+        test_lookup_snark_with::<Bn254, KZGEvaluationEngine<Bn254>>();
+        test_lookup_snark_with::<Grumpkin, IPAEvaluationEngine<Grumpkin>>();
+    }
+
+    fn test_memory_snark_with<G: Group, EE: EvaluationEngineTrait<G, CE = G::CE>>() {
+        let rng = &mut test_rng();
+        let (num_cons, size) = (8, 4);
+        let ck = G::CE::setup(rng, num_cons);
+        let pp_digest = <G as Group>::ScalarField::rand(rng);
+
+        let S = counter_shape::<G>(num_cons, size);
+        let (pk, vk) = RelaxedPLONKSNARK::<G, EE>::setup(&ck, &S).unwrap();
+        let init = [
+            (0..size)
+                .map(|_| <G as Group>::ScalarField::rand(rng))
+                .collect::<Vec<_>>(),
+            vec![<G as Group>::ScalarField::ZERO; size],
+        ];
+        let mut memory = init.clone();
+
+        let mut running_U = RelaxedPLONKInstance::default(&ck, &S);
+        let mut running_W = RelaxedPLONKWitness::default(&S);
+        let mut running_M = RelaxedMemoryInstance::default(&ck, &S, &init).unwrap();
+        let mut running_MW = RelaxedMemoryWitness::default(&S);
+        for _ in 0..3 {
+            let before = memory.clone();
+            let W = counter_witness(rng, &S, &mut memory);
+            let U = PLONKInstance::new(&S, &W.commit(&ck), &[]).unwrap();
+            let (M, MW) = MemoryWitness::new(&ck, &pp_digest, &S, &U, &W, &before).unwrap();
+            (_, (running_U, running_W), (running_M, running_MW)) = MemoryNIFS::prove(
+                &ck,
+                &pp_digest,
+                &S,
+                (&running_U, &running_W),
+                (&running_M, &running_MW),
+                (&U, &W),
+                (&M, &MW),
+            )
+            .unwrap();
+        }
+
+        let snark = RelaxedPLONKSNARK::prove_with_memory(
+            &ck,
+            &pk,
+            &running_U,
+            &running_W,
+            &running_M,
+            &running_MW,
+        )
+        .unwrap();
+        let comm_init = [&init[0], &init[1]].map(|v| G::CE::commit(&ck, v));
+        snark
+            .verify_with_memory(&vk, &running_U, &running_M, &comm_init)
+            .unwrap();
+
+        // the memory must start from the one the verifier expects
+        let mut other = init.clone();
+        other[0][0] += <G as Group>::ScalarField::ONE;
+        let comm_other = [&other[0], &other[1]].map(|v| G::CE::commit(&ck, v));
+        assert_eq!(
+            snark.verify_with_memory(&vk, &running_U, &running_M, &comm_other),
+            Err(MyError::MemoryError)
+        );
+
+        // the memory is neither dropped nor taken with other challenges
+        assert_eq!(
+            RelaxedPLONKSNARK::<G, EE>::prove(&ck, &pk, &running_U, &running_W).err(),
+            Some(MyError::MemoryError)
+        );
+        assert!(snark.verify(&vk, &running_U).is_err());
+        let mut M = running_M.clone();
+        M.challenges[1] += <G as Group>::ScalarField::ONE;
+        assert!(snark
+            .verify_with_memory(&vk, &running_U, &M, &comm_init)
+            .is_err());
+
+        // an error term off the sum leaves a root other than zero
+        let mut MW = running_MW.clone();
+        MW.E[0][0] += <G as Group>::ScalarField::ONE;
+        assert_eq!(
+            RelaxedPLONKSNARK::<G, EE>::prove_with_memory(
+                &ck, &pk, &running_U, &running_W, &running_M, &MW
+            )
+            .err(),
+            Some(MyError::UnSatMemorySum)
+        );
+    }
+
+    #[test]
+    fn test_memory_snark() {
+        test_memory_snark_with::<Bn254, KZGEvaluationEngine<Bn254>>();
+        test_memory_snark_with::<Grumpkin, IPAEvaluationEngine<Grumpkin>>();
+    }
+}
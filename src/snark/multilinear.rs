@@ -0,0 +1,242 @@
+/// multilinear polynomials by their evaluations over the boolean hypercube, the i-th one at the bits of i with the
+/// lowest bit as the first variable, which is the very vector a commitment reads as the coefficients of a univariate
+/// polynomial
+///
+/// an evaluation of the multilinear extension reduces to univariate evaluations as in gemini: https://eprint.iacr.org/2022/420.pdf
+/// binding the first variable of f(X) = f_e(X^2) + X * f_o(X^2) to r gives f'(X) = (1 - r) * f_e(X) + r * f_o(X),
+/// so that f'(x^2) follows from f(x) and f(-x)
+///
+use ark_ff::Field;
+use rayon::prelude::*;
+
+use crate::{
+    error::MyError,
+    traits::{CommitmentEngineTrait, EvaluationEngineTrait, Group, TranscriptEngineTrait},
+    Commitment, CommitmentKey,
+};
+
+/// eq(r, x) for every x of the hypercube
+pub(crate) fn eq_table<F: Field>(r: &[F]) -> Vec<F> {
+    r.iter().fold(vec![F::ONE], |table, r_k| {
+        let high = table.iter().map(|t| *t * r_k).collect::<Vec<_>>();
+        let low = table
+            .iter()
+            .zip(&high)
+            .map(|(t, h)| *t - h)
+            .collect::<Vec<_>>();
+        [low, high].concat()
+    })
+}
+
+/// eq(r, x) = prod_k (r_k * x_k + (1 - r_k) * (1 - x_k))
+pub(crate) fn eq_eval<F: Field>(r: &[F], x: &[F]) -> F {
+    r.iter().zip(x).fold(F::ONE, |acc, (r_k, x_k)| {
+        acc * (*r_k * x_k + (F::ONE - r_k) * (F::ONE - x_k))
+    })
+}
+
+/// binds the first variable to r
+pub(crate) fn fold_first<F: Field>(table: &[F], r: &F) -> Vec<F> {
+    table
+        .par_chunks(2)
+        .map(|pair| pair[0] + *r * (pair[1] - pair[0]))
+        .collect()
+}
+
+/// the multilinear extension at r
+pub(crate) fn evaluate<F: Field>(table: &[F], r: &[F]) -> F {
+    r.iter()
+        .fold(table.to_vec(), |t, r_k| fold_first(&t, r_k))
+        .first()
+        .copied()
+        .unwrap_or(F::ZERO)
+}
+
+/// the univariate polynomial of the given coefficients at x
+fn evaluate_univariate<F: Field>(coeffs: &[F], x: &F) -> F {
+    coeffs.iter().rev().fold(F::ZERO, |acc, c| acc * x + c)
+}
+
+/// the commitments to f with its first k variables bound, for k = 1, ..., num_vars - 1, together with the univariate
+/// evaluations of the k-th one at x^{2^k} and -x^{2^k}
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MultilinearEvaluationArgument<G: Group, EE: EvaluationEngineTrait<G, CE = G::CE>> {
+    comm_folds: Vec<Commitment<G>>,
+    evals_pos: Vec<<G as Group>::ScalarField>,
+    evals_neg: Vec<<G as Group>::ScalarField>,
+    args: Vec<EE::EvaluationArgument>,
+}
+
+impl<G: Group, EE: EvaluationEngineTrait<G, CE = G::CE>> MultilinearEvaluationArgument<G, EE> {
+    /// binds the claim and the folded commitments into the transcript, and squeezes the univariate point
+    fn challenge(
+        transcript: &mut G::TE,
+        comm: &Commitment<G>,
+        point: &[<G as Group>::ScalarField],
+        eval: &<G as Group>::ScalarField,
+        comm_folds: &[Commitment<G>],
+    ) -> Result<<G as Group>::ScalarField, MyError> {
+        transcript.dom_sep(b"multilinear");
+        transcript.absorb(b"C", comm);
+        point.iter().for_each(|r| transcript.absorb(b"r", r));
+        transcript.absorb(b"y", eval);
+        comm_folds
+            .iter()
+            .for_each(|c| transcript.absorb(b"C_fold", c));
+        transcript.squeeze(b"x")
+    }
+
+    /// proves that the multilinear extension of the committed table takes eval at point
+    pub fn prove(
+        ck: &CommitmentKey<G>,
+        pk: &EE::ProverKey,
+        transcript: &mut G::TE,
+        comm: &Commitment<G>,
+        table: &[<G as Group>::ScalarField],
+        point: &[<G as Group>::ScalarField],
+        eval: &<G as Group>::ScalarField,
+    ) -> Result<Self, MyError> {
+        if point.is_empty() || table.len() != 1 << point.len() {
+            return Err(MyError::InvalidInputLength);
+        }
+
+        let folds = point[..point.len() - 1]
+            .iter()
+            .scan(table.to_vec(), |t, r| {
+                *t = fold_first(t, r);
+                Some(t.clone())
+            })
+            .collect::<Vec<_>>();
+        let comm_folds = folds
+            .iter()
+            .map(|f| G::CE::commit(ck, f))
+            .collect::<Vec<_>>();
+        let x = Self::challenge(transcript, comm, point, eval, &comm_folds)?;
+
+        let (mut evals_pos, mut evals_neg, mut args) = (vec![], vec![], vec![]);
+        let mut x_k = x;
+        for (f, c) in std::iter::once(table)
+            .chain(folds.iter().map(|f| f.as_slice()))
+            .zip(std::iter::once(comm).chain(&comm_folds))
+        {
+            for (x, evals) in [(x_k, &mut evals_pos), (-x_k, &mut evals_neg)] {
+                let y = evaluate_univariate(f, &x);
+                args.push(EE::prove(ck, pk, transcript, c, f, &x, &y)?);
+                evals.push(y);
+            }
+            x_k.square_in_place();
+        }
+
+        Ok(Self {
+            comm_folds,
+            evals_pos,
+            evals_neg,
+            args,
+        })
+    }
+
+    /// checks every folding step against the univariate evaluations, and these against the commitments
+    pub fn verify(
+        &self,
+        vk: &EE::VerifierKey,
+        transcript: &mut G::TE,
+        comm: &Commitment<G>,
+        point: &[<G as Group>::ScalarField],
+        eval: &<G as Group>::ScalarField,
+    ) -> Result<(), MyError> {
+        let num_vars = point.len();
+        if num_vars == 0
+            || self.comm_folds.len() != num_vars - 1
+            || self.evals_pos.len() != num_vars
+            || self.evals_neg.len() != num_vars
+            || self.args.len() != 2 * num_vars
+        {
+            return Err(MyError::InvalidPCS);
+        }
+        let x = Self::challenge(transcript, comm, point, eval, &self.comm_folds)?;
+
+        let two_inv = <G as Group>::ScalarField::from(2u64).inverse().unwrap();
+        let mut x_k = x;
+        for (k, r_k) in point.iter().enumerate() {
+            let c = if k == 0 {
+                comm
+            } else {
+                &self.comm_folds[k - 1]
+            };
+            let (pos, neg) = (self.evals_pos[k], self.evals_neg[k]);
+            EE::verify(vk, transcript, c, &x_k, &pos, &self.args[2 * k])?;
+            EE::verify(vk, transcript, c, &-x_k, &neg, &self.args[2 * k + 1])?;
+
+            // f_{k+1}(x^2) = (1 - r_k) * (f_k(x) + f_k(-x)) / 2 + r_k * (f_k(x) - f_k(-x)) / 2x
+            let x_inv = x_k.inverse().ok_or(MyError::InvalidPCS)?;
+            let even = (pos + neg) * two_inv;
+            let odd = (pos - neg) * two_inv * x_inv;
+            let next = even + *r_k * (odd - even);
+            let expected = if k + 1 < num_vars {
+                self.evals_pos[k + 1]
+            } else {
+                *eval
+            };
+            if next != expected {
+                return Err(MyError::InvalidPCS);
+            }
+            x_k.square_in_place();
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::{keccak::Keccak256Transcript, kzg::KZGEvaluationEngine};
+    use ark_bn254::Bn254;
+    use ark_ff::UniformRand;
+    use jf_utils::test_rng;
+
+    #[test]
+    fn test_multilinear_evaluation() {
+        type F = <Bn254 as Group>::ScalarField;
+        let rng = &mut test_rng();
+        let num_vars = 3;
+        let ck = <Bn254 as Group>::CE::setup(rng, 1 << num_vars);
+        let (pk, vk) = KZGEvaluationEngine::<Bn254>::setup(&ck);
+
+        let table = (0..1 << num_vars).map(|_| F::rand(rng)).collect::<Vec<_>>();
+        let point = (0..num_vars).map(|_| F::rand(rng)).collect::<Vec<_>>();
+        let eval = evaluate(&table, &point);
+        assert_eq!(
+            eval,
+            eq_table(&point)
+                .iter()
+                .zip(&table)
+                .map(|(e, t)| *e * t)
+                .sum::<F>()
+        );
+        let comm = <Bn254 as Group>::CE::commit(&ck, &table);
+
+        let mut transcript = Keccak256Transcript::<Bn254>::new(b"test");
+        let arg = MultilinearEvaluationArgument::<Bn254, KZGEvaluationEngine<Bn254>>::prove(
+            &ck,
+            &pk,
+            &mut transcript,
+            &comm,
+            &table,
+            &point,
+            &eval,
+        )
+        .unwrap();
+
+        let mut transcript = Keccak256Transcript::<Bn254>::new(b"test");
+        arg.verify(&vk, &mut transcript, &comm, &point, &eval)
+            .unwrap();
+
+        // a wrong evaluation is rejected
+        let mut transcript = Keccak256Transcript::<Bn254>::new(b"test");
+        assert_eq!(
+            arg.verify(&vk, &mut transcript, &comm, &point, &(eval + F::ONE)),
+            Err(MyError::InvalidPCS)
+        );
+    }
+}
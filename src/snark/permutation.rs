@@ -0,0 +1,127 @@
+/// the wiring by the fractional sums of GKR, as in https://eprint.iacr.org/2023/1284.pdf, in place of committed
+/// helper columns
+///
+/// the wiring holds iff Σ_c Σ_i 1 / (γ + w_c[i] + β * id_c[i]) - 1 / (γ + w_c[i] + β * σ_c[i]) = 0. the leaves are
+/// indexed by the row in the lowest variables and by the term in the highest ones, the id terms of all the columns
+/// first, then the σ terms, padded with 0 / 1
+///
+use ark_ff::{Field, PrimeField};
+use rayon::prelude::*;
+
+use super::{
+    gkr::FractionalSumProof,
+    multilinear::{eq_table, evaluate},
+};
+use crate::{
+    error::MyError,
+    traits::{Group, TranscriptEngineTrait},
+};
+
+/// the fractional sum of the wiring, together with the evaluations of the wires and of σ at the point of the rows
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct PermutationProof<F: Field> {
+    gkr: FractionalSumProof<F>,
+    pub(crate) evals: Vec<F>,
+}
+
+/// the number of variables selecting a term among the 2 * num_perm ones
+fn term_vars(num_perm: usize) -> usize {
+    (2 * num_perm).next_power_of_two().trailing_zeros() as usize
+}
+
+/// p and q of the c-th term from (w, id, σ) of the columns at one row
+fn term<F: Field>(
+    c: usize,
+    num_perm: usize,
+    column: impl Fn(usize) -> (F, F, F),
+    beta: &F,
+    gamma: &F,
+) -> (F, F) {
+    match c {
+        c if c < num_perm => {
+            let (w, id, _) = column(c);
+            (F::ONE, *gamma + w + *beta * id)
+        }
+        c if c < 2 * num_perm => {
+            let (w, _, sigma) = column(c - num_perm);
+            (-F::ONE, *gamma + w + *beta * sigma)
+        }
+        _ => (F::ZERO, F::ONE),
+    }
+}
+
+impl<F: PrimeField> PermutationProof<F> {
+    /// proves the wiring of the columns w against id and σ, of as many rows each, returning the point of the rows
+    /// the wires and σ are evaluated at
+    pub(crate) fn prove<G: Group<ScalarField = F>>(
+        transcript: &mut G::TE,
+        w: &[&[F]],
+        id: &[Vec<F>],
+        sigma: &[Vec<F>],
+        beta: &F,
+        gamma: &F,
+    ) -> Result<(Self, Vec<F>), MyError> {
+        let (n, num_perm) = (w[0].len(), w.len());
+        let (p, q): (Vec<F>, Vec<F>) = (0..n << term_vars(num_perm))
+            .into_par_iter()
+            .map(|i| {
+                let row = i % n;
+                let column = |c: usize| (w[c][row], id[c][row], sigma[c][row]);
+                term(i / n, num_perm, column, beta, gamma)
+            })
+            .unzip();
+
+        let (gkr, point, _, _) = FractionalSumProof::prove::<G>(transcript, p, q)?;
+        let point = point[..n.trailing_zeros() as usize].to_vec();
+        let evals = w
+            .iter()
+            .map(|c| evaluate(c, &point))
+            .chain(sigma.iter().map(|c| evaluate(c, &point)))
+            .collect::<Vec<_>>();
+        evals.iter().for_each(|e| transcript.absorb(b"eval", e));
+
+        Ok((Self { gkr, evals }, point))
+    }
+
+    /// checks the wiring down to the leaves, given id at the point of the rows as a function of that point,
+    /// returning the point of the rows and the evaluations of the wires and of σ there, which are left to the caller
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn verify<G: Group<ScalarField = F>>(
+        &self,
+        transcript: &mut G::TE,
+        num_vars: usize,
+        num_perm: usize,
+        id: impl Fn(&[F]) -> Vec<F>,
+        beta: &F,
+        gamma: &F,
+    ) -> Result<(Vec<F>, &[F]), MyError> {
+        if self.evals.len() != 2 * num_perm {
+            return Err(MyError::ProofVerifyError);
+        }
+        let (root, point, p, q) = self
+            .gkr
+            .verify::<G>(transcript, num_vars + term_vars(num_perm))?;
+        if root.0 != F::ZERO || root.1 == F::ZERO {
+            return Err(MyError::ProofVerifyError);
+        }
+        let (rows, z) = point.split_at(num_vars);
+        let (w, sigma) = self.evals.split_at(num_perm);
+        let id = id(rows);
+        let column = |c: usize| (w[c], id[c], sigma[c]);
+        let leaves = eq_table(z)
+            .iter()
+            .enumerate()
+            .fold((F::ZERO, F::ZERO), |(p, q), (c, e)| {
+                let (p_c, q_c) = term(c, num_perm, column, beta, gamma);
+                (p + *e * p_c, q + *e * q_c)
+            });
+        if leaves != (p, q) {
+            return Err(MyError::ProofVerifyError);
+        }
+        self.evals
+            .iter()
+            .for_each(|e| transcript.absorb(b"eval", e));
+
+        Ok((rows.to_vec(), &self.evals))
+    }
+}
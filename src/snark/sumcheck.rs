@@ -0,0 +1,159 @@
+/// The sumcheck protocol over the scalar field of a group, made non-interactive by its transcript engine
+///
+/// the prover holds the tables of multilinear polynomials f_1, ..., f_k over the hypercube of num_vars variables and
+/// a composition g of the given degree, and proves Σ_x g(f_1(x), ..., f_k(x)) = claim. every round binds the lowest
+/// remaining variable, sending the round polynomial by its evaluations at 0, 1, ..., degree, computed over the pairs
+/// of rows in parallel. the verifier is left with a claim on g at a random point, to be checked by the caller
+///
+use ark_ff::{Field, PrimeField};
+use rayon::prelude::*;
+
+use super::multilinear::fold_first;
+use crate::{
+    error::MyError,
+    traits::{Group, TranscriptEngineTrait},
+};
+
+/// the round polynomials of a sumcheck, each by its evaluations at 0, 1, ..., degree
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SumcheckProof<F: Field> {
+    polys: Vec<Vec<F>>,
+}
+
+/// the polynomial taking the given values at 0, 1, ..., evals.len() - 1, at x
+fn interpolate_at<F: Field>(evals: &[F], x: &F) -> F {
+    (0..evals.len()).fold(F::ZERO, |acc, j| {
+        let basis = (0..evals.len()).filter(|m| *m != j).fold(F::ONE, |b, m| {
+            b * (*x - F::from(m as u64))
+                * (F::from(j as u64) - F::from(m as u64)).inverse().unwrap()
+        });
+        acc + evals[j] * basis
+    })
+}
+
+impl<F: PrimeField> SumcheckProof<F> {
+    /// proves Σ_x compose(tables(x)) = claim, returning the point and the values of the tables there
+    pub fn prove<G: Group<ScalarField = F>>(
+        transcript: &mut G::TE,
+        mut tables: Vec<Vec<F>>,
+        degree: usize,
+        compose: impl Fn(&[F]) -> F + Sync,
+    ) -> Result<(Self, Vec<F>, Vec<F>), MyError> {
+        let (mut polys, mut point) = (vec![], vec![]);
+        while tables[0].len() > 1 {
+            let half = tables[0].len() / 2;
+            let poly = (0..half)
+                .into_par_iter()
+                .map(|j| {
+                    (0..=degree)
+                        .map(|t| {
+                            let t = F::from(t as u64);
+                            let v = tables
+                                .iter()
+                                .map(|table| table[2 * j] + t * (table[2 * j + 1] - table[2 * j]))
+                                .collect::<Vec<_>>();
+                            compose(&v)
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .reduce(
+                    || vec![F::ZERO; degree + 1],
+                    |a, b| a.iter().zip(b).map(|(a, b)| *a + b).collect(),
+                );
+            poly.iter().for_each(|e| transcript.absorb(b"p", e));
+            let r = transcript.squeeze(b"r")?;
+            tables = tables.iter().map(|table| fold_first(table, &r)).collect();
+            polys.push(poly);
+            point.push(r);
+        }
+        let values = tables.iter().map(|table| table[0]).collect();
+        Ok((Self { polys }, point, values))
+    }
+
+    /// reduces the claim to one on the composed polynomial at the returned point
+    pub fn verify<G: Group<ScalarField = F>>(
+        &self,
+        transcript: &mut G::TE,
+        claim: F,
+        num_vars: usize,
+        degree: usize,
+    ) -> Result<(F, Vec<F>), MyError> {
+        if self.polys.len() != num_vars || self.polys.iter().any(|p| p.len() != degree + 1) {
+            return Err(MyError::InvalidSumcheck);
+        }
+        let mut claim = claim;
+        let mut point = vec![];
+        for poly in &self.polys {
+            if poly[0] + poly[1] != claim {
+                return Err(MyError::InvalidSumcheck);
+            }
+            poly.iter().for_each(|e| transcript.absorb(b"p", e));
+            let r = transcript.squeeze(b"r")?;
+            claim = interpolate_at(poly, &r);
+            point.push(r);
+        }
+        Ok((claim, point))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{provider::grumpkin::Grumpkin, snark::multilinear::evaluate};
+    use ark_bn254::Bn254;
+    use ark_ff::UniformRand;
+    use jf_utils::test_rng;
+
+    fn test_sumcheck_with<G: Group>() {
+        let rng = &mut test_rng();
+        let num_vars = 4;
+        let tables = (0..3)
+            .map(|_| {
+                (0..1 << num_vars)
+                    .map(|_| <G as Group>::ScalarField::rand(rng))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let compose = |v: &[<G as Group>::ScalarField]| v[0] * v[1] * v[2] + v[0];
+        let claim = (0..1 << num_vars)
+            .map(|i| compose(&[tables[0][i], tables[1][i], tables[2][i]]))
+            .sum::<<G as Group>::ScalarField>();
+
+        let mut transcript = G::TE::new(b"test");
+        let (proof, point, values) =
+            SumcheckProof::prove::<G>(&mut transcript, tables.clone(), 3, compose).unwrap();
+        tables
+            .iter()
+            .zip(&values)
+            .for_each(|(t, v)| assert_eq!(evaluate(t, &point), *v));
+
+        let mut transcript = G::TE::new(b"test");
+        let (value, r) = proof
+            .verify::<G>(&mut transcript, claim, num_vars, 3)
+            .unwrap();
+        assert_eq!((value, r), (compose(&values), point));
+
+        // neither another claim nor another number of rounds verifies
+        let mut transcript = G::TE::new(b"test");
+        assert_eq!(
+            proof.verify::<G>(
+                &mut transcript,
+                claim + <G as Group>::ScalarField::ONE,
+                num_vars,
+                3
+            ),
+            Err(MyError::InvalidSumcheck)
+        );
+        let mut transcript = G::TE::new(b"test");
+        assert_eq!(
+            proof.verify::<G>(&mut transcript, claim, num_vars - 1, 3),
+            Err(MyError::InvalidSumcheck)
+        );
+    }
+
+    #[test]
+    fn test_sumcheck() {
+        test_sumcheck_with::<Bn254>();
+        test_sumcheck_with::<Grumpkin>();
+    }
+}
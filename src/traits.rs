@@ -1,15 +1,44 @@
-use ark_ec::pairing::Pairing;
-use ark_ff::PrimeField;
-use core::fmt::Debug;
+use ark_ec::{pairing::Pairing, CurveGroup};
+use ark_ff::{BigInteger, PrimeField};
+use core::{
+    fmt::Debug,
+    ops::{Add, Mul},
+};
 use rand::rngs::StdRng;
 
-pub trait Group: Sized + Pairing {
+use crate::error::MyError;
+
+/// A curve together with the engines the folding scheme runs on top of it,
+/// implemented by a marker type of the curve
+pub trait Group: Clone + Copy + Debug + PartialEq + Eq + Send + Sync + Sized + 'static {
     type BaseField: PrimeField;
     type ScalarField: PrimeField;
+    /// projective points of the curve
+    type Curve: CurveGroup<BaseField = Self::BaseField, ScalarField = Self::ScalarField>;
     type PreprocessedGroupElement: Clone + Debug;
     type RO: ROTrait<<Self as Group>::BaseField, <Self as Group>::ScalarField>;
     type CE: CommitmentEngineTrait<Self>;
+    type TE: TranscriptEngineTrait<Self>;
+}
+
+/// pairing support on top of `Group`, only needed by pairing-based commitment engines like KZG
+pub trait PairingGroup: Group {
+    type Pairing: Pairing<
+        G1 = Self::Curve,
+        G1Affine = <Self::Curve as CurveGroup>::Affine,
+        ScalarField = <Self as Group>::ScalarField,
+    >;
 }
+
+/// A 2-cycle of curves, the base field of each one is the scalar field of the other,
+/// so that each side can verify the folding of the other one natively
+pub trait CurveCycle: Clone + Copy + Debug + PartialEq + Eq + Send + Sync + 'static {
+    /// the primary curve
+    type G1: Group<BaseField = <Self::G2 as Group>::ScalarField>;
+    /// the secondary curve
+    type G2: Group<BaseField = <Self::G1 as Group>::ScalarField>;
+}
+
 pub trait ROConstantsTrait<BaseField> {
     /// produces constants/parameters associated with the hash function
     fn new(rate: usize) -> Self;
@@ -25,21 +54,114 @@ pub trait ROTrait<BaseField, ScalarField> {
     fn absorb(&mut self, e: BaseField);
 
     /// Returns a challenge of `num_bits` by hashing the internal state
-    fn squeeze(&mut self) -> ScalarField;
+    fn squeeze(&mut self, num_bits: usize) -> ScalarField;
+}
+
+/// A helper trait for types that can be absorbed into the random oracle of a group
+pub trait AbsorbInROTrait<G: Group> {
+    /// Absorbs the value in the provided RO
+    fn absorb_in_ro(&self, ro: &mut G::RO);
+}
+
+/// Defines basic operations on commitments
+pub trait CommitmentTrait<G: Group>:
+    Clone
+    + Debug
+    + Default
+    + PartialEq
+    + Eq
+    + Send
+    + Sync
+    + AbsorbInROTrait<G>
+    + TranscriptReprTrait<G>
+    + Add<Self, Output = Self>
+    + Mul<<G as Group>::ScalarField, Output = Self>
+{
+    /// affine coordinates of the commitment together with its infinity flag
+    fn to_coordinates(&self) -> (G::BaseField, G::BaseField, bool);
 }
 
 pub trait CommitmentEngineTrait<G: Group> {
     /// Holds the type of the commitment key
-    type CommitmentKey: Clone + Debug;
+    type CommitmentKey: Clone + Debug + Send + Sync;
 
     /// Holds the type of the commitment
-    type Commitment: Clone + Debug + PartialEq + Eq;
+    type Commitment: CommitmentTrait<G>;
 
     /// Samples a new commitment key of a specified size
     fn setup(rng: &mut StdRng, degree: usize) -> Self::CommitmentKey;
 
     /// Commits to the provided vector using the provided generators
-    fn commit(ck: &Self::CommitmentKey, v: &[<G as Pairing>::ScalarField]) -> Self::Commitment;
+    fn commit(ck: &Self::CommitmentKey, v: &[<G as Group>::ScalarField]) -> Self::Commitment;
+}
+
+/// A trait that ties a commitment engine to an evaluation argument, i.e. a proof that a committed vector,
+/// read as the coefficients of a univariate polynomial, evaluates to the claimed value at a point
+pub trait EvaluationEngineTrait<G: Group>: Clone + Send + Sync {
+    /// the commitment engine the argument opens
+    type CE: CommitmentEngineTrait<G>;
+
+    /// A type that holds the prover key
+    type ProverKey: Clone + Debug;
+
+    /// A type that holds the verifier key
+    type VerifierKey: Clone + Debug;
+
+    /// A type that holds the evaluation argument
+    type EvaluationArgument: Clone + Debug + PartialEq + Eq;
+
+    /// A method to perform any additional setup needed to produce proofs of evaluations
+    fn setup(
+        ck: &<Self::CE as CommitmentEngineTrait<G>>::CommitmentKey,
+    ) -> (Self::ProverKey, Self::VerifierKey);
+
+    /// A method to prove the evaluation of a committed polynomial
+    fn prove(
+        ck: &<Self::CE as CommitmentEngineTrait<G>>::CommitmentKey,
+        pk: &Self::ProverKey,
+        transcript: &mut G::TE,
+        comm: &<Self::CE as CommitmentEngineTrait<G>>::Commitment,
+        poly: &[<G as Group>::ScalarField],
+        point: &<G as Group>::ScalarField,
+        eval: &<G as Group>::ScalarField,
+    ) -> Result<Self::EvaluationArgument, MyError>;
+
+    /// A method to verify the purported evaluation of a committed polynomial
+    fn verify(
+        vk: &Self::VerifierKey,
+        transcript: &mut G::TE,
+        comm: &<Self::CE as CommitmentEngineTrait<G>>::Commitment,
+        point: &<G as Group>::ScalarField,
+        eval: &<G as Group>::ScalarField,
+        arg: &Self::EvaluationArgument,
+    ) -> Result<(), MyError>;
 }
 
-// pub trait CommitmentTrait<E: Group>: Clone + Debug + PartialEq + Eq {}
+/// This trait defines the behavior of a transcript engine, used in place of the random oracle by native provers and
+/// verifiers of arguments no circuit recomputes, the folding challenges stay on `RO` as the augmented circuit squeezes them
+pub trait TranscriptEngineTrait<G: Group>: Send + Sync {
+    /// initializes the transcript
+    fn new(label: &'static [u8]) -> Self;
+
+    /// returns a scalar element of the group as a challenge
+    fn squeeze(&mut self, label: &'static [u8]) -> Result<<G as Group>::ScalarField, MyError>;
+
+    /// absorbs any type that implements `TranscriptReprTrait` under a label
+    fn absorb<T: TranscriptReprTrait<G>>(&mut self, label: &'static [u8], o: &T);
+
+    /// adds a domain separator
+    fn dom_sep(&mut self, bytes: &'static [u8]);
+}
+
+/// A helper trait to absorb different objects into a transcript
+pub trait TranscriptReprTrait<G: Group>: Send + Sync {
+    /// returns a byte representation of self to be added to the transcript
+    fn to_transcript_bytes(&self) -> Vec<u8>;
+}
+
+/// field elements, both scalars and base-field elements, are absorbed as canonical little-endian bytes
+impl<G: Group, F: PrimeField> TranscriptReprTrait<G> for F {
+    fn to_transcript_bytes(&self) -> Vec<u8> {
+        self.into_bigint().to_bytes_le()
+    }
+}